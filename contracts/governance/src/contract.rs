@@ -0,0 +1,554 @@
+use cosmwasm_std::{
+    from_binary, log, to_binary, Api, Binary, CosmosMsg, Decimal, Env, Extern, HandleResponse,
+    HandleResult, HumanAddr, InitResponse, Querier, StdError, StdResult, Storage, Uint128, WasmMsg,
+};
+use secret_toolkit::snip20::HandleMsg as Snip20HandleMsg;
+
+use crate::state::{
+    read_config, read_proposal, read_staker, read_state, read_viewing_key, read_vote, store_config,
+    store_proposal, store_staker, store_state, store_viewing_key, store_vote, Config, Proposal,
+    State,
+};
+use prediction::{
+    governance::{
+        ConfigResponse, Cw20HookMsg, HandleMsg, InitMsg, ProposalMsg, ProposalResponse,
+        ProposalStatus, QueryMsg, StakerResponse, StateResponse, VoteOption,
+    },
+    utils::ContractInfo,
+    viewing_key::ViewingKey,
+};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+pub fn init<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: InitMsg,
+) -> StdResult<InitResponse> {
+    validate_rates(msg.quorum, msg.threshold)?;
+
+    let config = Config {
+        contract_addr: deps.api.canonical_address(&env.contract.address)?,
+        voting_token_addr: deps.api.canonical_address(&msg.voting_token.address)?,
+        voting_token_code_hash: msg.voting_token.code_hash.clone(),
+        quorum: msg.quorum,
+        threshold: msg.threshold,
+        voting_period: msg.voting_period,
+        timelock_period: msg.timelock_period,
+        expiration_period: msg.expiration_period,
+        proposal_min_stake: msg.proposal_min_stake,
+    };
+
+    store_config(&mut deps.storage, &config)?;
+
+    store_state(
+        &mut deps.storage,
+        &State {
+            proposal_count: 0,
+            total_staked: Uint128::zero(),
+        },
+    )?;
+
+    Ok(InitResponse {
+        messages: vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: msg.voting_token.address,
+            callback_code_hash: msg.voting_token.code_hash,
+            msg: to_binary(&Snip20HandleMsg::RegisterReceive {
+                code_hash: env.contract_code_hash,
+                padding: None,
+            })?,
+            send: vec![],
+        })],
+        log: vec![],
+    })
+}
+
+pub fn handle<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: HandleMsg,
+) -> HandleResult {
+    match msg {
+        HandleMsg::Receive { from, msg, amount } => receive_cw20(deps, env, from, amount, msg),
+        HandleMsg::Unstake { amount } => unstake(deps, env, amount),
+        HandleMsg::CreateProposal {
+            title,
+            description,
+            messages,
+        } => create_proposal(deps, env, title, description, messages),
+        HandleMsg::CastVote { id, vote } => cast_vote(deps, env, id, vote),
+        HandleMsg::EndVoting { id } => end_voting(deps, env, id),
+        HandleMsg::ExecuteProposal { id } => execute_proposal(deps, env, id),
+        HandleMsg::UpdateConfig {
+            quorum,
+            threshold,
+            voting_period,
+            timelock_period,
+            expiration_period,
+            proposal_min_stake,
+        } => update_config(
+            deps,
+            env,
+            quorum,
+            threshold,
+            voting_period,
+            timelock_period,
+            expiration_period,
+            proposal_min_stake,
+        ),
+        HandleMsg::SetViewingKey { key, .. } => set_viewing_key(deps, env, key),
+    }
+}
+
+fn receive_cw20<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    from: HumanAddr,
+    amount: Uint128,
+    msg: Option<Binary>,
+) -> HandleResult {
+    let bin_msg = msg.ok_or_else(|| StdError::generic_err("data should be given"))?;
+    let config = read_config(&deps.storage)?;
+
+    match from_binary(&bin_msg)? {
+        Cw20HookMsg::Stake {} => {
+            if deps.api.canonical_address(&env.message.sender)? != config.voting_token_addr {
+                return Err(StdError::generic_err("invalid asset"));
+            }
+            stake(deps, from, amount)
+        }
+    }
+}
+
+fn stake<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    user: HumanAddr,
+    amount: Uint128,
+) -> HandleResult {
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Amount is zero"));
+    }
+
+    let user_raw = deps.api.canonical_address(&user)?;
+    let mut staker = read_staker(&deps.storage, &user_raw)?;
+    let mut state = read_state(&deps.storage)?;
+
+    staker.balance = staker.balance + amount;
+    state.total_staked = state.total_staked + amount;
+
+    store_staker(&mut deps.storage, &user_raw, &staker)?;
+    store_state(&mut deps.storage, &state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "stake"),
+            log("user", user),
+            log("amount", amount),
+        ],
+        data: None,
+    })
+}
+
+fn unstake<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    amount: Option<Uint128>,
+) -> HandleResult {
+    let config = read_config(&deps.storage)?;
+    let user_raw = deps.api.canonical_address(&env.message.sender)?;
+    let mut staker = read_staker(&deps.storage, &user_raw)?;
+
+    let amount = amount.unwrap_or(staker.balance);
+    if amount.is_zero() || amount > staker.balance {
+        return Err(StdError::generic_err("Invalid unstake amount"));
+    }
+
+    // Votes are counted at tally time, so the stake must stay until voting ends
+    if staker.locked_until > env.block.time {
+        return Err(StdError::generic_err("Tokens are locked by votes"));
+    }
+
+    let mut state = read_state(&deps.storage)?;
+    staker.balance = (staker.balance - amount)?;
+    state.total_staked = (state.total_staked - amount)?;
+
+    store_staker(&mut deps.storage, &user_raw, &staker)?;
+    store_state(&mut deps.storage, &state)?;
+
+    Ok(HandleResponse {
+        messages: vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.voting_token_addr)?,
+            callback_code_hash: config.voting_token_code_hash,
+            msg: to_binary(&Snip20HandleMsg::Transfer {
+                recipient: env.message.sender.clone(),
+                amount,
+                memo: None,
+                padding: None,
+            })?,
+            send: vec![],
+        })],
+        log: vec![
+            log("action", "unstake"),
+            log("user", env.message.sender),
+            log("amount", amount),
+        ],
+        data: None,
+    })
+}
+
+fn create_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    title: String,
+    description: String,
+    messages: Vec<ProposalMsg>,
+) -> HandleResult {
+    let config = read_config(&deps.storage)?;
+    let proposer = deps.api.canonical_address(&env.message.sender)?;
+    let staker = read_staker(&deps.storage, &proposer)?;
+
+    if staker.balance < config.proposal_min_stake {
+        return Err(StdError::generic_err("Insufficient stake"));
+    }
+
+    if messages.is_empty() {
+        return Err(StdError::generic_err("No messages"));
+    }
+
+    let mut state = read_state(&deps.storage)?;
+    state.proposal_count += 1;
+
+    let proposal = Proposal {
+        id: state.proposal_count,
+        proposer,
+        title,
+        description,
+        messages,
+        status: ProposalStatus::Voting,
+        yes_votes: Uint128::zero(),
+        no_votes: Uint128::zero(),
+        end_time: env.block.time.saturating_add(config.voting_period),
+        timelock_period: config.timelock_period,
+        expiration_period: config.expiration_period,
+    };
+
+    store_proposal(&mut deps.storage, &proposal)?;
+    store_state(&mut deps.storage, &state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "create_proposal"),
+            log("id", proposal.id),
+            log("end_time", proposal.end_time),
+        ],
+        data: None,
+    })
+}
+
+fn cast_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    id: u64,
+    vote: VoteOption,
+) -> HandleResult {
+    let mut proposal = read_proposal(&deps.storage, id)?;
+
+    if proposal.status != ProposalStatus::Voting || env.block.time >= proposal.end_time {
+        return Err(StdError::generic_err("Voting is closed"));
+    }
+
+    let voter = deps.api.canonical_address(&env.message.sender)?;
+    if read_vote(&deps.storage, id, &voter)?.is_some() {
+        return Err(StdError::generic_err("Already voted"));
+    }
+
+    let mut staker = read_staker(&deps.storage, &voter)?;
+    if staker.balance.is_zero() {
+        return Err(StdError::generic_err("No voting power"));
+    }
+
+    match vote {
+        VoteOption::Yes => proposal.yes_votes = proposal.yes_votes + staker.balance,
+        VoteOption::No => proposal.no_votes = proposal.no_votes + staker.balance,
+    }
+    staker.locked_until = std::cmp::max(staker.locked_until, proposal.end_time);
+
+    store_vote(&mut deps.storage, id, &voter, &vote)?;
+    store_staker(&mut deps.storage, &voter, &staker)?;
+    store_proposal(&mut deps.storage, &proposal)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "cast_vote"),
+            log("id", id),
+            log("power", staker.balance),
+        ],
+        data: None,
+    })
+}
+
+fn end_voting<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    id: u64,
+) -> HandleResult {
+    let config = read_config(&deps.storage)?;
+    let state = read_state(&deps.storage)?;
+    let mut proposal = read_proposal(&deps.storage, id)?;
+
+    if proposal.status != ProposalStatus::Voting {
+        return Err(StdError::generic_err("Voting is closed"));
+    }
+
+    if env.block.time < proposal.end_time {
+        return Err(StdError::generic_err("Voting period not over"));
+    }
+
+    let total_votes = proposal.yes_votes + proposal.no_votes;
+    let quorum_reached =
+        !total_votes.is_zero() && total_votes >= state.total_staked * config.quorum;
+    let threshold_reached = proposal.yes_votes > total_votes * config.threshold;
+
+    proposal.status = if quorum_reached && threshold_reached {
+        ProposalStatus::Passed
+    } else {
+        ProposalStatus::Rejected
+    };
+
+    store_proposal(&mut deps.storage, &proposal)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "end_voting"),
+            log("id", id),
+            log("passed", proposal.status == ProposalStatus::Passed),
+        ],
+        data: None,
+    })
+}
+
+fn execute_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    id: u64,
+) -> HandleResult {
+    let mut proposal = read_proposal(&deps.storage, id)?;
+
+    if proposal.status != ProposalStatus::Passed {
+        return Err(StdError::generic_err("Proposal not passed"));
+    }
+
+    if env.block.time < proposal.execute_after() {
+        return Err(StdError::generic_err("Timelock not expired"));
+    }
+
+    if env.block.time > proposal.expires_at() {
+        return Err(StdError::generic_err("Proposal expired"));
+    }
+
+    proposal.status = ProposalStatus::Executed;
+    store_proposal(&mut deps.storage, &proposal)?;
+
+    let messages = proposal
+        .messages
+        .into_iter()
+        .map(|proposal_msg| {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: proposal_msg.contract.address,
+                callback_code_hash: proposal_msg.contract.code_hash,
+                msg: proposal_msg.msg,
+                send: vec![],
+            })
+        })
+        .collect();
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![log("action", "execute_proposal"), log("id", id)],
+        data: None,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_config<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    quorum: Option<Decimal>,
+    threshold: Option<Decimal>,
+    voting_period: Option<u64>,
+    timelock_period: Option<u64>,
+    expiration_period: Option<u64>,
+    proposal_min_stake: Option<Uint128>,
+) -> HandleResult {
+    let mut config = read_config(&deps.storage)?;
+
+    // Only executed proposals can change the governance parameters
+    if deps.api.canonical_address(&env.message.sender)? != config.contract_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    if let Some(quorum) = quorum {
+        config.quorum = quorum;
+    }
+
+    if let Some(threshold) = threshold {
+        config.threshold = threshold;
+    }
+
+    validate_rates(config.quorum, config.threshold)?;
+
+    if let Some(voting_period) = voting_period {
+        config.voting_period = voting_period;
+    }
+
+    if let Some(timelock_period) = timelock_period {
+        config.timelock_period = timelock_period;
+    }
+
+    if let Some(expiration_period) = expiration_period {
+        config.expiration_period = expiration_period;
+    }
+
+    if let Some(proposal_min_stake) = proposal_min_stake {
+        config.proposal_min_stake = proposal_min_stake;
+    }
+
+    store_config(&mut deps.storage, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "update_config")],
+        data: None,
+    })
+}
+
+fn set_viewing_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    key: String,
+) -> HandleResult {
+    let vk = ViewingKey(key);
+
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+
+    store_viewing_key(&mut deps.storage, &message_sender, &vk)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "set_viewing_key"), log("success", true)],
+        data: None,
+    })
+}
+
+fn validate_rates(quorum: Decimal, threshold: Decimal) -> StdResult<()> {
+    if quorum > Decimal::one() || threshold > Decimal::one() {
+        return Err(StdError::generic_err("Invalid rate"));
+    }
+
+    Ok(())
+}
+
+pub fn query<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    msg: QueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::State {} => to_binary(&query_state(deps)?),
+        QueryMsg::Proposal { id } => to_binary(&query_proposal(deps, id)?),
+        QueryMsg::Proposals { start_after, limit } => {
+            to_binary(&query_proposals(deps, start_after, limit)?)
+        }
+        QueryMsg::Staker { address, key } => to_binary(&query_staker(deps, address, key)?),
+    }
+}
+
+fn query_config<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ConfigResponse> {
+    let config = read_config(&deps.storage)?;
+
+    Ok(ConfigResponse {
+        voting_token: ContractInfo {
+            address: deps.api.human_address(&config.voting_token_addr)?,
+            code_hash: config.voting_token_code_hash,
+        },
+        quorum: config.quorum,
+        threshold: config.threshold,
+        voting_period: config.voting_period,
+        timelock_period: config.timelock_period,
+        expiration_period: config.expiration_period,
+        proposal_min_stake: config.proposal_min_stake,
+    })
+}
+
+fn query_state<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<StateResponse> {
+    let state = read_state(&deps.storage)?;
+
+    Ok(StateResponse {
+        proposal_count: state.proposal_count,
+        total_staked: state.total_staked,
+    })
+}
+
+fn query_proposal<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    id: u64,
+) -> StdResult<ProposalResponse> {
+    let proposal = read_proposal(&deps.storage, id)?;
+
+    Ok(ProposalResponse {
+        id: proposal.id,
+        proposer: deps.api.human_address(&proposal.proposer)?,
+        execute_after: proposal.execute_after(),
+        title: proposal.title,
+        description: proposal.description,
+        messages: proposal.messages,
+        status: proposal.status,
+        yes_votes: proposal.yes_votes,
+        no_votes: proposal.no_votes,
+        end_time: proposal.end_time,
+    })
+}
+
+fn query_proposals<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<ProposalResponse>> {
+    let count = read_state(&deps.storage)?.proposal_count;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.unwrap_or(0) + 1;
+
+    (start..=count)
+        .take(limit)
+        .map(|id| query_proposal(deps, id))
+        .collect()
+}
+
+fn query_staker<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: HumanAddr,
+    key: String,
+) -> StdResult<StakerResponse> {
+    let user_raw = deps.api.canonical_address(&address)?;
+    let is_valid = match read_viewing_key(&deps.storage, &user_raw)? {
+        Some(hashed_key) => ViewingKey(key).check_viewing_key(hashed_key.as_slice()),
+        None => false,
+    };
+    if !is_valid {
+        return Err(StdError::generic_err("Invalid viewing key"));
+    }
+
+    let staker = read_staker(&deps.storage, &user_raw)?;
+
+    Ok(StakerResponse {
+        balance: staker.balance,
+        locked_until: staker.locked_until,
+    })
+}
@@ -0,0 +1,134 @@
+use cosmwasm_std::{Binary, CanonicalAddr, Decimal, StdResult, Storage, Uint128};
+use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use prediction::{
+    governance::{ProposalMsg, ProposalStatus, VoteOption},
+    viewing_key::ViewingKey,
+};
+
+static KEY_CONFIG: &[u8] = b"config";
+static KEY_STATE: &[u8] = b"state";
+
+static PREFIX_PROPOSAL: &[u8] = b"proposal";
+static PREFIX_STAKER: &[u8] = b"staker";
+static PREFIX_VOTE: &[u8] = b"vote";
+static PREFIX_VIEW_KEY: &[u8] = b"viewingkey";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub contract_addr: CanonicalAddr,
+    pub voting_token_addr: CanonicalAddr,
+    pub voting_token_code_hash: String,
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub voting_period: u64,
+    pub timelock_period: u64,
+    pub expiration_period: u64,
+    pub proposal_min_stake: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    pub proposal_count: u64,
+    pub total_staked: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: CanonicalAddr,
+    pub title: String,
+    pub description: String,
+    pub messages: Vec<ProposalMsg>,
+    pub status: ProposalStatus,
+    pub yes_votes: Uint128,
+    pub no_votes: Uint128,
+    pub end_time: u64,
+    /// Timelock and expiration are fixed when the proposal is created
+    pub timelock_period: u64,
+    pub expiration_period: u64,
+}
+
+impl Proposal {
+    pub fn execute_after(&self) -> u64 {
+        self.end_time.saturating_add(self.timelock_period)
+    }
+
+    pub fn expires_at(&self) -> u64 {
+        self.execute_after().saturating_add(self.expiration_period)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Staker {
+    pub balance: Uint128,
+    /// End time of the last proposal voted on
+    pub locked_until: u64,
+}
+
+pub fn store_config<S: Storage>(storage: &mut S, data: &Config) -> StdResult<()> {
+    Singleton::new(storage, KEY_CONFIG).save(data)
+}
+pub fn read_config<S: Storage>(storage: &S) -> StdResult<Config> {
+    ReadonlySingleton::new(storage, KEY_CONFIG).load()
+}
+
+pub fn store_state<S: Storage>(storage: &mut S, data: &State) -> StdResult<()> {
+    Singleton::new(storage, KEY_STATE).save(data)
+}
+pub fn read_state<S: Storage>(storage: &S) -> StdResult<State> {
+    ReadonlySingleton::new(storage, KEY_STATE).load()
+}
+
+pub fn store_proposal<S: Storage>(storage: &mut S, data: &Proposal) -> StdResult<()> {
+    Bucket::new(PREFIX_PROPOSAL, storage).save(&data.id.to_be_bytes(), data)
+}
+pub fn read_proposal<S: Storage>(storage: &S, id: u64) -> StdResult<Proposal> {
+    ReadonlyBucket::new(PREFIX_PROPOSAL, storage).load(&id.to_be_bytes())
+}
+
+pub fn store_staker<S: Storage>(
+    storage: &mut S,
+    user: &CanonicalAddr,
+    data: &Staker,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_STAKER, storage).save(user.as_slice(), data)
+}
+pub fn read_staker<S: Storage>(storage: &S, user: &CanonicalAddr) -> StdResult<Staker> {
+    Ok(ReadonlyBucket::new(PREFIX_STAKER, storage)
+        .may_load(user.as_slice())?
+        .unwrap_or_default())
+}
+
+pub fn store_vote<S: Storage>(
+    storage: &mut S,
+    id: u64,
+    user: &CanonicalAddr,
+    vote: &VoteOption,
+) -> StdResult<()> {
+    Bucket::multilevel(&[PREFIX_VOTE, &id.to_be_bytes()], storage).save(user.as_slice(), vote)
+}
+pub fn read_vote<S: Storage>(
+    storage: &S,
+    id: u64,
+    user: &CanonicalAddr,
+) -> StdResult<Option<VoteOption>> {
+    ReadonlyBucket::multilevel(&[PREFIX_VOTE, &id.to_be_bytes()], storage).may_load(user.as_slice())
+}
+
+pub fn store_viewing_key<S: Storage>(
+    storage: &mut S,
+    user: &CanonicalAddr,
+    key: &ViewingKey,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_VIEW_KEY, storage)
+        .save(&user.as_slice(), &Binary::from(&key.to_hashed()[..]))
+}
+pub fn read_viewing_key<S: Storage>(
+    storage: &S,
+    user: &CanonicalAddr,
+) -> StdResult<Option<Binary>> {
+    ReadonlyBucket::new(PREFIX_VIEW_KEY, storage).may_load(&user.as_slice())
+}
@@ -0,0 +1,397 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_env, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{from_binary, log, to_binary, CosmosMsg, HumanAddr, StdError, Uint128, WasmMsg};
+use secret_toolkit::snip20::HandleMsg as Snip20HandleMsg;
+
+use prediction::governance::{
+    ConfigResponse, HandleMsg, ProposalResponse, ProposalStatus, QueryMsg, StakerResponse,
+    VoteOption,
+};
+
+use crate::{
+    contract::{handle, query},
+    tests::test_utils::{
+        cast_vote, create_proposal, fee_rate_proposal_msg, init_governance, stake,
+    },
+};
+
+#[test]
+fn test_update_config_failed_if_not_executed_by_proposal() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_governance(&mut deps);
+
+    let msg = HandleMsg::UpdateConfig {
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: Some(0),
+        expiration_period: None,
+        proposal_min_stake: None,
+    };
+
+    let res = handle(&mut deps, mock_env("creator", &[]), msg.clone());
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    handle(&mut deps, mock_env(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+
+    let res = query(&deps, QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.timelock_period, 0);
+}
+
+#[test]
+fn test_proposal_executed_after_timelock() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_governance(&mut deps);
+    stake(&mut deps, "alice", 600);
+    stake(&mut deps, "bob", 400);
+
+    let mut env = mock_env("alice", &[]);
+    let start_time = env.block.time;
+    handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::CreateProposal {
+            title: "Lower fee".to_string(),
+            description: "Set fee rate to 2%".to_string(),
+            messages: vec![fee_rate_proposal_msg()],
+        },
+    )
+    .unwrap();
+
+    handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::CastVote {
+            id: 1,
+            vote: VoteOption::Yes,
+        },
+    )
+    .unwrap();
+
+    let res = handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::CastVote {
+            id: 1,
+            vote: VoteOption::No,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Already voted"), res);
+
+    let mut bob_env = mock_env("bob", &[]);
+    handle(
+        &mut deps,
+        bob_env.clone(),
+        HandleMsg::CastVote {
+            id: 1,
+            vote: VoteOption::No,
+        },
+    )
+    .unwrap();
+
+    // Voters can not withdraw before voting ends
+    let res = handle(
+        &mut deps,
+        bob_env.clone(),
+        HandleMsg::Unstake { amount: None },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Tokens are locked by votes"), res);
+
+    let res = handle(&mut deps, env.clone(), HandleMsg::EndVoting { id: 1 }).unwrap_err();
+    assert_eq!(StdError::generic_err("Voting period not over"), res);
+
+    env.block.time = start_time + 1000;
+    let res = handle(&mut deps, env.clone(), HandleMsg::EndVoting { id: 1 }).unwrap();
+    assert_eq!(res.log[2], log("passed", true));
+
+    let res = handle(&mut deps, env.clone(), HandleMsg::ExecuteProposal { id: 1 }).unwrap_err();
+    assert_eq!(StdError::generic_err("Timelock not expired"), res);
+
+    let res = query(&deps, QueryMsg::Proposal { id: 1 }).unwrap();
+    let proposal: ProposalResponse = from_binary(&res).unwrap();
+    assert_eq!(proposal.status, ProposalStatus::Passed);
+    assert_eq!(proposal.yes_votes, Uint128(600));
+    assert_eq!(proposal.no_votes, Uint128(400));
+    assert_eq!(proposal.execute_after, start_time + 1500);
+
+    env.block.time = start_time + 1500;
+    let res = handle(&mut deps, env.clone(), HandleMsg::ExecuteProposal { id: 1 }).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("prediction"),
+            callback_code_hash: "prediction_code_hash".to_string(),
+            msg: fee_rate_proposal_msg().msg,
+            send: vec![],
+        })]
+    );
+
+    let res = handle(&mut deps, env, HandleMsg::ExecuteProposal { id: 1 }).unwrap_err();
+    assert_eq!(StdError::generic_err("Proposal not passed"), res);
+
+    bob_env.block.time = start_time + 1000;
+    let res = handle(&mut deps, bob_env, HandleMsg::Unstake { amount: None }).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("voting_token"),
+            callback_code_hash: "voting_token_code_hash".to_string(),
+            msg: to_binary(&Snip20HandleMsg::Transfer {
+                recipient: HumanAddr::from("bob"),
+                amount: Uint128(400),
+                memo: None,
+                padding: None,
+            })
+            .unwrap(),
+            send: vec![],
+        })]
+    );
+
+    handle(
+        &mut deps,
+        mock_env("bob", &[]),
+        HandleMsg::SetViewingKey {
+            key: "bob_key".to_string(),
+            padding: None,
+        },
+    )
+    .unwrap();
+    let res = query(
+        &deps,
+        QueryMsg::Staker {
+            address: HumanAddr::from("bob"),
+            key: "bob_key".to_string(),
+        },
+    )
+    .unwrap();
+    let staker: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(staker.balance, Uint128::zero());
+}
+
+#[test]
+fn test_proposal_rejected_without_quorum() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_governance(&mut deps);
+    stake(&mut deps, "alice", 200);
+    stake(&mut deps, "bob", 800);
+
+    let mut env = mock_env("alice", &[]);
+    handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::CreateProposal {
+            title: "Lower fee".to_string(),
+            description: "Set fee rate to 2%".to_string(),
+            messages: vec![fee_rate_proposal_msg()],
+        },
+    )
+    .unwrap();
+    handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::CastVote {
+            id: 1,
+            vote: VoteOption::Yes,
+        },
+    )
+    .unwrap();
+
+    env.block.time += 1000;
+    let res = handle(&mut deps, env.clone(), HandleMsg::EndVoting { id: 1 }).unwrap();
+    assert_eq!(res.log[2], log("passed", false));
+
+    env.block.time += 500;
+    let res = handle(&mut deps, env, HandleMsg::ExecuteProposal { id: 1 }).unwrap_err();
+    assert_eq!(StdError::generic_err("Proposal not passed"), res);
+}
+
+#[test]
+fn test_cast_vote_failed_if_already_voted() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_governance(&mut deps);
+    stake(&mut deps, "alice", 600);
+
+    let env = mock_env("alice", &[]);
+    create_proposal(&mut deps, env.clone());
+    cast_vote(&mut deps, env.clone(), 1, VoteOption::Yes);
+
+    // Stake added after voting does not allow a second vote
+    stake(&mut deps, "alice", 400);
+    for vote in [VoteOption::Yes, VoteOption::No].iter() {
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            HandleMsg::CastVote {
+                id: 1,
+                vote: vote.clone(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(StdError::generic_err("Already voted"), res);
+    }
+
+    let res = query(&deps, QueryMsg::Proposal { id: 1 }).unwrap();
+    let proposal: ProposalResponse = from_binary(&res).unwrap();
+    assert_eq!(proposal.yes_votes, Uint128(600));
+    assert_eq!(proposal.no_votes, Uint128::zero());
+}
+
+#[test]
+fn test_unstake_failed_while_locked_by_votes() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_governance(&mut deps);
+    stake(&mut deps, "alice", 600);
+    stake(&mut deps, "bob", 400);
+
+    let mut env = mock_env("bob", &[]);
+    let end_time = env.block.time + 1000;
+    create_proposal(&mut deps, mock_env("alice", &[]));
+    cast_vote(&mut deps, env.clone(), 1, VoteOption::No);
+
+    env.block.time = end_time - 1;
+    let res = handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::Unstake {
+            amount: Some(Uint128(1)),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Tokens are locked by votes"), res);
+
+    // Alice did not vote and can leave at any time
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &[]),
+        HandleMsg::Unstake {
+            amount: Some(Uint128(100)),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.log[2], log("amount", 100));
+
+    env.block.time = end_time;
+    let res = handle(&mut deps, env, HandleMsg::Unstake { amount: None }).unwrap();
+    assert_eq!(res.log[2], log("amount", 400));
+}
+
+#[test]
+fn test_execute_proposal_failed_if_expired() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_governance(&mut deps);
+    stake(&mut deps, "alice", 600);
+
+    let mut env = mock_env("alice", &[]);
+    let start_time = env.block.time;
+    create_proposal(&mut deps, env.clone());
+    cast_vote(&mut deps, env.clone(), 1, VoteOption::Yes);
+
+    env.block.time = start_time + 1000;
+    handle(&mut deps, env.clone(), HandleMsg::EndVoting { id: 1 }).unwrap();
+
+    env.block.time = start_time + 1000 + 500 + 2000 + 1;
+    let res = handle(&mut deps, env, HandleMsg::ExecuteProposal { id: 1 }).unwrap_err();
+    assert_eq!(StdError::generic_err("Proposal expired"), res);
+
+    let res = query(&deps, QueryMsg::Proposal { id: 1 }).unwrap();
+    let proposal: ProposalResponse = from_binary(&res).unwrap();
+    assert_eq!(proposal.status, ProposalStatus::Passed);
+}
+
+#[test]
+fn test_end_voting_at_quorum_and_threshold() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_governance(&mut deps);
+    stake(&mut deps, "alice", 300);
+    stake(&mut deps, "bob", 300);
+    stake(&mut deps, "carol", 400);
+
+    let mut env = mock_env("alice", &[]);
+    let start_time = env.block.time;
+
+    // Votes of exactly the 30% quorum pass
+    create_proposal(&mut deps, env.clone());
+    cast_vote(&mut deps, env.clone(), 1, VoteOption::Yes);
+
+    // Yes votes of exactly the 50% threshold do not pass
+    create_proposal(&mut deps, env.clone());
+    cast_vote(&mut deps, env.clone(), 2, VoteOption::Yes);
+    cast_vote(&mut deps, mock_env("bob", &[]), 2, VoteOption::No);
+
+    env.block.time = start_time + 1000;
+    let res = handle(&mut deps, env.clone(), HandleMsg::EndVoting { id: 1 }).unwrap();
+    assert_eq!(res.log[2], log("passed", true));
+    let res = handle(&mut deps, env, HandleMsg::EndVoting { id: 2 }).unwrap();
+    assert_eq!(res.log[2], log("passed", false));
+}
+
+#[test]
+fn test_proposal_with_unbounded_timelock() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_governance(&mut deps);
+    stake(&mut deps, "alice", 600);
+
+    let msg = HandleMsg::UpdateConfig {
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: Some(u64::MAX),
+        expiration_period: None,
+        proposal_min_stake: None,
+    };
+    handle(&mut deps, mock_env(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+
+    let mut env = mock_env("alice", &[]);
+    create_proposal(&mut deps, env.clone());
+    cast_vote(&mut deps, env.clone(), 1, VoteOption::Yes);
+
+    env.block.time += 1000;
+    handle(&mut deps, env.clone(), HandleMsg::EndVoting { id: 1 }).unwrap();
+
+    let res = query(&deps, QueryMsg::Proposal { id: 1 }).unwrap();
+    let proposal: ProposalResponse = from_binary(&res).unwrap();
+    assert_eq!(proposal.execute_after, u64::MAX);
+
+    let res = handle(&mut deps, env, HandleMsg::ExecuteProposal { id: 1 }).unwrap_err();
+    assert_eq!(StdError::generic_err("Timelock not expired"), res);
+}
+
+#[test]
+fn test_query_staker_failed_if_key_is_invalid() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_governance(&mut deps);
+    stake(&mut deps, "alice", 600);
+
+    let query_msg = QueryMsg::Staker {
+        address: HumanAddr::from("alice"),
+        key: "alice_key".to_string(),
+    };
+    let res = query(&deps, query_msg.clone()).unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid viewing key"), res);
+
+    handle(
+        &mut deps,
+        mock_env("alice", &[]),
+        HandleMsg::SetViewingKey {
+            key: "other_key".to_string(),
+            padding: None,
+        },
+    )
+    .unwrap();
+    let res = query(&deps, query_msg).unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid viewing key"), res);
+}
@@ -0,0 +1,2 @@
+pub mod contract_test;
+pub mod test_utils;
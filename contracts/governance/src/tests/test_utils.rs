@@ -0,0 +1,79 @@
+use cosmwasm_std::testing::{mock_env, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{to_binary, Decimal, Env, Extern, HumanAddr, Uint128};
+
+use prediction::{
+    governance::{Cw20HookMsg, HandleMsg, InitMsg, ProposalMsg, VoteOption},
+    prediction::HandleMsg as PredictionHandleMsg,
+    utils::ContractInfo,
+};
+
+use crate::contract::{handle, init};
+
+pub fn init_governance(deps: &mut Extern<MockStorage, MockApi, MockQuerier>) {
+    let msg = InitMsg {
+        voting_token: ContractInfo {
+            address: HumanAddr::from("voting_token"),
+            code_hash: "voting_token_code_hash".to_string(),
+        },
+        quorum: Decimal::percent(30),
+        threshold: Decimal::percent(50),
+        voting_period: 1000,
+        timelock_period: 500,
+        expiration_period: 2000,
+        proposal_min_stake: Uint128(100),
+    };
+
+    let env = mock_env("creator", &[]);
+
+    init(deps, env, msg).unwrap();
+}
+
+pub fn stake(deps: &mut Extern<MockStorage, MockApi, MockQuerier>, user: &str, amount: u128) {
+    let msg = HandleMsg::Receive {
+        from: HumanAddr::from(user),
+        msg: Some(to_binary(&Cw20HookMsg::Stake {}).unwrap()),
+        amount: Uint128(amount),
+    };
+
+    handle(deps, mock_env("voting_token", &[]), msg).unwrap();
+}
+
+pub fn fee_rate_proposal_msg() -> ProposalMsg {
+    ProposalMsg {
+        contract: ContractInfo {
+            address: HumanAddr::from("prediction"),
+            code_hash: "prediction_code_hash".to_string(),
+        },
+        msg: to_binary(&PredictionHandleMsg::UpdateConfig {
+            owner_addr: None,
+            operator_addr: None,
+            treasury_addr: None,
+            oracle_addr: None,
+            oracle_code_hash: None,
+            fee_rate: Some(Decimal::percent(2)),
+            interval: None,
+            grace_interval: None,
+        })
+        .unwrap(),
+    }
+}
+
+/// Create the next proposal at the time of `env`
+pub fn create_proposal(deps: &mut Extern<MockStorage, MockApi, MockQuerier>, env: Env) {
+    let msg = HandleMsg::CreateProposal {
+        title: "Lower fee".to_string(),
+        description: "Set fee rate to 2%".to_string(),
+        messages: vec![fee_rate_proposal_msg()],
+    };
+
+    handle(deps, env, msg).unwrap();
+}
+
+pub fn cast_vote(
+    deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+    env: Env,
+    id: u64,
+    vote: VoteOption,
+) {
+    handle(deps, env, HandleMsg::CastVote { id, vote }).unwrap();
+}
@@ -3,7 +3,12 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use prediction::oracle::{ConfigResponse, HandleMsg, InitMsg, QueryMsg};
+use prediction::oracle::{
+    AssetResponse, AssetsResponse, ConfigResponse, ConsumerResponse, CrossRateResponse,
+    FeederKeyResponse, FeederRewardsResponse, FeedersResponse, HandleMsg, InitMsg,
+    LatestPriceResponse, LatestPricesResponse, PriceHistoryResponse, PriceHookMsg,
+    PriceStatusResponse, QueryMsg, RoundData, SubscriptionsResponse,
+};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -14,5 +19,19 @@ fn main() {
     export_schema(&schema_for!(InitMsg), &out_dir);
     export_schema(&schema_for!(HandleMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(AssetResponse), &out_dir);
+    export_schema(&schema_for!(AssetsResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(ConsumerResponse), &out_dir);
+    export_schema(&schema_for!(CrossRateResponse), &out_dir);
+    export_schema(&schema_for!(FeederKeyResponse), &out_dir);
+    export_schema(&schema_for!(FeederRewardsResponse), &out_dir);
+    export_schema(&schema_for!(FeedersResponse), &out_dir);
+    export_schema(&schema_for!(LatestPriceResponse), &out_dir);
+    export_schema(&schema_for!(LatestPricesResponse), &out_dir);
+    export_schema(&schema_for!(PriceHistoryResponse), &out_dir);
+    export_schema(&schema_for!(PriceHookMsg), &out_dir);
+    export_schema(&schema_for!(PriceStatusResponse), &out_dir);
+    export_schema(&schema_for!(RoundData), &out_dir);
+    export_schema(&schema_for!(SubscriptionsResponse), &out_dir);
 }
@@ -1,23 +1,81 @@
 use cosmwasm_std::{
-    log, to_binary, Api, Binary, Decimal, Env, Extern, HandleResponse, HandleResult, HumanAddr,
-    InitResponse, Querier, StdError, StdResult, Storage,
+    from_binary, log, to_binary, Api, Binary, CanonicalAddr, CosmosMsg, Decimal, Env, Extern,
+    HandleResponse, HandleResult, HumanAddr, InitResponse, LogAttribute, Querier, StdError,
+    StdResult, Storage, Uint128, WasmMsg,
 };
+use k256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use secret_toolkit::permit::{validate, RevokedPermits};
+use std::convert::TryFrom;
 
 use crate::state::{
-    read_config, read_feeder, read_price_info, store_config, store_feeder, store_price_info, Config,
+    push_price_history, read_asset, read_asset_keys, read_asset_paused, read_config, read_consumer,
+    read_feeder_key, read_feeder_nonce, read_feeder_rewards, read_feeders, read_heartbeat,
+    read_history_range, read_latest_time, read_pending_price, read_price_bounds,
+    read_price_history, read_price_info, read_subscriptions, read_unclaimed_rewards,
+    read_viewing_key, remove_asset, remove_asset_paused, remove_feeder_key, remove_feeder_rewards,
+    remove_feeders, remove_heartbeat, remove_pending_price, remove_price_bounds, remove_price_info,
+    remove_subscriptions, store_asset, store_asset_keys, store_asset_paused, store_config,
+    store_consumer, store_feeder_key, store_feeder_nonce, store_feeder_rewards, store_feeders,
+    store_heartbeat, store_latest_time, store_pending_price, store_price_bounds, store_price_info,
+    store_subscriptions, store_unclaimed_rewards, store_viewing_key, AccessFeeRaw, AssetRecord,
+    Config, HistoryRange, PendingPriceRaw, SubscriptionRaw, PREFIX_REVOKED_PERMITS,
 };
 use prediction::{
-    asset::AssetInfo,
-    oracle::{ConfigResponse, HandleMsg, InitMsg, PriceInfo, QueryMsg},
+    asset::{Asset, AssetInfo, AssetInfoRaw},
+    math::{decimal_div, decimal_sub, decimal_to_atomics},
+    oracle::{
+        AccessFee, AssetMetadata, AssetResponse, AssetsResponse, ConfigResponse, ConsumerResponse,
+        CrossRateResponse, Cw20HookMsg, FeederKeyResponse, FeederRewardsResponse, FeedersResponse,
+        HandleMsg, InitMsg, LatestPriceResponse, LatestPriceResult, LatestPricesResponse,
+        PendingPrice, PriceBounds, PriceHistoryResponse, PriceHookMsg, PriceInfo, PriceStatus,
+        PriceStatusResponse, QueryMsg, RoundData, SignedPriceReport, Subscription,
+        SubscriptionsResponse, FEED_PAUSED,
+    },
+    viewing_key::ViewingKey,
 };
 
+const DEFAULT_HISTORY_RETENTION: u64 = 100;
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+/// Seconds a signed report can be relayed after its timestamp
+const MAX_REPORT_DELAY: u64 = 300;
+/// Contracts hooked per asset, every new round of the asset executes all of them
+const MAX_SUBSCRIPTIONS: usize = 5;
+
+/// Lower median of the fresh submissions of an asset
+struct AggregatedPrice {
+    price: Decimal,
+    /// Feeder of the median submission
+    feeder: CanonicalAddr,
+    /// Time of the oldest fresh submission
+    started_at: u64,
+    /// Time of the latest submission
+    updated_at: u64,
+}
+
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    env: Env,
     msg: InitMsg,
 ) -> StdResult<InitResponse> {
     let config = Config {
+        contract_addr: deps.api.canonical_address(&env.contract.address)?,
         owner: deps.api.canonical_address(&msg.owner)?,
+        history_retention: validate_history_retention(
+            msg.history_retention.unwrap_or(DEFAULT_HISTORY_RETENTION),
+        )?,
+        quorum: validate_quorum(msg.quorum.unwrap_or(1))?,
+        max_price_age: msg.max_price_age,
+        guardian: match msg.guardian {
+            Some(guardian) => Some(deps.api.canonical_address(&guardian)?),
+            None => None,
+        },
+        paused: false,
+        restrict_reads: msg.restrict_reads.unwrap_or(false),
+        access_fee: match msg.access_fee {
+            Some(access_fee) => Some(to_access_fee_raw(deps, access_fee)?),
+            None => None,
+        },
     };
 
     store_config(&mut deps.storage, &config)?;
@@ -31,14 +89,81 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     msg: HandleMsg,
 ) -> HandleResult {
     match msg {
-        HandleMsg::FeedPrice { prices } => feed_price(deps, env, prices),
+        HandleMsg::FeedPrice {
+            prices,
+            quote_currency,
+        } => feed_price(deps, env, prices, quote_currency),
+        HandleMsg::SubmitSignedPrices {
+            reports,
+            signatures,
+        } => submit_signed_prices(deps, env, reports, signatures),
+        HandleMsg::Unsubscribe {
+            asset_info,
+            contract_addr,
+        } => unsubscribe(deps, env, asset_info, contract_addr),
+        HandleMsg::Pause { asset_info } => pause(deps, env, asset_info),
+        HandleMsg::PayAccess { consumer } => pay_access_native(deps, env, consumer),
+        HandleMsg::Receive { from, msg, amount } => receive_cw20(deps, env, from, amount, msg),
+        HandleMsg::ClaimFeederRewards {} => claim_feeder_rewards(deps, env),
+        HandleMsg::SetViewingKey { key, .. } => set_viewing_key(deps, env, key),
+        HandleMsg::RevokePermit { permit_name, .. } => revoke_permit(deps, env, permit_name),
         msg => {
-            assert_owner_privilege(deps, env)?;
+            assert_owner_privilege(deps, env.clone())?;
             match msg {
-                HandleMsg::UpdateConfig { owner } => update_config(deps, owner),
-                HandleMsg::RegisterAsset { asset_info, feeder } => {
-                    register_asset(deps, asset_info, feeder)
+                HandleMsg::UpdateConfig {
+                    owner,
+                    guardian,
+                    remove_guardian,
+                    history_retention,
+                    quorum,
+                    max_price_age,
+                    restrict_reads,
+                } => update_config(
+                    deps,
+                    owner,
+                    guardian,
+                    remove_guardian,
+                    history_retention,
+                    quorum,
+                    max_price_age,
+                    restrict_reads,
+                ),
+                HandleMsg::RegisterAsset {
+                    asset_info,
+                    feeder,
+                    heartbeat,
+                    metadata,
+                } => register_asset(deps, asset_info, feeder, heartbeat, metadata),
+                HandleMsg::UpdateAsset {
+                    asset_info,
+                    feeders,
+                    heartbeat,
+                    metadata,
+                } => update_asset(deps, asset_info, feeders, heartbeat, metadata),
+                HandleMsg::DeregisterAsset { asset_info } => deregister_asset(deps, asset_info),
+                HandleMsg::AddFeeder { asset_info, feeder } => add_feeder(deps, asset_info, feeder),
+                HandleMsg::RemoveFeeder { asset_info, feeder } => {
+                    remove_feeder(deps, asset_info, feeder)
                 }
+                HandleMsg::Subscribe {
+                    asset_info,
+                    contract_addr,
+                    code_hash,
+                } => subscribe(deps, asset_info, contract_addr, code_hash),
+                HandleMsg::SetFeederKey { feeder, pubkey } => set_feeder_key(deps, feeder, pubkey),
+                HandleMsg::SetPriceBounds { asset_info, bounds } => {
+                    set_price_bounds(deps, asset_info, bounds)
+                }
+                HandleMsg::ConfirmPendingPrice { asset_info } => {
+                    confirm_pending_price(deps, env, asset_info)
+                }
+                HandleMsg::RejectPendingPrice { asset_info } => {
+                    reject_pending_price(deps, asset_info)
+                }
+                HandleMsg::Unpause { asset_info } => unpause(deps, asset_info),
+                HandleMsg::AddConsumer { consumer } => add_consumer(deps, consumer),
+                HandleMsg::RemoveConsumer { consumer } => remove_consumer(deps, consumer),
+                HandleMsg::SetAccessFee { access_fee } => set_access_fee(deps, access_fee),
                 _ => panic!("do not enter here"),
             }
         }
@@ -48,12 +173,121 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
 pub fn query<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     msg: QueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::WithViewingKey {
+            consumer,
+            key,
+            query,
+        } => {
+            let consumer_raw = deps.api.canonical_address(&consumer)?;
+            let is_valid = match read_viewing_key(&deps.storage, &consumer_raw)? {
+                Some(hashed_key) => ViewingKey(key).check_viewing_key(hashed_key.as_slice()),
+                None => false,
+            };
+            if !is_valid {
+                return Err(StdError::generic_err("Invalid viewing key"));
+            }
+
+            query_as(deps, Some(consumer_raw), *query)
+        }
+        QueryMsg::WithPermit { permit, query } => {
+            let config: Config = read_config(&deps.storage)?;
+            let contract_addr = deps.api.human_address(&config.contract_addr)?;
+            let consumer = validate(deps, PREFIX_REVOKED_PERMITS, &permit, contract_addr)?;
+
+            query_as(deps, Some(deps.api.canonical_address(&consumer)?), *query)
+        }
+        msg => query_as(deps, None, msg),
+    }
+}
+
+/// Query of an authenticated consumer, price queries check its read access
+fn query_as<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    consumer: Option<CanonicalAddr>,
+    msg: QueryMsg,
 ) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::Feeder { asset_info } => to_binary(&query_feeder(deps, asset_info)?),
-        QueryMsg::LatestPrice { asset_info } => to_binary(&query_latest_price(deps, asset_info)?),
+        QueryMsg::Feeders { asset_info } => to_binary(&query_feeders(deps, asset_info)?),
+        QueryMsg::Subscriptions { asset_info } => {
+            to_binary(&query_subscriptions(deps, asset_info)?)
+        }
+        QueryMsg::FeederKey { feeder } => to_binary(&query_feeder_key(deps, feeder)?),
+        QueryMsg::Asset { asset_info } => to_binary(&query_asset(deps, asset_info)?),
+        QueryMsg::Assets { start_after, limit } => {
+            to_binary(&query_assets(deps, start_after, limit)?)
+        }
+        QueryMsg::Consumer { consumer } => to_binary(&query_consumer(deps, consumer)?),
+        QueryMsg::FeederRewards { feeder } => to_binary(&query_feeder_rewards(deps, feeder)?),
+        QueryMsg::WithViewingKey { .. } | QueryMsg::WithPermit { .. } => {
+            Err(StdError::generic_err("Nested authenticated query"))
+        }
+        msg => {
+            assert_read_access(deps, &consumer)?;
+            query_prices(deps, msg)
+        }
+    }
+}
+
+fn query_prices<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    msg: QueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::LatestPrice { asset_info, time } => {
+            to_binary(&query_latest_price(deps, asset_info, time)?)
+        }
+        QueryMsg::LatestPriceChecked { asset_info, time } => {
+            to_binary(&query_latest_price_checked(deps, asset_info, time)?)
+        }
+        QueryMsg::LatestPrices { asset_infos, time } => {
+            to_binary(&query_latest_prices(deps, asset_infos, time)?)
+        }
+        QueryMsg::CrossRate { base, quote, time } => {
+            to_binary(&query_cross_rate(deps, base, quote, time)?)
+        }
+        QueryMsg::PriceStatus { asset_info } => to_binary(&query_price_status(deps, asset_info)?),
+        QueryMsg::PriceHistory {
+            asset_info,
+            start_after,
+            limit,
+        } => to_binary(&query_price_history(deps, asset_info, start_after, limit)?),
+        QueryMsg::PriceAt {
+            asset_info,
+            timestamp,
+        } => to_binary(&query_price_at(deps, asset_info, timestamp)?),
+        QueryMsg::LatestRoundData { asset_info } => {
+            to_binary(&query_latest_round_data(deps, asset_info)?)
+        }
+        QueryMsg::GetRoundData {
+            asset_info,
+            round_id,
+        } => to_binary(&query_round_data(deps, asset_info, round_id)?),
+        _ => panic!("do not enter here"),
+    }
+}
+
+/// Allowed consumers always pass, paying consumers until a price is fed at their `paid_until`
+fn assert_read_access<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    consumer: &Option<CanonicalAddr>,
+) -> StdResult<()> {
+    let config: Config = read_config(&deps.storage)?;
+    if !config.restrict_reads {
+        return Ok(());
+    }
+
+    let consumer = consumer
+        .as_ref()
+        .ok_or_else(|| StdError::generic_err("Authenticated query required"))?;
+    let record = read_consumer(&deps.storage, consumer)?;
+    if !record.allowed && record.paid_until <= read_latest_time(&deps.storage)? {
+        return Err(StdError::generic_err("Read access denied"));
     }
+
+    Ok(())
 }
 
 fn query_config<S: Storage, A: Api, Q: Querier>(
@@ -63,23 +297,460 @@ fn query_config<S: Storage, A: Api, Q: Querier>(
 
     Ok(ConfigResponse {
         owner: deps.api.human_address(&config.owner)?,
+        history_retention: config.history_retention,
+        quorum: config.quorum,
+        max_price_age: config.max_price_age,
+        guardian: match config.guardian {
+            Some(guardian) => Some(deps.api.human_address(&guardian)?),
+            None => None,
+        },
+        paused: config.paused,
+        restrict_reads: config.restrict_reads,
+        access_fee: match config.access_fee {
+            Some(access_fee) => Some(AccessFee {
+                asset_info: access_fee.asset_info.to_normal(deps)?,
+                amount: access_fee.amount,
+                period: access_fee.period,
+            }),
+            None => None,
+        },
+    })
+}
+
+fn query_consumer<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    consumer: HumanAddr,
+) -> StdResult<ConsumerResponse> {
+    let record = read_consumer(&deps.storage, &deps.api.canonical_address(&consumer)?)?;
+
+    Ok(ConsumerResponse {
+        allowed: record.allowed,
+        paid_until: record.paid_until,
+    })
+}
+
+fn query_feeder_rewards<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    feeder: HumanAddr,
+) -> StdResult<FeederRewardsResponse> {
+    Ok(FeederRewardsResponse {
+        rewards: read_feeder_rewards(&deps.storage, &deps.api.canonical_address(&feeder)?)?,
+    })
+}
+
+fn query_feeders<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset_info: AssetInfo,
+) -> StdResult<FeedersResponse> {
+    let feeders = read_human_feeders(deps, &asset_info.to_raw(deps)?)?;
+
+    Ok(FeedersResponse { feeders })
+}
+
+fn query_subscriptions<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset_info: AssetInfo,
+) -> StdResult<SubscriptionsResponse> {
+    let subscriptions = read_subscriptions(&deps.storage, &asset_info.to_raw(deps)?)?
+        .into_iter()
+        .map(|subscription| {
+            Ok(Subscription {
+                contract_addr: deps.api.human_address(&subscription.contract_addr)?,
+                code_hash: subscription.code_hash,
+            })
+        })
+        .collect::<StdResult<Vec<Subscription>>>()?;
+
+    Ok(SubscriptionsResponse { subscriptions })
+}
+
+fn query_feeder_key<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    feeder: HumanAddr,
+) -> StdResult<FeederKeyResponse> {
+    let feeder_raw = deps.api.canonical_address(&feeder)?;
+
+    Ok(FeederKeyResponse {
+        pubkey: read_feeder_key(&deps.storage, &feeder_raw)?,
+        nonce: read_feeder_nonce(&deps.storage, &feeder_raw)?,
     })
 }
 
-fn query_feeder<S: Storage, A: Api, Q: Querier>(
+fn read_human_feeders<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset_info_raw: &AssetInfoRaw,
+) -> StdResult<Vec<HumanAddr>> {
+    read_feeders(&deps.storage, asset_info_raw)?
+        .iter()
+        .map(|feeder| deps.api.human_address(feeder))
+        .collect()
+}
+
+fn query_assets<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AssetsResponse> {
+    let asset_keys = read_asset_keys(&deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let start = match start_after {
+        Some(start_after) => match asset_keys.binary_search(&start_after) {
+            Ok(index) => index + 1,
+            Err(index) => index,
+        },
+        None => 0,
+    };
+
+    let assets = asset_keys
+        .iter()
+        .skip(start)
+        .take(limit)
+        .map(|asset_key| to_asset_response(deps, asset_key.clone()))
+        .collect::<StdResult<Vec<AssetResponse>>>()?;
+
+    Ok(AssetsResponse { assets })
+}
+
+fn query_asset<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     asset_info: AssetInfo,
-) -> StdResult<HumanAddr> {
-    let feeder = read_feeder(&deps.storage, &asset_info.to_raw(deps)?)?;
+) -> StdResult<AssetResponse> {
+    to_asset_response(deps, get_asset_key(asset_info))
+}
+
+fn to_asset_response<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset_key: String,
+) -> StdResult<AssetResponse> {
+    let record = read_asset(&deps.storage, &asset_key)?
+        .ok_or_else(|| StdError::generic_err("Asset not registered"))?;
+
+    Ok(AssetResponse {
+        asset_key,
+        asset_info: record.asset_info.to_normal(deps)?,
+        feeders: read_human_feeders(deps, &record.asset_info)?,
+        heartbeat: read_heartbeat(&deps.storage, &record.asset_info)?,
+        metadata: record.metadata,
+    })
+}
 
-    Ok(deps.api.human_address(&feeder)?)
+/// Metadata of a registered asset
+fn read_metadata<S: Storage>(storage: &S, asset_info: AssetInfo) -> StdResult<AssetMetadata> {
+    read_asset(storage, &get_asset_key(asset_info))?
+        .map(|record| record.metadata)
+        .ok_or_else(|| StdError::generic_err("Asset not registered"))
 }
 
 fn query_latest_price<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     asset_info: AssetInfo,
+    time: Option<u64>,
+) -> StdResult<LatestPriceResponse> {
+    let config: Config = read_config(&deps.storage)?;
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let metadata = read_metadata(&deps.storage, asset_info)?;
+
+    assert_feed_active(&deps.storage, &config, &asset_info_raw)?;
+
+    // The contract has seen the latest time pass even if the query gives an older one
+    let latest_time = read_latest_time(&deps.storage)?;
+    let now = time.map_or(latest_time, |time| time.max(latest_time));
+    let aggregated = aggregate_price(&deps.storage, &config, &asset_info_raw, now)?;
+    let heartbeat = read_heartbeat(&deps.storage, &asset_info_raw)?;
+
+    // Removed feeders and config updates change the price without recording a round
+    let range = read_history_range(&deps.storage, &asset_info_raw)?;
+    let round_id = if range.next > range.first {
+        let round = read_price_history(&deps.storage, &asset_info_raw, range.next - 1)?;
+        Some(round.round_id).filter(|_| {
+            round.price == aggregated.price && round.updated_at == aggregated.updated_at
+        })
+    } else {
+        None
+    };
+
+    Ok(LatestPriceResponse {
+        price: aggregated.price,
+        last_updated_time: aggregated.updated_at,
+        quote_currency: metadata.quote_currency,
+        decimals: metadata.decimals,
+        feeder: deps.api.human_address(&aggregated.feeder)?,
+        heartbeat,
+        is_stale: heartbeat.map_or(false, |heartbeat| {
+            now > aggregated.updated_at.saturating_add(heartbeat)
+        }),
+        round_id,
+    })
+}
+
+fn query_latest_price_checked<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset_info: AssetInfo,
+    time: u64,
+) -> StdResult<LatestPriceResponse> {
+    let latest_price = query_latest_price(deps, asset_info, Some(time))?;
+    if latest_price.is_stale {
+        return Err(StdError::generic_err("Price is stale"));
+    }
+
+    Ok(latest_price)
+}
+
+fn query_latest_prices<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset_infos: Vec<AssetInfo>,
+    time: Option<u64>,
+) -> StdResult<LatestPricesResponse> {
+    if asset_infos.len() > MAX_LIMIT as usize {
+        return Err(StdError::generic_err("Too many assets"));
+    }
+
+    let prices = asset_infos
+        .into_iter()
+        .map(|asset_info| {
+            let (price, error) = match query_latest_price(deps, asset_info.clone(), time) {
+                Ok(price) => (Some(price), None),
+                Err(StdError::GenericErr { msg, .. }) => (None, Some(msg)),
+                Err(err) => (None, Some(err.to_string())),
+            };
+            LatestPriceResult {
+                asset_info,
+                price,
+                error,
+            }
+        })
+        .collect();
+
+    Ok(LatestPricesResponse { prices })
+}
+
+fn query_cross_rate<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    base: AssetInfo,
+    quote: AssetInfo,
+    time: Option<u64>,
+) -> StdResult<CrossRateResponse> {
+    let base_price = query_latest_price(deps, base, time)?;
+    let quote_price = query_latest_price(deps, quote, time)?;
+    if base_price.quote_currency != quote_price.quote_currency {
+        return Err(StdError::generic_err("Quote currency mismatch"));
+    }
+
+    Ok(CrossRateResponse {
+        rate: decimal_div(base_price.price, quote_price.price)?,
+        last_updated_time: base_price
+            .last_updated_time
+            .min(quote_price.last_updated_time),
+        is_stale: base_price.is_stale || quote_price.is_stale,
+    })
+}
+
+fn query_price_status<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset_info: AssetInfo,
+) -> StdResult<PriceStatusResponse> {
+    let config: Config = read_config(&deps.storage)?;
+    let asset_info_raw = asset_info.to_raw(deps)?;
+
+    let pending_price = match read_pending_price(&deps.storage, &asset_info_raw)? {
+        Some(pending_price) => Some(PendingPrice {
+            feeder: deps.api.human_address(&pending_price.feeder)?,
+            price: pending_price.price_info.price,
+            last_updated_time: pending_price.price_info.last_updated_time,
+        }),
+        None => None,
+    };
+
+    Ok(PriceStatusResponse {
+        status: if is_paused(&deps.storage, &config, &asset_info_raw)? {
+            PriceStatus::Paused
+        } else if pending_price.is_some() {
+            PriceStatus::Halted
+        } else {
+            PriceStatus::Active
+        },
+        bounds: read_price_bounds(&deps.storage, &asset_info_raw)?,
+        pending_price,
+    })
+}
+
+/// Whether the feeds of the asset are paused, globally or for the asset
+fn is_paused<S: Storage>(
+    storage: &S,
+    config: &Config,
+    asset_info_raw: &AssetInfoRaw,
+) -> StdResult<bool> {
+    Ok(config.paused || read_asset_paused(storage, asset_info_raw)?)
+}
+
+/// Fail unless the latest price of the asset can be used, a pause takes precedence over a halt
+fn assert_feed_active<S: Storage>(
+    storage: &S,
+    config: &Config,
+    asset_info_raw: &AssetInfoRaw,
+) -> StdResult<()> {
+    if is_paused(storage, config, asset_info_raw)? {
+        return Err(StdError::generic_err(FEED_PAUSED));
+    }
+    if read_pending_price(storage, asset_info_raw)?.is_some() {
+        return Err(StdError::generic_err("Price feed halted"));
+    }
+
+    Ok(())
+}
+
+/// Lower median of the submissions, those older than `max_price_age` at `now` are absent
+fn aggregate_price<S: Storage>(
+    storage: &S,
+    config: &Config,
+    asset_info_raw: &AssetInfoRaw,
+    now: u64,
+) -> StdResult<AggregatedPrice> {
+    let mut submissions: Vec<(Decimal, CanonicalAddr, u64)> = vec![];
+    for feeder in read_feeders(storage, asset_info_raw)? {
+        if let Some(price_info) = read_price_info(storage, asset_info_raw, &feeder)? {
+            submissions.push((price_info.price, feeder, price_info.last_updated_time));
+        }
+    }
+
+    submissions.retain(|(_, _, time)| {
+        config.max_price_age.map_or(true, |max_price_age| {
+            time.saturating_add(max_price_age) >= now
+        })
+    });
+    let latest_time = submissions
+        .iter()
+        .map(|(_, _, time)| *time)
+        .max()
+        .unwrap_or_default();
+
+    if (submissions.len() as u64) < config.quorum {
+        return Err(StdError::generic_err(format!(
+            "Quorum not reached: {} of {} prices",
+            submissions.len(),
+            config.quorum
+        )));
+    }
+
+    let started_at = submissions
+        .iter()
+        .map(|(_, _, time)| *time)
+        .min()
+        .unwrap_or_default();
+
+    submissions.sort_by(|a, b| a.0.cmp(&b.0));
+    let (price, feeder, _) = submissions.swap_remove((submissions.len() - 1) / 2);
+    Ok(AggregatedPrice {
+        price,
+        feeder,
+        started_at,
+        updated_at: latest_time,
+    })
+}
+
+fn query_price_history<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset_info: AssetInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PriceHistoryResponse> {
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let range = read_history_range(&deps.storage, &asset_info_raw)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as u64;
+
+    let start = match start_after {
+        Some(time) => first_index_after(&deps.storage, &asset_info_raw, &range, time)?,
+        None => range.first,
+    };
+
+    let prices = (start..range.next.min(start + limit))
+        .map(|index| read_price_history(&deps.storage, &asset_info_raw, index).map(to_price_info))
+        .collect::<StdResult<Vec<PriceInfo>>>()?;
+
+    Ok(PriceHistoryResponse { prices })
+}
+
+fn query_price_at<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset_info: AssetInfo,
+    timestamp: u64,
 ) -> StdResult<PriceInfo> {
-    Ok(read_price_info(&deps.storage, asset_info.to_raw(deps)?)?)
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let range = read_history_range(&deps.storage, &asset_info_raw)?;
+
+    let index = first_index_after(&deps.storage, &asset_info_raw, &range, timestamp)?;
+    if index == range.first {
+        return Err(StdError::generic_err(format!("No price at {}", timestamp)));
+    }
+
+    read_price_history(&deps.storage, &asset_info_raw, index - 1).map(to_price_info)
+}
+
+fn query_latest_round_data<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset_info: AssetInfo,
+) -> StdResult<RoundData> {
+    let config: Config = read_config(&deps.storage)?;
+    let asset_info_raw = asset_info.to_raw(deps)?;
+
+    assert_feed_active(&deps.storage, &config, &asset_info_raw)?;
+
+    let range = read_history_range(&deps.storage, &asset_info_raw)?;
+    if range.next == range.first {
+        return Err(StdError::generic_err("No round data"));
+    }
+
+    read_price_history(&deps.storage, &asset_info_raw, range.next - 1)
+}
+
+fn query_round_data<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset_info: AssetInfo,
+    round_id: u64,
+) -> StdResult<RoundData> {
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let range = read_history_range(&deps.storage, &asset_info_raw)?;
+
+    // Rounds below the retained range are pruned, those above it are not recorded yet
+    if round_id <= range.first || round_id > range.next {
+        return Err(StdError::generic_err(format!(
+            "Round {} not found",
+            round_id
+        )));
+    }
+
+    read_price_history(&deps.storage, &asset_info_raw, round_id - 1)
+}
+
+/// Retained rounds are labeled with the unit they were recorded in
+fn to_price_info(round: RoundData) -> PriceInfo {
+    PriceInfo {
+        price: round.price,
+        last_updated_time: round.updated_at,
+        quote_currency: round.quote_currency,
+        decimals: round.decimals,
+    }
+}
+
+/// First retained index fed after `time`, `push_price_history` records increasing times
+fn first_index_after<S: Storage>(
+    storage: &S,
+    asset_info_raw: &AssetInfoRaw,
+    range: &HistoryRange,
+    time: u64,
+) -> StdResult<u64> {
+    let (mut low, mut high) = (range.first, range.next);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if read_price_history(storage, asset_info_raw, mid)?.updated_at <= time {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    Ok(low)
 }
 
 fn assert_owner_privilege<S: Storage, A: Api, Q: Querier>(
@@ -97,81 +768,1143 @@ fn feed_price<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     prices: Vec<(AssetInfo, Decimal)>,
+    quote_currency: Option<String>,
 ) -> HandleResult {
     let feeder_raw = deps.api.canonical_address(&env.message.sender)?;
+    let config: Config = read_config(&deps.storage)?;
 
     let mut logs = vec![log("action", "feed_price")];
+    let mut messages = vec![];
 
-    for price in prices {
-        let asset_info_raw = price.0.to_raw(deps)?;
-        if feeder_raw != read_feeder(&deps.storage, &asset_info_raw)? {
-            return Err(StdError::unauthorized());
-        }
-
-        logs.push(log("asset_key", get_asset_key(price.0)));
-        logs.push(log("price", price.1));
-
-        store_price_info(
-            &mut deps.storage,
-            &asset_info_raw,
-            PriceInfo {
-                price: price.1,
-                last_updated_time: env.block.time,
-            },
+    for (asset_info, price) in prices {
+        let (price_logs, hooks) = submit_price(
+            deps,
+            &config,
+            env.block.time,
+            &feeder_raw,
+            asset_info,
+            price,
+            env.block.time,
+            &quote_currency,
         )?;
+        logs.extend(price_logs);
+        messages.extend(hooks);
     }
 
     Ok(HandleResponse {
-        messages: vec![],
+        messages,
         log: logs,
         data: None,
     })
 }
 
-fn update_config<S: Storage, A: Api, Q: Querier>(
+/// Verify the reports against the keys of their feeders and submit them at their timestamps
+fn submit_signed_prices<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    owner: Option<HumanAddr>,
+    env: Env,
+    reports: Vec<SignedPriceReport>,
+    signatures: Vec<Binary>,
 ) -> HandleResult {
-    let mut config: Config = read_config(&deps.storage)?;
-
-    if let Some(owner) = owner {
-        config.owner = deps.api.canonical_address(&owner)?;
+    if reports.len() != signatures.len() {
+        return Err(StdError::generic_err("Signature count mismatch"));
     }
 
-    store_config(&mut deps.storage, &config)?;
+    let config: Config = read_config(&deps.storage)?;
+
+    let mut logs = vec![log("action", "submit_signed_prices")];
+    let mut messages = vec![];
+
+    for (report, signature) in reports.into_iter().zip(signatures) {
+        if report.timestamp > env.block.time {
+            return Err(StdError::generic_err("Report from the future"));
+        }
+        if env.block.time - report.timestamp > MAX_REPORT_DELAY {
+            return Err(StdError::generic_err("Report expired"));
+        }
+
+        let feeder_raw = deps.api.canonical_address(&report.feeder)?;
+        if report.nonce <= read_feeder_nonce(&deps.storage, &feeder_raw)? {
+            return Err(StdError::generic_err("Nonce already used"));
+        }
+
+        let pubkey = read_feeder_key(&deps.storage, &feeder_raw)?
+            .ok_or_else(|| StdError::generic_err("Feeder key not registered"))?;
+        let message = report.signing_bytes(&env.contract.address, &env.block.chain_id)?;
+        verify_signature(&pubkey, &message, &signature)?;
+
+        let (price_logs, hooks) = submit_price(
+            deps,
+            &config,
+            env.block.time,
+            &feeder_raw,
+            report.asset_info,
+            report.price,
+            report.timestamp,
+            &None,
+        )?;
+        store_feeder_nonce(&mut deps.storage, &feeder_raw, report.nonce)?;
+
+        logs.push(log("feeder", &report.feeder));
+        logs.extend(price_logs);
+        messages.extend(hooks);
+    }
 
     Ok(HandleResponse {
-        messages: vec![],
-        log: vec![log("action", "update_config")],
+        messages,
+        log: logs,
         data: None,
     })
 }
 
-fn register_asset<S: Storage, A: Api, Q: Querier>(
+/// Store the price of a registered feeder observed at `updated_at`, held as pending
+/// if it crosses the bounds, returns the logs and the hooks of a new round
+#[allow(clippy::too_many_arguments)]
+fn submit_price<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
+    config: &Config,
+    now: u64,
+    feeder_raw: &CanonicalAddr,
     asset_info: AssetInfo,
-    feeder: HumanAddr,
-) -> HandleResult {
+    price: Decimal,
+    updated_at: u64,
+    quote_currency: &Option<String>,
+) -> StdResult<(Vec<LogAttribute>, Vec<CosmosMsg>)> {
     let asset_info_raw = asset_info.to_raw(deps)?;
-    let asset_key = get_asset_key(asset_info);
+    if !read_feeders(&deps.storage, &asset_info_raw)?.contains(feeder_raw) {
+        return Err(StdError::unauthorized());
+    }
+    if is_paused(&deps.storage, config, &asset_info_raw)? {
+        return Err(StdError::generic_err(FEED_PAUSED));
+    }
+    store_latest_time(&mut deps.storage, now)?;
+
+    let metadata = read_metadata(&deps.storage, asset_info.clone())?;
+    if let Some(quote_currency) = quote_currency {
+        if quote_currency != &metadata.quote_currency {
+            return Err(StdError::generic_err("Quote currency mismatch"));
+        }
+    }
+    validate_precision(price, metadata.decimals)?;
+
+    if let Some(submission) = read_price_info(&deps.storage, &asset_info_raw, feeder_raw)? {
+        if updated_at < submission.last_updated_time {
+            return Err(StdError::generic_err(
+                "Price older than the latest submission",
+            ));
+        }
+    }
+
+    let mut logs = vec![
+        log("asset_key", get_asset_key(asset_info.clone())),
+        log("price", price),
+    ];
+
+    let price_info = PriceInfo {
+        price,
+        last_updated_time: updated_at,
+        quote_currency: metadata.quote_currency,
+        decimals: metadata.decimals,
+    };
+
+    // Held for the owner instead of failing the whole batch
+    if crosses_bounds(&deps.storage, config, &asset_info_raw, price, now)? {
+        store_pending_price(
+            &mut deps.storage,
+            &asset_info_raw,
+            &PendingPriceRaw {
+                feeder: feeder_raw.clone(),
+                price_info,
+            },
+        )?;
+        logs.push(log("status", "halted"));
+        return Ok((logs, vec![]));
+    }
 
-    store_feeder(
+    let round = store_submission(
         &mut deps.storage,
+        config,
         &asset_info_raw,
-        deps.api.canonical_address(&feeder)?,
+        feeder_raw,
+        &price_info,
+        now,
     )?;
+    let hooks = match round {
+        Some(round) => {
+            logs.push(log("round_id", round.round_id));
+            // Consumers cannot read a halted price, so they are not notified until it is resolved
+            if read_pending_price(&deps.storage, &asset_info_raw)?.is_some() {
+                vec![]
+            } else {
+                price_hooks(deps, asset_info, &asset_info_raw, &round)?
+            }
+        }
+        None => vec![],
+    };
 
-    Ok(HandleResponse {
-        messages: vec![],
-        log: vec![
-            log("action", "register_asset"),
-            log("asset_key", asset_key),
-            log("feeder", feeder),
-        ],
-        data: None,
-    })
+    Ok((logs, hooks))
+}
+
+/// `PriceUpdated` messages to the subscribers of the asset
+fn price_hooks<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    asset_info: AssetInfo,
+    asset_info_raw: &AssetInfoRaw,
+    round: &RoundData,
+) -> StdResult<Vec<CosmosMsg>> {
+    let msg = to_binary(&PriceHookMsg::PriceUpdated {
+        asset_info,
+        round_id: round.round_id,
+        price: round.price,
+        updated_at: round.updated_at,
+    })?;
+
+    read_subscriptions(&deps.storage, asset_info_raw)?
+        .into_iter()
+        .map(|subscription| {
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.human_address(&subscription.contract_addr)?,
+                callback_code_hash: subscription.code_hash,
+                msg: msg.clone(),
+                send: vec![],
+            }))
+        })
+        .collect()
+}
+
+/// Verify a 64 byte secp256k1 signature of the SHA-256 hash of `message`
+fn verify_signature(pubkey: &Binary, message: &[u8], signature: &Binary) -> StdResult<()> {
+    let pubkey = VerifyingKey::from_sec1_bytes(pubkey.as_slice())
+        .map_err(|_| StdError::generic_err("Invalid public key"))?;
+    let signature = Signature::try_from(signature.as_slice())
+        .map_err(|_| StdError::generic_err("Invalid signature"))?;
+
+    pubkey
+        .verify(message, &signature)
+        .map_err(|_| StdError::generic_err("Invalid signature"))
+}
+
+/// Store the submission of a feeder, the history records the aggregated price
+/// as a new round once the quorum is reached, returns the new round
+fn store_submission<S: Storage>(
+    storage: &mut S,
+    config: &Config,
+    asset_info_raw: &AssetInfoRaw,
+    feeder_raw: &CanonicalAddr,
+    price_info: &PriceInfo,
+    now: u64,
+) -> StdResult<Option<RoundData>> {
+    store_price_info(storage, asset_info_raw, feeder_raw, price_info)?;
+
+    match aggregate_price(storage, config, asset_info_raw, now) {
+        Ok(aggregated) => push_price_history(
+            storage,
+            asset_info_raw,
+            aggregated.price,
+            aggregated.started_at,
+            aggregated.updated_at,
+            price_info.quote_currency.clone(),
+            price_info.decimals,
+            config.history_retention,
+        ),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Whether the price is zero, out of the bounds or too far from the current price
+fn crosses_bounds<S: Storage>(
+    storage: &S,
+    config: &Config,
+    asset_info_raw: &AssetInfoRaw,
+    price: Decimal,
+    now: u64,
+) -> StdResult<bool> {
+    let bounds = read_price_bounds(storage, asset_info_raw)?;
+
+    if price == Decimal::zero()
+        || bounds
+            .min_price
+            .map_or(false, |min_price| price < min_price)
+        || bounds
+            .max_price
+            .map_or(false, |max_price| price > max_price)
+    {
+        return Ok(true);
+    }
+
+    let current = match (
+        bounds.max_deviation,
+        aggregate_price(storage, config, asset_info_raw, now),
+    ) {
+        (Some(max_deviation), Ok(current)) if current.price != Decimal::zero() => {
+            Some((max_deviation, current.price))
+        }
+        _ => None,
+    };
+
+    match current {
+        Some((max_deviation, current_price)) => {
+            let diff = if price > current_price {
+                decimal_sub(price, current_price)?
+            } else {
+                decimal_sub(current_price, price)?
+            };
+            Ok(decimal_div(diff, current_price)? > max_deviation)
+        }
+        None => Ok(false),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_config<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    owner: Option<HumanAddr>,
+    guardian: Option<HumanAddr>,
+    remove_guardian: Option<bool>,
+    history_retention: Option<u64>,
+    quorum: Option<u64>,
+    max_price_age: Option<u64>,
+    restrict_reads: Option<bool>,
+) -> HandleResult {
+    let mut config: Config = read_config(&deps.storage)?;
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.canonical_address(&owner)?;
+    }
+
+    if let Some(guardian) = guardian {
+        if remove_guardian == Some(true) {
+            return Err(StdError::generic_err(
+                "Cannot set and remove the guardian at once",
+            ));
+        }
+        config.guardian = Some(deps.api.canonical_address(&guardian)?);
+    }
+
+    if remove_guardian == Some(true) {
+        config.guardian = None;
+    }
+
+    // The oldest prices above a lowered retention are dropped by the next feed
+    if let Some(history_retention) = history_retention {
+        config.history_retention = validate_history_retention(history_retention)?;
+    }
+
+    if let Some(quorum) = quorum {
+        config.quorum = validate_quorum(quorum)?;
+    }
+
+    if let Some(max_price_age) = max_price_age {
+        config.max_price_age = Some(max_price_age);
+    }
+
+    if let Some(restrict_reads) = restrict_reads {
+        config.restrict_reads = restrict_reads;
+    }
+
+    store_config(&mut deps.storage, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "update_config")],
+        data: None,
+    })
+}
+
+fn register_asset<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    asset_info: AssetInfo,
+    feeder: HumanAddr,
+    heartbeat: Option<u64>,
+    metadata: AssetMetadata,
+) -> HandleResult {
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let asset_key = get_asset_key(asset_info);
+
+    store_asset(
+        &mut deps.storage,
+        &asset_key,
+        &AssetRecord {
+            asset_info: asset_info_raw.clone(),
+            metadata: validate_metadata(metadata)?,
+        },
+    )?;
+    let mut asset_keys = read_asset_keys(&deps.storage)?;
+    if let Err(index) = asset_keys.binary_search(&asset_key) {
+        asset_keys.insert(index, asset_key.clone());
+        store_asset_keys(&mut deps.storage, &asset_keys)?;
+    }
+
+    store_heartbeat(&mut deps.storage, &asset_info_raw, heartbeat)?;
+    for old_feeder in read_feeders(&deps.storage, &asset_info_raw)? {
+        remove_price_info(&mut deps.storage, &asset_info_raw, &old_feeder);
+    }
+    store_feeders(
+        &mut deps.storage,
+        &asset_info_raw,
+        &[deps.api.canonical_address(&feeder)?],
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "register_asset"),
+            log("asset_key", asset_key),
+            log("feeder", feeder),
+        ],
+        data: None,
+    })
+}
+
+fn update_asset<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    asset_info: AssetInfo,
+    feeders: Option<Vec<HumanAddr>>,
+    heartbeat: Option<u64>,
+    metadata: Option<AssetMetadata>,
+) -> HandleResult {
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let asset_key = get_asset_key(asset_info);
+
+    let mut record = read_asset(&deps.storage, &asset_key)?
+        .ok_or_else(|| StdError::generic_err("Asset not registered"))?;
+
+    if let Some(metadata) = metadata {
+        let metadata = validate_metadata(metadata)?;
+
+        // Prices in the previous unit are dropped
+        if metadata.quote_currency != record.metadata.quote_currency
+            || metadata.decimals != record.metadata.decimals
+        {
+            for feeder in read_feeders(&deps.storage, &asset_info_raw)? {
+                remove_price_info(&mut deps.storage, &asset_info_raw, &feeder);
+            }
+            remove_pending_price(&mut deps.storage, &asset_info_raw);
+        }
+
+        record.metadata = metadata;
+        store_asset(&mut deps.storage, &asset_key, &record)?;
+    }
+
+    if let Some(heartbeat) = heartbeat {
+        store_heartbeat(&mut deps.storage, &asset_info_raw, Some(heartbeat))?;
+    }
+
+    if let Some(feeders) = feeders {
+        let mut feeders_raw: Vec<CanonicalAddr> = vec![];
+        for feeder in feeders.iter() {
+            let feeder_raw = deps.api.canonical_address(feeder)?;
+            if feeders_raw.contains(&feeder_raw) {
+                return Err(StdError::generic_err("Feeder already registered"));
+            }
+            feeders_raw.push(feeder_raw);
+        }
+        if feeders_raw.is_empty() {
+            return Err(StdError::generic_err("Can not remove the last feeder"));
+        }
+
+        for old_feeder in read_feeders(&deps.storage, &asset_info_raw)? {
+            if !feeders_raw.contains(&old_feeder) {
+                remove_price_info(&mut deps.storage, &asset_info_raw, &old_feeder);
+            }
+        }
+        store_feeders(&mut deps.storage, &asset_info_raw, &feeders_raw)?;
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "update_asset"), log("asset_key", asset_key)],
+        data: None,
+    })
+}
+
+fn deregister_asset<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    asset_info: AssetInfo,
+) -> HandleResult {
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let asset_key = get_asset_key(asset_info);
+
+    if read_asset(&deps.storage, &asset_key)?.is_none() {
+        return Err(StdError::generic_err("Asset not registered"));
+    }
+
+    // The history range is kept so the round ids continue after registering again
+    for feeder in read_feeders(&deps.storage, &asset_info_raw)? {
+        remove_price_info(&mut deps.storage, &asset_info_raw, &feeder);
+    }
+    remove_feeders(&mut deps.storage, &asset_info_raw);
+    remove_heartbeat(&mut deps.storage, &asset_info_raw);
+    remove_price_bounds(&mut deps.storage, &asset_info_raw);
+    remove_pending_price(&mut deps.storage, &asset_info_raw);
+    remove_subscriptions(&mut deps.storage, &asset_info_raw);
+    remove_asset_paused(&mut deps.storage, &asset_info_raw);
+    remove_asset(&mut deps.storage, &asset_key);
+
+    let mut asset_keys = read_asset_keys(&deps.storage)?;
+    asset_keys.retain(|registered| registered != &asset_key);
+    store_asset_keys(&mut deps.storage, &asset_keys)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "deregister_asset"),
+            log("asset_key", asset_key),
+        ],
+        data: None,
+    })
+}
+
+fn add_feeder<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    asset_info: AssetInfo,
+    feeder: HumanAddr,
+) -> HandleResult {
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let asset_key = get_asset_key(asset_info);
+    let feeder_raw = deps.api.canonical_address(&feeder)?;
+
+    let mut feeders = read_feeders(&deps.storage, &asset_info_raw)?;
+    if feeders.is_empty() {
+        return Err(StdError::generic_err("Asset not registered"));
+    }
+    if feeders.contains(&feeder_raw) {
+        return Err(StdError::generic_err("Feeder already registered"));
+    }
+
+    feeders.push(feeder_raw);
+    store_feeders(&mut deps.storage, &asset_info_raw, &feeders)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "add_feeder"),
+            log("asset_key", asset_key),
+            log("feeder", feeder),
+        ],
+        data: None,
+    })
+}
+
+fn remove_feeder<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    asset_info: AssetInfo,
+    feeder: HumanAddr,
+) -> HandleResult {
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let asset_key = get_asset_key(asset_info);
+    let feeder_raw = deps.api.canonical_address(&feeder)?;
+
+    let mut feeders = read_feeders(&deps.storage, &asset_info_raw)?;
+    if !feeders.contains(&feeder_raw) {
+        return Err(StdError::generic_err("Feeder not registered"));
+    }
+    if feeders.len() == 1 {
+        return Err(StdError::generic_err("Can not remove the last feeder"));
+    }
+
+    feeders.retain(|registered| registered != &feeder_raw);
+    store_feeders(&mut deps.storage, &asset_info_raw, &feeders)?;
+    remove_price_info(&mut deps.storage, &asset_info_raw, &feeder_raw);
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "remove_feeder"),
+            log("asset_key", asset_key),
+            log("feeder", feeder),
+        ],
+        data: None,
+    })
+}
+
+fn subscribe<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    asset_info: AssetInfo,
+    contract_addr: HumanAddr,
+    code_hash: String,
+) -> HandleResult {
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let asset_key = get_asset_key(asset_info);
+    if read_asset(&deps.storage, &asset_key)?.is_none() {
+        return Err(StdError::generic_err("Asset not registered"));
+    }
+
+    let contract_raw = deps.api.canonical_address(&contract_addr)?;
+    let mut subscriptions = read_subscriptions(&deps.storage, &asset_info_raw)?;
+    if subscriptions
+        .iter()
+        .any(|subscription| subscription.contract_addr == contract_raw)
+    {
+        return Err(StdError::generic_err("Already subscribed"));
+    }
+    if subscriptions.len() >= MAX_SUBSCRIPTIONS {
+        return Err(StdError::generic_err("Too many subscriptions"));
+    }
+
+    subscriptions.push(SubscriptionRaw {
+        contract_addr: contract_raw,
+        code_hash,
+    });
+    store_subscriptions(&mut deps.storage, &asset_info_raw, &subscriptions)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "subscribe"),
+            log("asset_key", asset_key),
+            log("contract_addr", contract_addr),
+        ],
+        data: None,
+    })
+}
+
+fn unsubscribe<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    asset_info: AssetInfo,
+    contract_addr: HumanAddr,
+) -> HandleResult {
+    if env.message.sender != contract_addr {
+        assert_owner_privilege(deps, env)?;
+    }
+
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let contract_raw = deps.api.canonical_address(&contract_addr)?;
+    let mut subscriptions = read_subscriptions(&deps.storage, &asset_info_raw)?;
+    let count = subscriptions.len();
+    subscriptions.retain(|subscription| subscription.contract_addr != contract_raw);
+    if subscriptions.len() == count {
+        return Err(StdError::generic_err("Not subscribed"));
+    }
+    store_subscriptions(&mut deps.storage, &asset_info_raw, &subscriptions)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "unsubscribe"),
+            log("asset_key", get_asset_key(asset_info)),
+            log("contract_addr", contract_addr),
+        ],
+        data: None,
+    })
+}
+
+fn set_feeder_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    feeder: HumanAddr,
+    pubkey: Option<Binary>,
+) -> HandleResult {
+    let feeder_raw = deps.api.canonical_address(&feeder)?;
+
+    match &pubkey {
+        Some(pubkey) => {
+            if pubkey.len() != 33 || VerifyingKey::from_sec1_bytes(pubkey.as_slice()).is_err() {
+                return Err(StdError::generic_err("Invalid public key"));
+            }
+            store_feeder_key(&mut deps.storage, &feeder_raw, pubkey)?;
+        }
+        None => remove_feeder_key(&mut deps.storage, &feeder_raw),
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "set_feeder_key"),
+            log("feeder", feeder),
+            log("enabled", pubkey.is_some()),
+        ],
+        data: None,
+    })
+}
+
+fn set_price_bounds<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    asset_info: AssetInfo,
+    bounds: PriceBounds,
+) -> HandleResult {
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let asset_key = get_asset_key(asset_info);
+
+    if read_feeders(&deps.storage, &asset_info_raw)?.is_empty() {
+        return Err(StdError::generic_err("Asset not registered"));
+    }
+    if let (Some(min_price), Some(max_price)) = (bounds.min_price, bounds.max_price) {
+        if min_price > max_price {
+            return Err(StdError::generic_err("Invalid price bounds"));
+        }
+    }
+
+    store_price_bounds(&mut deps.storage, &asset_info_raw, &bounds)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "set_price_bounds"),
+            log("asset_key", asset_key),
+        ],
+        data: None,
+    })
+}
+
+fn confirm_pending_price<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    asset_info: AssetInfo,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let asset_key = get_asset_key(asset_info.clone());
+
+    let pending_price = read_pending_price(&deps.storage, &asset_info_raw)?
+        .ok_or_else(|| StdError::generic_err("No pending price"))?;
+    remove_pending_price(&mut deps.storage, &asset_info_raw);
+
+    // A later submission of the feeder is kept
+    let submission = read_price_info(&deps.storage, &asset_info_raw, &pending_price.feeder)?;
+    let mut messages = vec![];
+    if submission.map_or(true, |submission| {
+        submission.last_updated_time <= pending_price.price_info.last_updated_time
+    }) {
+        if let Some(round) = store_submission(
+            &mut deps.storage,
+            &config,
+            &asset_info_raw,
+            &pending_price.feeder,
+            &pending_price.price_info,
+            env.block.time,
+        )? {
+            messages = price_hooks(deps, asset_info, &asset_info_raw, &round)?;
+        }
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![
+            log("action", "confirm_pending_price"),
+            log("asset_key", asset_key),
+            log("price", pending_price.price_info.price),
+        ],
+        data: None,
+    })
+}
+
+fn reject_pending_price<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    asset_info: AssetInfo,
+) -> HandleResult {
+    let asset_info_raw = asset_info.to_raw(deps)?;
+    let asset_key = get_asset_key(asset_info);
+
+    let pending_price = read_pending_price(&deps.storage, &asset_info_raw)?
+        .ok_or_else(|| StdError::generic_err("No pending price"))?;
+    remove_pending_price(&mut deps.storage, &asset_info_raw);
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "reject_pending_price"),
+            log("asset_key", asset_key),
+            log("price", pending_price.price_info.price),
+        ],
+        data: None,
+    })
+}
+
+/// Pause the feeds of the asset, or of all assets, by the owner or the guardian
+fn pause<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    asset_info: Option<AssetInfo>,
+) -> HandleResult {
+    let mut config: Config = read_config(&deps.storage)?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    if sender_raw != config.owner && Some(sender_raw) != config.guardian {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut logs = vec![log("action", "pause")];
+    match asset_info {
+        Some(asset_info) => {
+            let asset_info_raw = asset_info.to_raw(deps)?;
+            let asset_key = get_asset_key(asset_info);
+            if read_asset(&deps.storage, &asset_key)?.is_none() {
+                return Err(StdError::generic_err("Asset not registered"));
+            }
+            if read_asset_paused(&deps.storage, &asset_info_raw)? {
+                return Err(StdError::generic_err("Already paused"));
+            }
+
+            store_asset_paused(&mut deps.storage, &asset_info_raw, true)?;
+            logs.push(log("asset_key", asset_key));
+        }
+        None => {
+            if config.paused {
+                return Err(StdError::generic_err("Already paused"));
+            }
+
+            config.paused = true;
+            store_config(&mut deps.storage, &config)?;
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: None,
+    })
+}
+
+/// Resume the feeds of the asset, or of all assets, the pause of the other level is kept
+fn unpause<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    asset_info: Option<AssetInfo>,
+) -> HandleResult {
+    let mut config: Config = read_config(&deps.storage)?;
+
+    let mut logs = vec![log("action", "unpause")];
+    match asset_info {
+        Some(asset_info) => {
+            let asset_info_raw = asset_info.to_raw(deps)?;
+            if !read_asset_paused(&deps.storage, &asset_info_raw)? {
+                return Err(StdError::generic_err("Not paused"));
+            }
+
+            remove_asset_paused(&mut deps.storage, &asset_info_raw);
+            logs.push(log("asset_key", get_asset_key(asset_info)));
+        }
+        None => {
+            if !config.paused {
+                return Err(StdError::generic_err("Not paused"));
+            }
+
+            config.paused = false;
+            store_config(&mut deps.storage, &config)?;
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: None,
+    })
+}
+
+fn add_consumer<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    consumer: HumanAddr,
+) -> HandleResult {
+    let consumer_raw = deps.api.canonical_address(&consumer)?;
+
+    let mut record = read_consumer(&deps.storage, &consumer_raw)?;
+    if record.allowed {
+        return Err(StdError::generic_err("Consumer already allowed"));
+    }
+
+    record.allowed = true;
+    store_consumer(&mut deps.storage, &consumer_raw, &record)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "add_consumer"), log("consumer", consumer)],
+        data: None,
+    })
+}
+
+fn remove_consumer<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    consumer: HumanAddr,
+) -> HandleResult {
+    let consumer_raw = deps.api.canonical_address(&consumer)?;
+
+    let mut record = read_consumer(&deps.storage, &consumer_raw)?;
+    if !record.allowed {
+        return Err(StdError::generic_err("Consumer not allowed"));
+    }
+
+    record.allowed = false;
+    store_consumer(&mut deps.storage, &consumer_raw, &record)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "remove_consumer"), log("consumer", consumer)],
+        data: None,
+    })
+}
+
+/// Credited rewards are paid in the fee asset, so it only changes once they are claimed
+fn set_access_fee<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    access_fee: Option<AccessFee>,
+) -> HandleResult {
+    let mut config: Config = read_config(&deps.storage)?;
+
+    let access_fee = match access_fee {
+        Some(access_fee) => Some(to_access_fee_raw(deps, access_fee)?),
+        None => None,
+    };
+    let asset_changed = config.access_fee.as_ref().map(|fee| &fee.asset_info)
+        != access_fee.as_ref().map(|fee| &fee.asset_info);
+    if asset_changed && !read_unclaimed_rewards(&deps.storage)?.is_zero() {
+        return Err(StdError::generic_err("Feeder rewards not claimed"));
+    }
+
+    config.access_fee = access_fee;
+    store_config(&mut deps.storage, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "set_access_fee")],
+        data: None,
+    })
+}
+
+fn pay_access_native<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    consumer: Option<HumanAddr>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let access_fee = config
+        .access_fee
+        .ok_or_else(|| StdError::generic_err("Access fee not set"))?;
+
+    let amount = match access_fee.asset_info {
+        AssetInfoRaw::NativeToken { denom } => env
+            .message
+            .sent_funds
+            .iter()
+            .find(|coin| coin.denom == denom)
+            .map(|coin| coin.amount)
+            .unwrap_or_else(Uint128::zero),
+        AssetInfoRaw::Token { .. } => return Err(StdError::generic_err("Invalid asset")),
+    };
+
+    let consumer = consumer.unwrap_or_else(|| env.message.sender.clone());
+    pay_access(deps, env, consumer, amount)
+}
+
+fn receive_cw20<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    from: HumanAddr,
+    amount: Uint128,
+    msg: Option<Binary>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let access_fee = config
+        .access_fee
+        .ok_or_else(|| StdError::generic_err("Access fee not set"))?;
+
+    match access_fee.asset_info {
+        AssetInfoRaw::Token { contract_addr, .. } => {
+            if env.message.sender != deps.api.human_address(&contract_addr)? {
+                return Err(StdError::generic_err("Invalid asset"));
+            }
+        }
+        AssetInfoRaw::NativeToken { .. } => return Err(StdError::generic_err("Invalid asset")),
+    }
+
+    match msg {
+        Some(msg) => match from_binary(&msg)? {
+            Cw20HookMsg::PayAccess { consumer } => {
+                pay_access(deps, env, consumer.unwrap_or(from), amount)
+            }
+        },
+        None => Err(StdError::generic_err("data should be given")),
+    }
+}
+
+/// Extend the access of the consumer by the periods paid and credit the payment to the feeders
+fn pay_access<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    consumer: HumanAddr,
+    amount: Uint128,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let access_fee = config
+        .access_fee
+        .ok_or_else(|| StdError::generic_err("Access fee not set"))?;
+
+    let periods = amount.u128() / access_fee.amount.u128();
+    if periods == 0 {
+        return Err(StdError::generic_err("Payment below the access fee"));
+    }
+
+    let consumer_raw = deps.api.canonical_address(&consumer)?;
+    let mut record = read_consumer(&deps.storage, &consumer_raw)?;
+    let start = record.paid_until.max(env.block.time) as u128;
+    record.paid_until = periods
+        .checked_mul(access_fee.period as u128)
+        .and_then(|duration| duration.checked_add(start))
+        .and_then(|paid_until| u64::try_from(paid_until).ok())
+        .ok_or_else(|| StdError::generic_err("Payment too large"))?;
+    store_consumer(&mut deps.storage, &consumer_raw, &record)?;
+    store_latest_time(&mut deps.storage, env.block.time)?;
+
+    credit_feeders(&mut deps.storage, amount)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "pay_access"),
+            log("consumer", consumer),
+            log("amount", amount),
+            log("paid_until", record.paid_until),
+        ],
+        data: None,
+    })
+}
+
+/// Split the payment equally between the feeders of all assets,
+/// the remainder goes to the first feeder
+fn credit_feeders<S: Storage>(storage: &mut S, amount: Uint128) -> StdResult<()> {
+    let mut feeders: Vec<CanonicalAddr> = vec![];
+    for asset_key in read_asset_keys(storage)? {
+        if let Some(record) = read_asset(storage, &asset_key)? {
+            for feeder in read_feeders(storage, &record.asset_info)? {
+                if !feeders.contains(&feeder) {
+                    feeders.push(feeder);
+                }
+            }
+        }
+    }
+    if feeders.is_empty() {
+        return Err(StdError::generic_err("No feeders to credit"));
+    }
+
+    let share = amount.u128() / feeders.len() as u128;
+    let remainder = amount.u128() - share * feeders.len() as u128;
+    for (index, feeder) in feeders.iter().enumerate() {
+        let credit = if index == 0 { share + remainder } else { share };
+        let rewards = read_feeder_rewards(storage, feeder)?;
+        store_feeder_rewards(storage, feeder, Uint128(rewards.u128() + credit))?;
+    }
+
+    let unclaimed = read_unclaimed_rewards(storage)?;
+    store_unclaimed_rewards(storage, unclaimed + amount)
+}
+
+fn claim_feeder_rewards<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let feeder_raw = deps.api.canonical_address(&env.message.sender)?;
+    let rewards = read_feeder_rewards(&deps.storage, &feeder_raw)?;
+    if rewards.is_zero() {
+        return Err(StdError::generic_err("No rewards"));
+    }
+
+    // The fee asset can not change while rewards are unclaimed
+    let config: Config = read_config(&deps.storage)?;
+    let access_fee = config
+        .access_fee
+        .ok_or_else(|| StdError::generic_err("Access fee not set"))?;
+
+    remove_feeder_rewards(&mut deps.storage, &feeder_raw);
+    let unclaimed = read_unclaimed_rewards(&deps.storage)?;
+    store_unclaimed_rewards(&mut deps.storage, (unclaimed - rewards)?)?;
+
+    Ok(HandleResponse {
+        messages: vec![Asset {
+            info: access_fee.asset_info.to_normal(deps)?,
+            amount: rewards,
+        }
+        .into_msg(env.contract.address, env.message.sender.clone())?],
+        log: vec![
+            log("action", "claim_feeder_rewards"),
+            log("feeder", env.message.sender),
+            log("amount", rewards),
+        ],
+        data: None,
+    })
+}
+
+fn set_viewing_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    key: String,
+) -> HandleResult {
+    let consumer_raw = deps.api.canonical_address(&env.message.sender)?;
+    store_viewing_key(&mut deps.storage, &consumer_raw, &ViewingKey(key))?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "set_viewing_key"), log("success", true)],
+        data: None,
+    })
+}
+
+fn revoke_permit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    permit_name: String,
+) -> HandleResult {
+    RevokedPermits::revoke_permit(
+        &mut deps.storage,
+        PREFIX_REVOKED_PERMITS,
+        &env.message.sender,
+        &permit_name,
+    );
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "revoke_permit")],
+        data: None,
+    })
+}
+
+fn to_access_fee_raw<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    access_fee: AccessFee,
+) -> StdResult<AccessFeeRaw> {
+    if access_fee.amount.is_zero() || access_fee.period == 0 {
+        return Err(StdError::generic_err("Invalid access fee"));
+    }
+
+    Ok(AccessFeeRaw {
+        asset_info: access_fee.asset_info.to_raw(deps)?,
+        amount: access_fee.amount,
+        period: access_fee.period,
+    })
+}
+
+fn validate_history_retention(history_retention: u64) -> StdResult<u64> {
+    if history_retention == 0 {
+        return Err(StdError::generic_err("History retention must be positive"));
+    }
+
+    Ok(history_retention)
+}
+
+fn validate_metadata(metadata: AssetMetadata) -> StdResult<AssetMetadata> {
+    if metadata.symbol.is_empty() || metadata.quote_currency.is_empty() || metadata.decimals > 18 {
+        return Err(StdError::generic_err("Invalid asset metadata"));
+    }
+
+    Ok(metadata)
+}
+
+fn validate_precision(price: Decimal, decimals: u8) -> StdResult<()> {
+    if decimal_to_atomics(price) % 10u128.pow(18 - decimals as u32) != 0 {
+        return Err(StdError::generic_err(format!(
+            "Price exceeds {} decimals",
+            decimals
+        )));
+    }
+
+    Ok(())
+}
+
+fn validate_quorum(quorum: u64) -> StdResult<u64> {
+    if quorum == 0 {
+        return Err(StdError::generic_err("Quorum must be positive"));
+    }
+
+    Ok(quorum)
 }
 
+/// Canonical key of an asset in logs and the asset registry
 fn get_asset_key(asset_info: AssetInfo) -> String {
     match asset_info {
         AssetInfo::NativeToken { denom } => format!("native_token_{}", denom),
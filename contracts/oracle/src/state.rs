@@ -1,18 +1,92 @@
-use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
+use cosmwasm_std::{Binary, CanonicalAddr, Decimal, StdResult, Storage, Uint128};
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use prediction::asset::AssetInfoRaw;
-use prediction::oracle::PriceInfo;
+use prediction::oracle::{AssetMetadata, PriceBounds, PriceInfo, RoundData};
+use prediction::viewing_key::ViewingKey;
 
 static KEY_CONFIG: &[u8] = b"config";
-static PREFIX_FEEDER: &[u8] = b"prefix_feeder";
+static KEY_ASSET_KEYS: &[u8] = b"asset_keys";
+static KEY_LATEST_TIME: &[u8] = b"latest_time";
+static KEY_UNCLAIMED_REWARDS: &[u8] = b"unclaimed_rewards";
+static PREFIX_ASSETS: &[u8] = b"prefix_assets";
+static PREFIX_FEEDERS: &[u8] = b"prefix_feeders";
 static PREFIX_PRICE_INFO: &[u8] = b"prefix_price_info";
+static PREFIX_PRICE_HISTORY: &[u8] = b"prefix_price_history";
+static PREFIX_HISTORY_RANGE: &[u8] = b"prefix_history_range";
+static PREFIX_PRICE_BOUNDS: &[u8] = b"prefix_price_bounds";
+static PREFIX_PENDING_PRICE: &[u8] = b"prefix_pending_price";
+static PREFIX_HEARTBEAT: &[u8] = b"prefix_heartbeat";
+static PREFIX_FEEDER_KEY: &[u8] = b"prefix_feeder_key";
+static PREFIX_FEEDER_NONCE: &[u8] = b"prefix_feeder_nonce";
+static PREFIX_SUBSCRIPTIONS: &[u8] = b"prefix_subscriptions";
+static PREFIX_ASSET_PAUSED: &[u8] = b"prefix_asset_paused";
+static PREFIX_CONSUMERS: &[u8] = b"prefix_consumers";
+static PREFIX_FEEDER_REWARDS: &[u8] = b"prefix_feeder_rewards";
+static PREFIX_VIEW_KEY: &[u8] = b"prefix_view_key";
+
+pub const PREFIX_REVOKED_PERMITS: &str = "revoked_permits";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
+    pub contract_addr: CanonicalAddr,
     pub owner: CanonicalAddr,
+    pub history_retention: u64,
+    /// Fresh submissions required for a price
+    pub quorum: u64,
+    /// Seconds a submission stays fresh, unlimited if not set
+    pub max_price_age: Option<u64>,
+    /// Allowed to pause the feeds besides the owner
+    pub guardian: Option<CanonicalAddr>,
+    /// Feeds of all assets are rejected
+    pub paused: bool,
+    /// Price queries require an allowed or paying consumer
+    pub restrict_reads: bool,
+    pub access_fee: Option<AccessFeeRaw>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccessFeeRaw {
+    pub asset_info: AssetInfoRaw,
+    pub amount: Uint128,
+    pub period: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ConsumerRaw {
+    pub allowed: bool,
+    pub paid_until: u64,
+}
+
+/// Registered asset stored under its asset key
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetRecord {
+    pub asset_info: AssetInfoRaw,
+    pub metadata: AssetMetadata,
+}
+
+/// Contract receiving the price hooks of an asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubscriptionRaw {
+    pub contract_addr: CanonicalAddr,
+    pub code_hash: String,
+}
+
+/// Indexes `[first, next)` of the retained price history of an asset,
+/// the round at `index` has the id `index + 1`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct HistoryRange {
+    pub first: u64,
+    pub next: u64,
+}
+
+/// Feed held by the circuit breaker, the asset is halted while it exists
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingPriceRaw {
+    pub feeder: CanonicalAddr,
+    pub price_info: PriceInfo,
 }
 
 pub fn store_config<S: Storage>(storage: &mut S, data: &Config) -> StdResult<()> {
@@ -22,24 +96,338 @@ pub fn read_config<S: Storage>(storage: &S) -> StdResult<Config> {
     ReadonlySingleton::new(storage, KEY_CONFIG).load()
 }
 
-pub fn store_feeder<S: Storage>(
+/// Sorted keys of the registered assets
+pub fn store_asset_keys<S: Storage>(storage: &mut S, asset_keys: &[String]) -> StdResult<()> {
+    Singleton::new(storage, KEY_ASSET_KEYS).save(&asset_keys.to_vec())
+}
+pub fn read_asset_keys<S: Storage>(storage: &S) -> StdResult<Vec<String>> {
+    Ok(ReadonlySingleton::new(storage, KEY_ASSET_KEYS)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_asset<S: Storage>(
+    storage: &mut S,
+    asset_key: &str,
+    record: &AssetRecord,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_ASSETS, storage).save(asset_key.as_bytes(), record)
+}
+pub fn read_asset<S: Storage>(storage: &S, asset_key: &str) -> StdResult<Option<AssetRecord>> {
+    ReadonlyBucket::new(PREFIX_ASSETS, storage).may_load(asset_key.as_bytes())
+}
+pub fn remove_asset<S: Storage>(storage: &mut S, asset_key: &str) {
+    Bucket::<S, AssetRecord>::new(PREFIX_ASSETS, storage).remove(asset_key.as_bytes())
+}
+
+pub fn store_feeders<S: Storage>(
     storage: &mut S,
     asset_info: &AssetInfoRaw,
-    feeder: CanonicalAddr,
+    feeders: &[CanonicalAddr],
 ) -> StdResult<()> {
-    Bucket::new(PREFIX_FEEDER, storage).save(asset_info.as_bytes(), &feeder)
+    Bucket::new(PREFIX_FEEDERS, storage).save(asset_info.as_bytes(), &feeders.to_vec())
 }
-pub fn read_feeder<S: Storage>(storage: &S, asset_info: &AssetInfoRaw) -> StdResult<CanonicalAddr> {
-    ReadonlyBucket::new(PREFIX_FEEDER, storage).load(asset_info.as_bytes())
+/// Feeders of the asset, empty if it is not registered
+pub fn read_feeders<S: Storage>(
+    storage: &S,
+    asset_info: &AssetInfoRaw,
+) -> StdResult<Vec<CanonicalAddr>> {
+    Ok(ReadonlyBucket::new(PREFIX_FEEDERS, storage)
+        .may_load(asset_info.as_bytes())?
+        .unwrap_or_default())
+}
+pub fn remove_feeders<S: Storage>(storage: &mut S, asset_info: &AssetInfoRaw) {
+    Bucket::<S, Vec<CanonicalAddr>>::new(PREFIX_FEEDERS, storage).remove(asset_info.as_bytes())
 }
 
+/// Latest submission of a feeder
 pub fn store_price_info<S: Storage>(
     storage: &mut S,
     asset_info: &AssetInfoRaw,
-    price_info: PriceInfo,
+    feeder: &CanonicalAddr,
+    price_info: &PriceInfo,
+) -> StdResult<()> {
+    Bucket::multilevel(&[PREFIX_PRICE_INFO, asset_info.as_bytes()], storage)
+        .save(feeder.as_slice(), price_info)
+}
+pub fn read_price_info<S: Storage>(
+    storage: &S,
+    asset_info: &AssetInfoRaw,
+    feeder: &CanonicalAddr,
+) -> StdResult<Option<PriceInfo>> {
+    ReadonlyBucket::multilevel(&[PREFIX_PRICE_INFO, asset_info.as_bytes()], storage)
+        .may_load(feeder.as_slice())
+}
+pub fn remove_price_info<S: Storage>(
+    storage: &mut S,
+    asset_info: &AssetInfoRaw,
+    feeder: &CanonicalAddr,
+) {
+    Bucket::<S, PriceInfo>::multilevel(&[PREFIX_PRICE_INFO, asset_info.as_bytes()], storage)
+        .remove(feeder.as_slice())
+}
+
+pub fn read_history_range<S: Storage>(
+    storage: &S,
+    asset_info: &AssetInfoRaw,
+) -> StdResult<HistoryRange> {
+    Ok(ReadonlyBucket::new(PREFIX_HISTORY_RANGE, storage)
+        .may_load(asset_info.as_bytes())?
+        .unwrap_or_default())
+}
+
+pub fn read_price_history<S: Storage>(
+    storage: &S,
+    asset_info: &AssetInfoRaw,
+    index: u64,
+) -> StdResult<RoundData> {
+    ReadonlyBucket::multilevel(&[PREFIX_PRICE_HISTORY, asset_info.as_bytes()], storage)
+        .load(&index.to_be_bytes())
+}
+
+/// Append a round to the history and drop the oldest rounds above `retention`,
+/// a later price in the same block replaces the price of its round
+#[allow(clippy::too_many_arguments)]
+pub fn push_price_history<S: Storage>(
+    storage: &mut S,
+    asset_info: &AssetInfoRaw,
+    price: Decimal,
+    started_at: u64,
+    updated_at: u64,
+    quote_currency: String,
+    decimals: u8,
+    retention: u64,
+) -> StdResult<Option<RoundData>> {
+    let mut range = read_history_range(storage, asset_info)?;
+    if range.next > range.first {
+        let last = read_price_history(storage, asset_info, range.next - 1)?;
+        if last.updated_at > updated_at {
+            return Ok(None);
+        }
+
+        if last.updated_at == updated_at {
+            let round = RoundData {
+                price,
+                started_at,
+                quote_currency,
+                decimals,
+                ..last
+            };
+            Bucket::multilevel(&[PREFIX_PRICE_HISTORY, asset_info.as_bytes()], storage)
+                .save(&(range.next - 1).to_be_bytes(), &round)?;
+            return Ok(Some(round));
+        }
+    }
+
+    let round = RoundData {
+        round_id: range.next + 1,
+        price,
+        started_at,
+        updated_at,
+        quote_currency,
+        decimals,
+    };
+
+    let mut history = Bucket::multilevel(&[PREFIX_PRICE_HISTORY, asset_info.as_bytes()], storage);
+    history.save(&range.next.to_be_bytes(), &round)?;
+    range.next += 1;
+
+    while range.next - range.first > retention {
+        history.remove(&range.first.to_be_bytes());
+        range.first += 1;
+    }
+
+    Bucket::new(PREFIX_HISTORY_RANGE, storage).save(asset_info.as_bytes(), &range)?;
+
+    Ok(Some(round))
+}
+
+pub fn store_price_bounds<S: Storage>(
+    storage: &mut S,
+    asset_info: &AssetInfoRaw,
+    bounds: &PriceBounds,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_PRICE_BOUNDS, storage).save(asset_info.as_bytes(), bounds)
+}
+pub fn read_price_bounds<S: Storage>(
+    storage: &S,
+    asset_info: &AssetInfoRaw,
+) -> StdResult<PriceBounds> {
+    Ok(ReadonlyBucket::new(PREFIX_PRICE_BOUNDS, storage)
+        .may_load(asset_info.as_bytes())?
+        .unwrap_or_default())
+}
+pub fn remove_price_bounds<S: Storage>(storage: &mut S, asset_info: &AssetInfoRaw) {
+    Bucket::<S, PriceBounds>::new(PREFIX_PRICE_BOUNDS, storage).remove(asset_info.as_bytes())
+}
+
+pub fn store_pending_price<S: Storage>(
+    storage: &mut S,
+    asset_info: &AssetInfoRaw,
+    pending_price: &PendingPriceRaw,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_PENDING_PRICE, storage).save(asset_info.as_bytes(), pending_price)
+}
+pub fn read_pending_price<S: Storage>(
+    storage: &S,
+    asset_info: &AssetInfoRaw,
+) -> StdResult<Option<PendingPriceRaw>> {
+    ReadonlyBucket::new(PREFIX_PENDING_PRICE, storage).may_load(asset_info.as_bytes())
+}
+pub fn remove_pending_price<S: Storage>(storage: &mut S, asset_info: &AssetInfoRaw) {
+    Bucket::<S, PendingPriceRaw>::new(PREFIX_PENDING_PRICE, storage).remove(asset_info.as_bytes())
+}
+
+pub fn store_heartbeat<S: Storage>(
+    storage: &mut S,
+    asset_info: &AssetInfoRaw,
+    heartbeat: Option<u64>,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_HEARTBEAT, storage).save(asset_info.as_bytes(), &heartbeat)
+}
+pub fn read_heartbeat<S: Storage>(
+    storage: &S,
+    asset_info: &AssetInfoRaw,
+) -> StdResult<Option<u64>> {
+    Ok(ReadonlyBucket::new(PREFIX_HEARTBEAT, storage)
+        .may_load(asset_info.as_bytes())?
+        .unwrap_or_default())
+}
+pub fn remove_heartbeat<S: Storage>(storage: &mut S, asset_info: &AssetInfoRaw) {
+    Bucket::<S, Option<u64>>::new(PREFIX_HEARTBEAT, storage).remove(asset_info.as_bytes())
+}
+
+/// Compressed secp256k1 public key verifying the signed reports of a feeder
+pub fn store_feeder_key<S: Storage>(
+    storage: &mut S,
+    feeder: &CanonicalAddr,
+    pubkey: &Binary,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_FEEDER_KEY, storage).save(feeder.as_slice(), pubkey)
+}
+pub fn read_feeder_key<S: Storage>(
+    storage: &S,
+    feeder: &CanonicalAddr,
+) -> StdResult<Option<Binary>> {
+    ReadonlyBucket::new(PREFIX_FEEDER_KEY, storage).may_load(feeder.as_slice())
+}
+pub fn remove_feeder_key<S: Storage>(storage: &mut S, feeder: &CanonicalAddr) {
+    Bucket::<S, Binary>::new(PREFIX_FEEDER_KEY, storage).remove(feeder.as_slice())
+}
+
+/// Last report nonce of a feeder, kept when its key changes so reports can not be replayed
+pub fn store_feeder_nonce<S: Storage>(
+    storage: &mut S,
+    feeder: &CanonicalAddr,
+    nonce: u64,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_FEEDER_NONCE, storage).save(feeder.as_slice(), &nonce)
+}
+pub fn read_feeder_nonce<S: Storage>(storage: &S, feeder: &CanonicalAddr) -> StdResult<u64> {
+    Ok(ReadonlyBucket::new(PREFIX_FEEDER_NONCE, storage)
+        .may_load(feeder.as_slice())?
+        .unwrap_or_default())
+}
+
+pub fn store_subscriptions<S: Storage>(
+    storage: &mut S,
+    asset_info: &AssetInfoRaw,
+    subscriptions: &[SubscriptionRaw],
+) -> StdResult<()> {
+    Bucket::new(PREFIX_SUBSCRIPTIONS, storage).save(asset_info.as_bytes(), &subscriptions.to_vec())
+}
+pub fn read_subscriptions<S: Storage>(
+    storage: &S,
+    asset_info: &AssetInfoRaw,
+) -> StdResult<Vec<SubscriptionRaw>> {
+    Ok(ReadonlyBucket::new(PREFIX_SUBSCRIPTIONS, storage)
+        .may_load(asset_info.as_bytes())?
+        .unwrap_or_default())
+}
+pub fn remove_subscriptions<S: Storage>(storage: &mut S, asset_info: &AssetInfoRaw) {
+    Bucket::<S, Vec<SubscriptionRaw>>::new(PREFIX_SUBSCRIPTIONS, storage)
+        .remove(asset_info.as_bytes())
+}
+
+/// Feeds of the asset are rejected, whatever the global pause
+pub fn store_asset_paused<S: Storage>(
+    storage: &mut S,
+    asset_info: &AssetInfoRaw,
+    paused: bool,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_ASSET_PAUSED, storage).save(asset_info.as_bytes(), &paused)
+}
+pub fn read_asset_paused<S: Storage>(storage: &S, asset_info: &AssetInfoRaw) -> StdResult<bool> {
+    Ok(ReadonlyBucket::new(PREFIX_ASSET_PAUSED, storage)
+        .may_load(asset_info.as_bytes())?
+        .unwrap_or_default())
+}
+pub fn remove_asset_paused<S: Storage>(storage: &mut S, asset_info: &AssetInfoRaw) {
+    Bucket::<S, bool>::new(PREFIX_ASSET_PAUSED, storage).remove(asset_info.as_bytes())
+}
+
+/// Latest block time seen by a feed or a payment, queries have no block time
+/// so the paid access ends by this time
+pub fn store_latest_time<S: Storage>(storage: &mut S, time: u64) -> StdResult<()> {
+    Singleton::new(storage, KEY_LATEST_TIME).save(&time)
+}
+pub fn read_latest_time<S: Storage>(storage: &S) -> StdResult<u64> {
+    Ok(ReadonlySingleton::new(storage, KEY_LATEST_TIME)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_consumer<S: Storage>(
+    storage: &mut S,
+    consumer: &CanonicalAddr,
+    record: &ConsumerRaw,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_CONSUMERS, storage).save(consumer.as_slice(), record)
+}
+pub fn read_consumer<S: Storage>(storage: &S, consumer: &CanonicalAddr) -> StdResult<ConsumerRaw> {
+    Ok(ReadonlyBucket::new(PREFIX_CONSUMERS, storage)
+        .may_load(consumer.as_slice())?
+        .unwrap_or_default())
+}
+
+/// Access fees credited to all feeders and not claimed yet
+pub fn store_unclaimed_rewards<S: Storage>(storage: &mut S, amount: Uint128) -> StdResult<()> {
+    Singleton::new(storage, KEY_UNCLAIMED_REWARDS).save(&amount)
+}
+pub fn read_unclaimed_rewards<S: Storage>(storage: &S) -> StdResult<Uint128> {
+    Ok(ReadonlySingleton::new(storage, KEY_UNCLAIMED_REWARDS)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_feeder_rewards<S: Storage>(
+    storage: &mut S,
+    feeder: &CanonicalAddr,
+    rewards: Uint128,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_FEEDER_REWARDS, storage).save(feeder.as_slice(), &rewards)
+}
+pub fn read_feeder_rewards<S: Storage>(storage: &S, feeder: &CanonicalAddr) -> StdResult<Uint128> {
+    Ok(ReadonlyBucket::new(PREFIX_FEEDER_REWARDS, storage)
+        .may_load(feeder.as_slice())?
+        .unwrap_or_default())
+}
+pub fn remove_feeder_rewards<S: Storage>(storage: &mut S, feeder: &CanonicalAddr) {
+    Bucket::<S, Uint128>::new(PREFIX_FEEDER_REWARDS, storage).remove(feeder.as_slice())
+}
+
+/// Hashed viewing key of a consumer
+pub fn store_viewing_key<S: Storage>(
+    storage: &mut S,
+    consumer: &CanonicalAddr,
+    key: &ViewingKey,
 ) -> StdResult<()> {
-    Bucket::new(PREFIX_PRICE_INFO, storage).save(&asset_info.as_bytes(), &price_info)
+    Bucket::new(PREFIX_VIEW_KEY, storage)
+        .save(consumer.as_slice(), &Binary::from(&key.to_hashed()[..]))
 }
-pub fn read_price_info<S: Storage>(storage: &S, asset_info: AssetInfoRaw) -> StdResult<PriceInfo> {
-    ReadonlyBucket::new(PREFIX_PRICE_INFO, storage).load(&asset_info.as_bytes())
+pub fn read_viewing_key<S: Storage>(
+    storage: &S,
+    consumer: &CanonicalAddr,
+) -> StdResult<Option<Binary>> {
+    ReadonlyBucket::new(PREFIX_VIEW_KEY, storage).may_load(consumer.as_slice())
 }
@@ -1,15 +1,30 @@
-use cosmwasm_std::testing::{mock_dependencies, mock_env};
-use cosmwasm_std::{from_binary, log, Decimal, HumanAddr, StdError};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    from_binary, log, to_binary, BankMsg, Binary, Coin, CosmosMsg, Decimal, Env, Extern,
+    HandleResult, HumanAddr, StdError, StdResult, Uint128, WasmMsg,
+};
 use std::str::FromStr;
 
 use prediction::{
     asset::AssetInfo,
-    oracle::{ConfigResponse, HandleMsg, InitMsg, PriceInfo, QueryMsg},
+    oracle::{
+        AccessFee, AssetMetadata, AssetResponse, AssetsResponse, ConfigResponse, ConsumerResponse,
+        CrossRateResponse, Cw20HookMsg, FeederKeyResponse, FeederRewardsResponse, FeedersResponse,
+        HandleMsg, InitMsg, LatestPriceResponse, LatestPriceResult, LatestPricesResponse,
+        PendingPrice, PriceBounds, PriceHistoryResponse, PriceHookMsg, PriceInfo, PriceStatus,
+        PriceStatusResponse, QueryMsg, RoundData, SignedPriceReport, Subscription,
+        SubscriptionsResponse,
+    },
 };
 
 use crate::{
     contract::{handle, init, query},
-    tests::test_utils::{init_oracle, register_test_assets},
+    tests::test_utils::{
+        asset_metadata, compressed_pubkey, init_oracle, register_test_assets, sign_report,
+        sign_report_for, signing_key,
+    },
 };
 
 #[test]
@@ -18,6 +33,12 @@ fn test_init() {
 
     let msg = InitMsg {
         owner: HumanAddr::from("owner"),
+        history_retention: None,
+        quorum: None,
+        max_price_age: None,
+        guardian: None,
+        restrict_reads: None,
+        access_fee: None,
     };
 
     let env = mock_env("addr", &[]);
@@ -29,6 +50,13 @@ fn test_init() {
     assert_eq!(
         ConfigResponse {
             owner: HumanAddr::from("owner"),
+            history_retention: 100,
+            quorum: 1,
+            max_price_age: None,
+            guardian: None,
+            paused: false,
+            restrict_reads: false,
+            access_fee: None,
         },
         config
     );
@@ -42,6 +70,12 @@ fn test_update_config_failed_if_unauthorized() {
 
     let msg = HandleMsg::UpdateConfig {
         owner: Some(HumanAddr::from("owner1")),
+        history_retention: None,
+        quorum: None,
+        max_price_age: None,
+        guardian: None,
+        remove_guardian: None,
+        restrict_reads: None,
     };
 
     let env = mock_env("addr", &[]);
@@ -61,6 +95,12 @@ fn test_update_config() {
 
     let msg = HandleMsg::UpdateConfig {
         owner: Some(HumanAddr::from("owner1")),
+        history_retention: None,
+        quorum: None,
+        max_price_age: None,
+        guardian: None,
+        remove_guardian: None,
+        restrict_reads: None,
     };
 
     let env = mock_env("owner", &[]);
@@ -73,6 +113,13 @@ fn test_update_config() {
     assert_eq!(
         ConfigResponse {
             owner: HumanAddr::from("owner1"),
+            history_retention: 100,
+            quorum: 1,
+            max_price_age: None,
+            guardian: None,
+            paused: false,
+            restrict_reads: false,
+            access_fee: None,
         },
         config
     );
@@ -89,6 +136,8 @@ fn test_register_asset_failed_if_unauthorized() {
             denom: "sscrt".to_string(),
         },
         feeder: HumanAddr::from("feeder"),
+        heartbeat: None,
+        metadata: asset_metadata("SCRT"),
     };
 
     let env = mock_env("addr", &[]);
@@ -111,6 +160,8 @@ fn test_register_asset_native_token() {
             denom: "sscrt".to_string(),
         },
         feeder: HumanAddr::from("feeder"),
+        heartbeat: None,
+        metadata: asset_metadata("SCRT"),
     };
 
     let env = mock_env("owner", &[]);
@@ -128,7 +179,7 @@ fn test_register_asset_native_token() {
 
     let res = query(
         &deps,
-        QueryMsg::Feeder {
+        QueryMsg::Feeders {
             asset_info: AssetInfo::NativeToken {
                 denom: "sscrt".to_string(),
             },
@@ -136,8 +187,8 @@ fn test_register_asset_native_token() {
     )
     .unwrap();
 
-    let feeder: HumanAddr = from_binary(&res).unwrap();
-    assert_eq!(HumanAddr::from("feeder"), feeder);
+    let res: FeedersResponse = from_binary(&res).unwrap();
+    assert_eq!(vec![HumanAddr::from("feeder")], res.feeders);
 }
 
 #[test]
@@ -153,6 +204,8 @@ fn test_register_asset_snip20_token() {
             viewing_key: String::from("viewing_key"),
         },
         feeder: HumanAddr::from("feeder"),
+        heartbeat: None,
+        metadata: asset_metadata("SCRT"),
     };
 
     let env = mock_env("owner", &[]);
@@ -170,7 +223,7 @@ fn test_register_asset_snip20_token() {
 
     let res = query(
         &deps,
-        QueryMsg::Feeder {
+        QueryMsg::Feeders {
             asset_info: AssetInfo::Token {
                 contract_addr: HumanAddr::from("usdt"),
                 token_code_hash: String::from("token_code_hash"),
@@ -180,8 +233,238 @@ fn test_register_asset_snip20_token() {
     )
     .unwrap();
 
-    let feeder: HumanAddr = from_binary(&res).unwrap();
-    assert_eq!(HumanAddr::from("feeder"), feeder);
+    let res: FeedersResponse = from_binary(&res).unwrap();
+    assert_eq!(vec![HumanAddr::from("feeder")], res.feeders);
+}
+
+fn assets(
+    deps: &Extern<MockStorage, MockApi, MockQuerier>,
+    start_after: Option<&str>,
+    limit: Option<u32>,
+) -> Vec<AssetResponse> {
+    let res = query(
+        deps,
+        QueryMsg::Assets {
+            start_after: start_after.map(String::from),
+            limit,
+        },
+    )
+    .unwrap();
+    from_binary::<AssetsResponse>(&res).unwrap().assets
+}
+
+fn asset_keys(assets: Vec<AssetResponse>) -> Vec<String> {
+    assets.into_iter().map(|asset| asset.asset_key).collect()
+}
+
+#[test]
+fn test_assets() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    assert_eq!(assets(&deps, None, None), vec![]);
+
+    register_test_assets(&mut deps);
+
+    assert_eq!(
+        assets(&deps, None, Some(1)),
+        vec![AssetResponse {
+            asset_key: "native_token_sscrt".to_string(),
+            asset_info: sscrt(),
+            metadata: asset_metadata("SSCRT"),
+            feeders: vec![HumanAddr::from("feeder1")],
+            heartbeat: None,
+        }]
+    );
+    assert_eq!(
+        asset_keys(assets(&deps, Some("native_token_sscrt"), Some(2))),
+        vec!["native_token_sscrt2", "snip20_token_snip20_test1"]
+    );
+    assert_eq!(
+        asset_keys(assets(&deps, Some("snip20_token"), None)),
+        vec!["snip20_token_snip20_test1", "snip20_token_snip20_test2"]
+    );
+
+    // Registering again replaces the entry
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: sscrt(),
+        feeder: HumanAddr::from("feeder2"),
+        heartbeat: None,
+        metadata: asset_metadata("SCRT"),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    let registered = assets(&deps, None, None);
+    assert_eq!(registered.len(), 4);
+    assert_eq!(registered[0].metadata.symbol, "SCRT");
+
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: sscrt(),
+        feeder: HumanAddr::from("feeder1"),
+        heartbeat: None,
+        metadata: asset_metadata(""),
+    };
+    match handle(&mut deps, mock_env("owner", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid asset metadata"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn test_update_asset() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+    feed_sscrt_at(&mut deps, 10, 100);
+
+    let msg = HandleMsg::UpdateAsset {
+        asset_info: sscrt(),
+        feeders: Some(vec![HumanAddr::from("feeder2"), HumanAddr::from("feeder3")]),
+        heartbeat: Some(60),
+        metadata: Some(AssetMetadata {
+            description: Some("Secret SCRT".to_string()),
+            ..asset_metadata("SSCRT")
+        }),
+    };
+
+    match handle(&mut deps, mock_env("addr", &[]), msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "update_asset"),
+            log("asset_key", "native_token_sscrt"),
+        ]
+    );
+
+    let asset = assets(&deps, None, Some(1)).remove(0);
+    assert_eq!(asset.metadata.description, Some("Secret SCRT".to_string()));
+    assert_eq!(
+        asset.feeders,
+        vec![HumanAddr::from("feeder2"), HumanAddr::from("feeder3")]
+    );
+    assert_eq!(asset.heartbeat, Some(60));
+
+    // The submission of the removed feeder is dropped
+    match latest_sscrt_price(&deps) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Quorum not reached: 0 of 1 prices")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // Unset settings are kept
+    let msg = HandleMsg::UpdateAsset {
+        asset_info: sscrt(),
+        feeders: None,
+        heartbeat: None,
+        metadata: None,
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    assert_eq!(assets(&deps, None, Some(1)).remove(0), asset);
+
+    for (feeders, error) in vec![
+        (vec![], "Can not remove the last feeder"),
+        (
+            vec![HumanAddr::from("feeder2"), HumanAddr::from("feeder2")],
+            "Feeder already registered",
+        ),
+    ] {
+        let msg = HandleMsg::UpdateAsset {
+            asset_info: sscrt(),
+            feeders: Some(feeders),
+            heartbeat: None,
+            metadata: None,
+        };
+        match handle(&mut deps, mock_env("owner", &[]), msg) {
+            Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, error),
+            _ => panic!("Must return generic error"),
+        }
+    }
+
+    let msg = HandleMsg::UpdateAsset {
+        asset_info: AssetInfo::NativeToken {
+            denom: "uatom".to_string(),
+        },
+        feeders: None,
+        heartbeat: None,
+        metadata: None,
+    };
+    match handle(&mut deps, mock_env("owner", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Asset not registered"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn test_deregister_asset() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+    feed_sscrt_at(&mut deps, 10, 100);
+
+    let msg = HandleMsg::DeregisterAsset {
+        asset_info: sscrt(),
+    };
+
+    match handle(&mut deps, mock_env("addr", &[]), msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = handle(&mut deps, mock_env("owner", &[]), msg.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "deregister_asset"),
+            log("asset_key", "native_token_sscrt"),
+        ]
+    );
+
+    assert_eq!(
+        asset_keys(assets(&deps, None, None)),
+        vec![
+            "native_token_sscrt2",
+            "snip20_token_snip20_test1",
+            "snip20_token_snip20_test2"
+        ]
+    );
+    assert_eq!(feeders(&deps), Vec::<HumanAddr>::new());
+
+    let mut env = mock_env("feeder1", &[]);
+    env.block.time = 110;
+    let feed = HandleMsg::FeedPrice {
+        prices: vec![(sscrt(), Decimal::one())],
+        quote_currency: None,
+    };
+    match handle(&mut deps, env, feed) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    match handle(&mut deps, mock_env("owner", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Asset not registered"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // The round ids continue after registering again
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: sscrt(),
+        feeder: HumanAddr::from("feeder1"),
+        heartbeat: None,
+        metadata: asset_metadata("SSCRT"),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    feed_sscrt_at(&mut deps, 11, 120);
+    assert_eq!(round_data(&deps, None).unwrap().round_id, 2);
 }
 
 #[test]
@@ -199,6 +482,7 @@ fn test_feed_price_failed_if_unauthorized() {
             },
             Decimal::from_str("0.1").unwrap(),
         )],
+        quote_currency: None,
     };
 
     let env = mock_env("addr", &[]);
@@ -224,6 +508,7 @@ fn test_feed_price_failed_if_unauthorized() {
                 Decimal::from_str("0.1").unwrap(),
             ),
         ],
+        quote_currency: None,
     };
 
     let env = mock_env("feeder1", &[]);
@@ -260,6 +545,7 @@ fn test_feed_price() {
                 Decimal::from_str("0.3").unwrap(),
             ),
         ],
+        quote_currency: None,
     };
 
     let env = mock_env("feeder1", &[]);
@@ -271,8 +557,10 @@ fn test_feed_price() {
             log("action", "feed_price"),
             log("asset_key", "native_token_sscrt"),
             log("price", "0.1"),
+            log("round_id", 1),
             log("asset_key", "snip20_token_snip20_test1"),
-            log("price", "0.3")
+            log("price", "0.3"),
+            log("round_id", 1),
         ]
     );
 
@@ -282,15 +570,22 @@ fn test_feed_price() {
             asset_info: AssetInfo::NativeToken {
                 denom: "sscrt".to_string(),
             },
+            time: None,
         },
     )
     .unwrap();
 
-    let price: PriceInfo = from_binary(&res).unwrap();
+    let price: LatestPriceResponse = from_binary(&res).unwrap();
     assert_eq!(
-        PriceInfo {
+        LatestPriceResponse {
             price: Decimal::from_str("0.1").unwrap(),
-            last_updated_time: env.block.time
+            last_updated_time: env.block.time,
+            quote_currency: "USD".to_string(),
+            decimals: 6,
+            feeder: HumanAddr::from("feeder1"),
+            heartbeat: None,
+            is_stale: false,
+            round_id: Some(1),
         },
         price
     );
@@ -303,16 +598,2007 @@ fn test_feed_price() {
                 token_code_hash: String::from("token_code_hash"),
                 viewing_key: String::from("viewing_key"),
             },
+            time: None,
         },
     )
     .unwrap();
 
-    let price: PriceInfo = from_binary(&res).unwrap();
+    let price: LatestPriceResponse = from_binary(&res).unwrap();
     assert_eq!(
-        PriceInfo {
+        LatestPriceResponse {
             price: Decimal::from_str("0.3").unwrap(),
-            last_updated_time: env.block.time
+            last_updated_time: env.block.time,
+            quote_currency: "USD".to_string(),
+            decimals: 6,
+            feeder: HumanAddr::from("feeder1"),
+            heartbeat: None,
+            is_stale: false,
+            round_id: Some(1),
         },
         price
     );
 }
+
+#[test]
+fn test_feed_price_units() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+
+    let feed = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+                price: &str,
+                quote_currency: Option<&str>| {
+        let msg = HandleMsg::FeedPrice {
+            prices: vec![(sscrt(), Decimal::from_str(price).unwrap())],
+            quote_currency: quote_currency.map(String::from),
+        };
+        handle(deps, mock_env("feeder1", &[]), msg)
+    };
+
+    match feed(&mut deps, "1.5", Some("SCRT")) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Quote currency mismatch"),
+        _ => panic!("Must return generic error"),
+    }
+    match feed(&mut deps, "1.0000001", None) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Price exceeds 6 decimals"),
+        _ => panic!("Must return generic error"),
+    }
+    feed(&mut deps, "1.000001", Some("USD")).unwrap();
+
+    let price = latest_sscrt_price(&deps).unwrap();
+    assert_eq!(price.quote_currency, "USD");
+    assert_eq!(price.decimals, 6);
+
+    let res = query(
+        &deps,
+        QueryMsg::Asset {
+            asset_info: sscrt(),
+        },
+    )
+    .unwrap();
+    let asset: AssetResponse = from_binary(&res).unwrap();
+    assert_eq!(asset.metadata, asset_metadata("SSCRT"));
+
+    // Prices in the previous unit are dropped
+    let msg = HandleMsg::UpdateAsset {
+        asset_info: sscrt(),
+        feeders: None,
+        heartbeat: None,
+        metadata: Some(AssetMetadata {
+            decimals: 8,
+            ..asset_metadata("SSCRT")
+        }),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    match latest_sscrt_price(&deps) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Quorum not reached: 0 of 1 prices")
+        }
+        _ => panic!("Must return generic error"),
+    }
+    feed(&mut deps, "1.0000001", None).unwrap();
+
+    let msg = HandleMsg::UpdateAsset {
+        asset_info: sscrt(),
+        feeders: None,
+        heartbeat: None,
+        metadata: Some(AssetMetadata {
+            decimals: 19,
+            ..asset_metadata("SSCRT")
+        }),
+    };
+    match handle(&mut deps, mock_env("owner", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid asset metadata"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+fn sscrt() -> AssetInfo {
+    AssetInfo::NativeToken {
+        denom: "sscrt".to_string(),
+    }
+}
+
+fn price_info(price: u64, time: u64) -> PriceInfo {
+    PriceInfo {
+        price: Decimal::from_ratio(price, 1u64),
+        last_updated_time: time,
+        quote_currency: "USD".to_string(),
+        decimals: 6,
+    }
+}
+
+fn feed_sscrt_at(deps: &mut Extern<MockStorage, MockApi, MockQuerier>, price: u64, time: u64) {
+    let mut env = mock_env("feeder1", &[]);
+    env.block.time = time;
+
+    let msg = HandleMsg::FeedPrice {
+        prices: vec![(sscrt(), Decimal::from_ratio(price, 1u64))],
+        quote_currency: None,
+    };
+    handle(deps, env, msg).unwrap();
+}
+
+fn sscrt2() -> AssetInfo {
+    AssetInfo::NativeToken {
+        denom: "sscrt2".to_string(),
+    }
+}
+
+#[test]
+fn test_subscriptions() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+
+    let subscribe_msg = |contract_addr: &str| HandleMsg::Subscribe {
+        asset_info: sscrt(),
+        contract_addr: HumanAddr::from(contract_addr),
+        code_hash: format!("{}_code_hash", contract_addr),
+    };
+    match handle(
+        &mut deps,
+        mock_env("consumer", &[]),
+        subscribe_msg("consumer"),
+    ) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = handle(&mut deps, mock_env("owner", &[]), subscribe_msg("consumer")).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "subscribe"),
+            log("asset_key", "native_token_sscrt"),
+            log("contract_addr", "consumer"),
+        ]
+    );
+    match handle(&mut deps, mock_env("owner", &[]), subscribe_msg("consumer")) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Already subscribed"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let res = query(
+        &deps,
+        QueryMsg::Subscriptions {
+            asset_info: sscrt(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<SubscriptionsResponse>(&res).unwrap(),
+        SubscriptionsResponse {
+            subscriptions: vec![Subscription {
+                contract_addr: HumanAddr::from("consumer"),
+                code_hash: "consumer_code_hash".to_string(),
+            }],
+        }
+    );
+
+    // Only the new round of the subscribed asset is pushed
+    let env = mock_env("feeder1", &[]);
+    let msg = HandleMsg::FeedPrice {
+        prices: vec![
+            (sscrt(), Decimal::from_str("1.5").unwrap()),
+            (
+                AssetInfo::Token {
+                    contract_addr: HumanAddr::from("snip20_test1"),
+                    token_code_hash: String::from("token_code_hash"),
+                    viewing_key: String::from("viewing_key"),
+                },
+                Decimal::from_str("2").unwrap(),
+            ),
+        ],
+        quote_currency: None,
+    };
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("consumer"),
+            callback_code_hash: "consumer_code_hash".to_string(),
+            msg: to_binary(&PriceHookMsg::PriceUpdated {
+                asset_info: sscrt(),
+                round_id: 1,
+                price: Decimal::from_str("1.5").unwrap(),
+                updated_at: env.block.time,
+            })
+            .unwrap(),
+            send: vec![],
+        })]
+    );
+
+    for index in 1..5 {
+        let msg = subscribe_msg(&format!("consumer{}", index));
+        handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    }
+    match handle(
+        &mut deps,
+        mock_env("owner", &[]),
+        subscribe_msg("consumer5"),
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Too many subscriptions"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // The consumer or the owner unsubscribes
+    let unsubscribe_msg = HandleMsg::Unsubscribe {
+        asset_info: sscrt(),
+        contract_addr: HumanAddr::from("consumer"),
+    };
+    match handle(&mut deps, mock_env("other", &[]), unsubscribe_msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+    handle(
+        &mut deps,
+        mock_env("consumer", &[]),
+        unsubscribe_msg.clone(),
+    )
+    .unwrap();
+    match handle(&mut deps, mock_env("owner", &[]), unsubscribe_msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Not subscribed"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn test_set_feeder_key() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    let key = compressed_pubkey(&signing_key(1));
+    let msg = HandleMsg::SetFeederKey {
+        feeder: HumanAddr::from("feeder1"),
+        pubkey: Some(key.clone()),
+    };
+    match handle(&mut deps, mock_env("feeder1", &[]), msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "set_feeder_key"),
+            log("feeder", "feeder1"),
+            log("enabled", true),
+        ]
+    );
+
+    let query_key = |deps: &Extern<MockStorage, MockApi, MockQuerier>| {
+        let res = query(
+            deps,
+            QueryMsg::FeederKey {
+                feeder: HumanAddr::from("feeder1"),
+            },
+        )
+        .unwrap();
+        from_binary::<FeederKeyResponse>(&res).unwrap()
+    };
+    assert_eq!(
+        query_key(&deps),
+        FeederKeyResponse {
+            pubkey: Some(key),
+            nonce: 0,
+        }
+    );
+
+    let msg = HandleMsg::SetFeederKey {
+        feeder: HumanAddr::from("feeder1"),
+        pubkey: Some(Binary::from(vec![1u8; 33])),
+    };
+    match handle(&mut deps, mock_env("owner", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid public key"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let msg = HandleMsg::SetFeederKey {
+        feeder: HumanAddr::from("feeder1"),
+        pubkey: None,
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    assert_eq!(query_key(&deps).pubkey, None);
+}
+
+#[test]
+fn test_submit_signed_prices() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+
+    let key = signing_key(1);
+    let msg = HandleMsg::SetFeederKey {
+        feeder: HumanAddr::from("feeder1"),
+        pubkey: Some(compressed_pubkey(&key)),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+
+    let now = mock_env("relayer", &[]).block.time;
+    let report =
+        |asset_info: AssetInfo, price: &str, timestamp: u64, nonce: u64| SignedPriceReport {
+            feeder: HumanAddr::from("feeder1"),
+            asset_info,
+            price: Decimal::from_str(price).unwrap(),
+            timestamp,
+            nonce,
+        };
+    let submit = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+                  reports: Vec<SignedPriceReport>,
+                  signatures: Vec<_>| {
+        let msg = HandleMsg::SubmitSignedPrices {
+            reports,
+            signatures,
+        };
+        handle(deps, mock_env("relayer", &[]), msg)
+    };
+    let expect_err = |res: HandleResult, expected: &str| match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, expected),
+        _ => panic!("Must return generic error"),
+    };
+
+    let first = report(sscrt(), "1.5", now - 10, 1);
+    let res = submit(
+        &mut deps,
+        vec![first.clone()],
+        vec![sign_report(&key, &first)],
+    )
+    .unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "submit_signed_prices"),
+            log("feeder", "feeder1"),
+            log("asset_key", "native_token_sscrt"),
+            log("price", "1.5"),
+            log("round_id", 1),
+        ]
+    );
+    // Recorded at the time of the report
+    let price = latest_sscrt_price(&deps).unwrap();
+    assert_eq!(price.price, Decimal::from_str("1.5").unwrap());
+    assert_eq!(price.last_updated_time, now - 10);
+
+    // Replayed report
+    expect_err(
+        submit(
+            &mut deps,
+            vec![first.clone()],
+            vec![sign_report(&key, &first)],
+        ),
+        "Nonce already used",
+    );
+
+    let older = report(sscrt(), "1.6", now - 20, 2);
+    expect_err(
+        submit(
+            &mut deps,
+            vec![older.clone()],
+            vec![sign_report(&key, &older)],
+        ),
+        "Price older than the latest submission",
+    );
+
+    // Signed by another key or tampered with
+    let second = report(sscrt(), "1.6", now, 2);
+    expect_err(
+        submit(
+            &mut deps,
+            vec![second.clone()],
+            vec![sign_report(&signing_key(2), &second)],
+        ),
+        "Invalid signature",
+    );
+    expect_err(
+        submit(
+            &mut deps,
+            vec![report(sscrt(), "1.7", now, 2)],
+            vec![sign_report(&key, &second)],
+        ),
+        "Invalid signature",
+    );
+    expect_err(
+        submit(&mut deps, vec![second.clone()], vec![]),
+        "Signature count mismatch",
+    );
+
+    // Signed for another oracle or another chain
+    let env = mock_env("relayer", &[]);
+    expect_err(
+        submit(
+            &mut deps,
+            vec![second.clone()],
+            vec![sign_report_for(
+                &key,
+                &second,
+                &HumanAddr::from("other_oracle"),
+                &env.block.chain_id,
+            )],
+        ),
+        "Invalid signature",
+    );
+    expect_err(
+        submit(
+            &mut deps,
+            vec![second.clone()],
+            vec![sign_report_for(
+                &key,
+                &second,
+                &env.contract.address,
+                "other-chain",
+            )],
+        ),
+        "Invalid signature",
+    );
+
+    let expired = report(sscrt(), "1.6", now - 301, 2);
+    expect_err(
+        submit(
+            &mut deps,
+            vec![expired.clone()],
+            vec![sign_report(&key, &expired)],
+        ),
+        "Report expired",
+    );
+    let future = report(sscrt(), "1.6", now + 1, 2);
+    expect_err(
+        submit(
+            &mut deps,
+            vec![future.clone()],
+            vec![sign_report(&key, &future)],
+        ),
+        "Report from the future",
+    );
+
+    // feeder1 does not feed sscrt2
+    let other_asset = report(sscrt2(), "1.6", now, 2);
+    match submit(
+        &mut deps,
+        vec![other_asset.clone()],
+        vec![sign_report(&key, &other_asset)],
+    ) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let unregistered = SignedPriceReport {
+        feeder: HumanAddr::from("feeder2"),
+        ..report(sscrt2(), "1.6", now, 1)
+    };
+    expect_err(
+        submit(
+            &mut deps,
+            vec![unregistered.clone()],
+            vec![sign_report(&key, &unregistered)],
+        ),
+        "Feeder key not registered",
+    );
+
+    // Nonces increase within a batch
+    let third = report(sscrt(), "1.7", now, 5);
+    submit(
+        &mut deps,
+        vec![second.clone(), third.clone()],
+        vec![sign_report(&key, &second), sign_report(&key, &third)],
+    )
+    .unwrap();
+    assert_eq!(
+        latest_sscrt_price(&deps).unwrap().price,
+        Decimal::from_str("1.7").unwrap()
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::FeederKey {
+            feeder: HumanAddr::from("feeder1"),
+        },
+    )
+    .unwrap();
+    assert_eq!(from_binary::<FeederKeyResponse>(&res).unwrap().nonce, 5);
+}
+
+#[test]
+fn test_latest_prices() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+    feed_sscrt_at(&mut deps, 10, 100);
+
+    let res = query(
+        &deps,
+        QueryMsg::LatestPrices {
+            asset_infos: vec![sscrt(), sscrt2()],
+            time: Some(100),
+        },
+    )
+    .unwrap();
+    let prices = from_binary::<LatestPricesResponse>(&res).unwrap().prices;
+
+    assert_eq!(prices[0].asset_info, sscrt());
+    assert_eq!(
+        prices[0].price.as_ref().map(|price| price.price),
+        Some(Decimal::from_ratio(10u64, 1u64))
+    );
+    assert_eq!(prices[0].error, None);
+    assert_eq!(
+        prices[1],
+        LatestPriceResult {
+            asset_info: sscrt2(),
+            price: None,
+            error: Some("Quorum not reached: 0 of 1 prices".to_string()),
+        }
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::LatestPrices {
+            asset_infos: vec![sscrt(); 31],
+            time: None,
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Too many assets"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn test_cross_rate() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+    feed_sscrt_at(&mut deps, 10, 100);
+
+    let mut env = mock_env("feeder2", &[]);
+    env.block.time = 90;
+    let feed = HandleMsg::FeedPrice {
+        prices: vec![(sscrt2(), Decimal::from_ratio(4u64, 1u64))],
+        quote_currency: None,
+    };
+    handle(&mut deps, env.clone(), feed.clone()).unwrap();
+
+    let cross_rate = |deps: &Extern<MockStorage, MockApi, MockQuerier>, base: AssetInfo| {
+        query(
+            deps,
+            QueryMsg::CrossRate {
+                base,
+                quote: sscrt2(),
+                time: None,
+            },
+        )
+        .and_then(|res| from_binary::<CrossRateResponse>(&res))
+    };
+
+    assert_eq!(
+        cross_rate(&deps, sscrt()).unwrap(),
+        CrossRateResponse {
+            rate: Decimal::from_str("2.5").unwrap(),
+            last_updated_time: 90,
+            is_stale: false,
+        }
+    );
+
+    let base = AssetInfo::NativeToken {
+        denom: "uatom".to_string(),
+    };
+    match cross_rate(&deps, base) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Asset not registered"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let msg = HandleMsg::UpdateAsset {
+        asset_info: sscrt2(),
+        feeders: None,
+        heartbeat: None,
+        metadata: Some(AssetMetadata {
+            quote_currency: "SCRT".to_string(),
+            ..asset_metadata("SSCRT2")
+        }),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    handle(&mut deps, env, feed).unwrap();
+    match cross_rate(&deps, sscrt()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Quote currency mismatch"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+fn price_history(
+    deps: &Extern<MockStorage, MockApi, MockQuerier>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Vec<PriceInfo> {
+    let res = query(
+        deps,
+        QueryMsg::PriceHistory {
+            asset_info: sscrt(),
+            start_after,
+            limit,
+        },
+    )
+    .unwrap();
+
+    let history: PriceHistoryResponse = from_binary(&res).unwrap();
+    history.prices
+}
+
+#[test]
+fn test_price_history() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+
+    assert_eq!(price_history(&deps, None, None), vec![]);
+
+    feed_sscrt_at(&mut deps, 1, 100);
+    feed_sscrt_at(&mut deps, 2, 110);
+    // A later price in the same block replaces the price of its round
+    feed_sscrt_at(&mut deps, 3, 110);
+    feed_sscrt_at(&mut deps, 4, 120);
+
+    assert_eq!(
+        price_history(&deps, None, None),
+        vec![price_info(1, 100), price_info(3, 110), price_info(4, 120)]
+    );
+    assert_eq!(
+        price_history(&deps, None, Some(2)),
+        vec![price_info(1, 100), price_info(3, 110)]
+    );
+    assert_eq!(
+        price_history(&deps, Some(110), None),
+        vec![price_info(4, 120)]
+    );
+    assert_eq!(price_history(&deps, Some(120), None), vec![]);
+
+    // The other assets have their own history
+    let res = query(
+        &deps,
+        QueryMsg::PriceHistory {
+            asset_info: AssetInfo::NativeToken {
+                denom: "sscrt2".to_string(),
+            },
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let history: PriceHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(history.prices, vec![]);
+
+    // Retained rounds keep the unit they were recorded in
+    let msg = HandleMsg::UpdateAsset {
+        asset_info: sscrt(),
+        feeders: None,
+        heartbeat: None,
+        metadata: Some(AssetMetadata {
+            decimals: 2,
+            ..asset_metadata("SCRT")
+        }),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    feed_sscrt_at(&mut deps, 5, 130);
+
+    assert_eq!(
+        price_history(&deps, Some(110), None),
+        vec![
+            price_info(4, 120),
+            PriceInfo {
+                decimals: 2,
+                ..price_info(5, 130)
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_price_at() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+
+    for (index, time) in [100, 110, 120, 130].iter().enumerate() {
+        feed_sscrt_at(&mut deps, index as u64 + 1, *time);
+    }
+
+    let price_at = |deps: &Extern<MockStorage, MockApi, MockQuerier>, timestamp: u64| {
+        query(
+            deps,
+            QueryMsg::PriceAt {
+                asset_info: sscrt(),
+                timestamp,
+            },
+        )
+        .and_then(|res| from_binary::<PriceInfo>(&res))
+    };
+
+    assert_eq!(price_at(&deps, 100).unwrap(), price_info(1, 100));
+    assert_eq!(price_at(&deps, 119).unwrap(), price_info(2, 110));
+    assert_eq!(price_at(&deps, 1000).unwrap(), price_info(4, 130));
+    match price_at(&deps, 99) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No price at 99"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn test_history_retention() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner"),
+        history_retention: Some(0),
+        quorum: None,
+        max_price_age: None,
+        guardian: None,
+        restrict_reads: None,
+        access_fee: None,
+    };
+    match init(&mut deps, mock_env("addr", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "History retention must be positive")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner"),
+        history_retention: Some(3),
+        quorum: None,
+        max_price_age: None,
+        guardian: None,
+        restrict_reads: None,
+        access_fee: None,
+    };
+    init(&mut deps, mock_env("addr", &[]), msg).unwrap();
+
+    register_test_assets(&mut deps);
+
+    for time in 1..=5 {
+        feed_sscrt_at(&mut deps, time, time * 10);
+    }
+    assert_eq!(
+        price_history(&deps, None, None),
+        vec![price_info(3, 30), price_info(4, 40), price_info(5, 50)]
+    );
+
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        history_retention: Some(2),
+        quorum: None,
+        max_price_age: None,
+        guardian: None,
+        remove_guardian: None,
+        restrict_reads: None,
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+
+    let res = query(&deps, QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.history_retention, 2);
+
+    feed_sscrt_at(&mut deps, 6, 60);
+    assert_eq!(
+        price_history(&deps, None, None),
+        vec![price_info(5, 50), price_info(6, 60)]
+    );
+}
+
+fn round_data(
+    deps: &Extern<MockStorage, MockApi, MockQuerier>,
+    round_id: Option<u64>,
+) -> StdResult<RoundData> {
+    let msg = match round_id {
+        Some(round_id) => QueryMsg::GetRoundData {
+            asset_info: sscrt(),
+            round_id,
+        },
+        None => QueryMsg::LatestRoundData {
+            asset_info: sscrt(),
+        },
+    };
+    query(deps, msg).and_then(|res| from_binary(&res))
+}
+
+#[test]
+fn test_round_data() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner"),
+        history_retention: Some(3),
+        quorum: Some(2),
+        max_price_age: None,
+        guardian: None,
+        restrict_reads: None,
+        access_fee: None,
+    };
+    init(&mut deps, mock_env("addr", &[]), msg).unwrap();
+
+    register_test_assets(&mut deps);
+    let msg = HandleMsg::AddFeeder {
+        asset_info: sscrt(),
+        feeder: HumanAddr::from("feeder2"),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+
+    // No round before the quorum is reached
+    feed_sscrt_at(&mut deps, 10, 100);
+    match round_data(&deps, None) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No round data"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let mut env = mock_env("feeder2", &[]);
+    env.block.time = 105;
+    let msg = HandleMsg::FeedPrice {
+        prices: vec![(sscrt(), Decimal::from_ratio(12u64, 1u64))],
+        quote_currency: None,
+    };
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(res.log.last(), Some(&log("round_id", 1)));
+
+    let first_round = RoundData {
+        round_id: 1,
+        price: Decimal::from_ratio(10u64, 1u64),
+        started_at: 100,
+        updated_at: 105,
+        quote_currency: "USD".to_string(),
+        decimals: 6,
+    };
+    assert_eq!(round_data(&deps, None).unwrap(), first_round);
+    assert_eq!(round_data(&deps, Some(1)).unwrap(), first_round);
+
+    // A feed in a new block records a round
+    let feed = |price: u64| HandleMsg::FeedPrice {
+        prices: vec![(sscrt(), Decimal::from_ratio(price, 1u64))],
+        quote_currency: None,
+    };
+    let mut env = mock_env("feeder1", &[]);
+    env.block.time = 110;
+    let res = handle(&mut deps, env.clone(), feed(11)).unwrap();
+    assert_eq!(res.log.last(), Some(&log("round_id", 2)));
+
+    let second_round = RoundData {
+        round_id: 2,
+        price: Decimal::from_ratio(11u64, 1u64),
+        started_at: 105,
+        updated_at: 110,
+        quote_currency: "USD".to_string(),
+        decimals: 6,
+    };
+    assert_eq!(round_data(&deps, None).unwrap(), second_round);
+
+    // A later feed in the same block replaces the price of the round
+    let res = handle(&mut deps, env, feed(14)).unwrap();
+    assert_eq!(res.log.last(), Some(&log("round_id", 2)));
+    let second_round = RoundData {
+        price: Decimal::from_ratio(12u64, 1u64),
+        ..second_round
+    };
+    assert_eq!(round_data(&deps, None).unwrap(), second_round);
+
+    let res = query(
+        &deps,
+        QueryMsg::LatestPrice {
+            asset_info: sscrt(),
+            time: None,
+        },
+    )
+    .unwrap();
+    let latest_price: LatestPriceResponse = from_binary(&res).unwrap();
+    assert_eq!(latest_price.price, Decimal::from_ratio(12u64, 1u64));
+    assert_eq!(latest_price.round_id, Some(2));
+
+    for time in 12..=13 {
+        feed_sscrt_at(&mut deps, time, time * 10);
+    }
+    assert_eq!(round_data(&deps, None).unwrap().round_id, 4);
+
+    let res = query(
+        &deps,
+        QueryMsg::LatestPrice {
+            asset_info: sscrt(),
+            time: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<LatestPriceResponse>(&res).unwrap().round_id,
+        Some(4)
+    );
+
+    // Rounds below the retained range are pruned, those above it are not recorded yet
+    for round_id in &[1, 5] {
+        match round_data(&deps, Some(*round_id)) {
+            Err(StdError::GenericErr { msg, .. }) => {
+                assert_eq!(msg, format!("Round {} not found", round_id))
+            }
+            _ => panic!("Must return generic error"),
+        }
+    }
+    assert_eq!(round_data(&deps, Some(2)).unwrap(), second_round);
+}
+
+fn feeders(deps: &Extern<MockStorage, MockApi, MockQuerier>) -> Vec<HumanAddr> {
+    let res = query(
+        deps,
+        QueryMsg::Feeders {
+            asset_info: sscrt(),
+        },
+    )
+    .unwrap();
+    let res: FeedersResponse = from_binary(&res).unwrap();
+    res.feeders
+}
+
+#[test]
+fn test_add_and_remove_feeder() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    let add_feeder = |feeder: &str| HandleMsg::AddFeeder {
+        asset_info: sscrt(),
+        feeder: HumanAddr::from(feeder),
+    };
+    let remove_feeder = |feeder: &str| HandleMsg::RemoveFeeder {
+        asset_info: sscrt(),
+        feeder: HumanAddr::from(feeder),
+    };
+
+    match handle(&mut deps, mock_env("owner", &[]), add_feeder("feeder2")) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Asset not registered"),
+        _ => panic!("Must return generic error"),
+    }
+
+    register_test_assets(&mut deps);
+
+    match handle(&mut deps, mock_env("addr", &[]), add_feeder("feeder2")) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = handle(&mut deps, mock_env("owner", &[]), add_feeder("feeder2")).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "add_feeder"),
+            log("asset_key", "native_token_sscrt"),
+            log("feeder", "feeder2")
+        ]
+    );
+    assert_eq!(
+        feeders(&deps),
+        vec![HumanAddr::from("feeder1"), HumanAddr::from("feeder2")]
+    );
+
+    match handle(&mut deps, mock_env("owner", &[]), add_feeder("feeder2")) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Feeder already registered"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // The removed feeder can not feed and its submission is dropped
+    feed_sscrt_at(&mut deps, 1, 100);
+    handle(&mut deps, mock_env("owner", &[]), remove_feeder("feeder1")).unwrap();
+    assert_eq!(feeders(&deps), vec![HumanAddr::from("feeder2")]);
+    match query(
+        &deps,
+        QueryMsg::LatestPrice {
+            asset_info: sscrt(),
+            time: None,
+        },
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Quorum not reached: 0 of 1 prices")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    match handle(&mut deps, mock_env("owner", &[]), remove_feeder("feeder1")) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Feeder not registered"),
+        _ => panic!("Must return generic error"),
+    }
+    match handle(&mut deps, mock_env("owner", &[]), remove_feeder("feeder2")) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Can not remove the last feeder")
+        }
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn test_latest_price_with_quorum() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner"),
+        history_retention: None,
+        quorum: Some(2),
+        max_price_age: Some(60),
+        guardian: None,
+        restrict_reads: None,
+        access_fee: None,
+    };
+    init(&mut deps, mock_env("addr", &[]), msg).unwrap();
+
+    register_test_assets(&mut deps);
+    for feeder in &["feeder2", "feeder3", "feeder4"] {
+        let msg = HandleMsg::AddFeeder {
+            asset_info: sscrt(),
+            feeder: HumanAddr::from(*feeder),
+        };
+        handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    }
+
+    let feed = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+                feeder: &str,
+                price: u64,
+                time: u64| {
+        let mut env = mock_env(feeder, &[]);
+        env.block.time = time;
+        let msg = HandleMsg::FeedPrice {
+            prices: vec![(sscrt(), Decimal::from_ratio(price, 1u64))],
+            quote_currency: None,
+        };
+        handle(deps, env, msg).unwrap();
+    };
+    let latest_price = |deps: &Extern<MockStorage, MockApi, MockQuerier>| {
+        query(
+            deps,
+            QueryMsg::LatestPrice {
+                asset_info: sscrt(),
+                time: None,
+            },
+        )
+        .and_then(|res| from_binary::<LatestPriceResponse>(&res))
+        .map(|res| PriceInfo {
+            price: res.price,
+            last_updated_time: res.last_updated_time,
+            quote_currency: res.quote_currency,
+            decimals: res.decimals,
+        })
+    };
+
+    feed(&mut deps, "feeder1", 10, 100);
+    match latest_price(&deps) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Quorum not reached: 1 of 2 prices")
+        }
+        _ => panic!("Must return generic error"),
+    }
+    assert_eq!(price_history(&deps, None, None), vec![]);
+
+    // Lower median of an even count
+    feed(&mut deps, "feeder2", 12, 110);
+    assert_eq!(latest_price(&deps).unwrap(), price_info(10, 110));
+
+    feed(&mut deps, "feeder3", 11, 120);
+    feed(&mut deps, "feeder4", 30, 120);
+    assert_eq!(latest_price(&deps).unwrap(), price_info(11, 120));
+
+    // The submission of feeder1 is older than 60 seconds
+    feed(&mut deps, "feeder3", 13, 170);
+    assert_eq!(latest_price(&deps).unwrap(), price_info(13, 170));
+
+    // Without new submissions, those of feeder2 and feeder4 get too old as well
+    let res = query(
+        &deps,
+        QueryMsg::LatestPrice {
+            asset_info: sscrt(),
+            time: Some(181),
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Quorum not reached: 1 of 2 prices")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    assert_eq!(
+        price_history(&deps, None, None),
+        vec![
+            price_info(10, 110),
+            price_info(11, 120),
+            price_info(13, 170)
+        ]
+    );
+}
+
+fn price_status(deps: &Extern<MockStorage, MockApi, MockQuerier>) -> PriceStatusResponse {
+    let res = query(
+        deps,
+        QueryMsg::PriceStatus {
+            asset_info: sscrt(),
+        },
+    )
+    .unwrap();
+    from_binary(&res).unwrap()
+}
+
+fn latest_sscrt_price(deps: &Extern<MockStorage, MockApi, MockQuerier>) -> StdResult<PriceInfo> {
+    query(
+        deps,
+        QueryMsg::LatestPrice {
+            asset_info: sscrt(),
+            time: None,
+        },
+    )
+    .and_then(|res| from_binary::<LatestPriceResponse>(&res))
+    .map(|res| PriceInfo {
+        price: res.price,
+        last_updated_time: res.last_updated_time,
+        quote_currency: res.quote_currency,
+        decimals: res.decimals,
+    })
+}
+
+#[test]
+fn test_set_price_bounds() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+
+    let bounds = PriceBounds {
+        max_deviation: Some(Decimal::percent(10)),
+        min_price: Some(Decimal::percent(50)),
+        max_price: Some(Decimal::from_ratio(100u64, 1u64)),
+    };
+    let msg = HandleMsg::SetPriceBounds {
+        asset_info: sscrt(),
+        bounds: bounds.clone(),
+    };
+
+    match handle(&mut deps, mock_env("addr", &[]), msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let invalid = HandleMsg::SetPriceBounds {
+        asset_info: sscrt(),
+        bounds: PriceBounds {
+            min_price: Some(Decimal::from_ratio(200u64, 1u64)),
+            ..bounds.clone()
+        },
+    };
+    match handle(&mut deps, mock_env("owner", &[]), invalid) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid price bounds"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let res = handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "set_price_bounds"),
+            log("asset_key", "native_token_sscrt"),
+        ]
+    );
+    assert_eq!(
+        price_status(&deps),
+        PriceStatusResponse {
+            status: PriceStatus::Active,
+            bounds,
+            pending_price: None,
+        }
+    );
+}
+
+#[test]
+fn test_circuit_breaker() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+
+    let msg = HandleMsg::SetPriceBounds {
+        asset_info: sscrt(),
+        bounds: PriceBounds {
+            max_deviation: Some(Decimal::percent(10)),
+            min_price: None,
+            max_price: Some(Decimal::from_ratio(100u64, 1u64)),
+        },
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+
+    let confirm = HandleMsg::ConfirmPendingPrice {
+        asset_info: sscrt(),
+    };
+    let reject = HandleMsg::RejectPendingPrice {
+        asset_info: sscrt(),
+    };
+
+    match handle(&mut deps, mock_env("owner", &[]), confirm.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No pending price"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // Without a current price only the bounds are checked
+    feed_sscrt_at(&mut deps, 10, 100);
+    assert_eq!(latest_sscrt_price(&deps).unwrap(), price_info(10, 100));
+
+    // A zero price is always held
+    let mut env = mock_env("feeder1", &[]);
+    env.block.time = 110;
+    let msg = HandleMsg::FeedPrice {
+        prices: vec![(sscrt(), Decimal::zero())],
+        quote_currency: None,
+    };
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "feed_price"),
+            log("asset_key", "native_token_sscrt"),
+            log("price", "0"),
+            log("status", "halted"),
+        ]
+    );
+    assert_eq!(price_status(&deps).status, PriceStatus::Halted);
+    assert_eq!(
+        price_status(&deps).pending_price,
+        Some(PendingPrice {
+            feeder: HumanAddr::from("feeder1"),
+            price: Decimal::zero(),
+            last_updated_time: 110,
+        })
+    );
+    match latest_sscrt_price(&deps) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Price feed halted"),
+        _ => panic!("Must return generic error"),
+    }
+
+    match handle(&mut deps, mock_env("addr", &[]), reject.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+    let res = handle(&mut deps, mock_env("owner", &[]), reject.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "reject_pending_price"),
+            log("asset_key", "native_token_sscrt"),
+            log("price", "0"),
+        ]
+    );
+    assert_eq!(latest_sscrt_price(&deps).unwrap(), price_info(10, 100));
+
+    // Within 10% of the current price
+    feed_sscrt_at(&mut deps, 11, 120);
+    assert_eq!(latest_sscrt_price(&deps).unwrap(), price_info(11, 120));
+
+    // Deviates more than 10%, confirmed by the owner
+    feed_sscrt_at(&mut deps, 13, 130);
+    assert_eq!(price_status(&deps).status, PriceStatus::Halted);
+    handle(&mut deps, mock_env("owner", &[]), confirm).unwrap();
+    assert_eq!(price_status(&deps).status, PriceStatus::Active);
+    assert_eq!(latest_sscrt_price(&deps).unwrap(), price_info(13, 130));
+
+    // Above the maximum price
+    feed_sscrt_at(&mut deps, 101, 140);
+    assert_eq!(price_status(&deps).status, PriceStatus::Halted);
+
+    // Subscribers are not notified of the rounds recorded while a price is held
+    let msg = HandleMsg::Subscribe {
+        asset_info: sscrt(),
+        contract_addr: HumanAddr::from("consumer"),
+        code_hash: "consumer_code_hash".to_string(),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    let mut env = mock_env("feeder1", &[]);
+    env.block.time = 150;
+    let msg = HandleMsg::FeedPrice {
+        prices: vec![(sscrt(), Decimal::from_ratio(12u64, 1u64))],
+        quote_currency: None,
+    };
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "feed_price"),
+            log("asset_key", "native_token_sscrt"),
+            log("price", "12"),
+            log("round_id", 4),
+        ]
+    );
+    assert_eq!(price_status(&deps).status, PriceStatus::Halted);
+    handle(&mut deps, mock_env("owner", &[]), reject).unwrap();
+
+    assert_eq!(
+        price_history(&deps, None, None),
+        vec![
+            price_info(10, 100),
+            price_info(11, 120),
+            price_info(13, 130),
+            price_info(12, 150)
+        ]
+    );
+}
+
+#[test]
+fn test_pause() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        guardian: Some(HumanAddr::from("guardian")),
+        remove_guardian: None,
+        history_retention: None,
+        quorum: None,
+        max_price_age: None,
+        restrict_reads: None,
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+
+    feed_sscrt_at(&mut deps, 10, 100);
+
+    let pause_sscrt = HandleMsg::Pause {
+        asset_info: Some(sscrt()),
+    };
+    let unpause_sscrt = HandleMsg::Unpause {
+        asset_info: Some(sscrt()),
+    };
+
+    match handle(&mut deps, mock_env("feeder1", &[]), pause_sscrt.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = handle(&mut deps, mock_env("guardian", &[]), pause_sscrt.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "pause"),
+            log("asset_key", "native_token_sscrt")
+        ]
+    );
+    match handle(&mut deps, mock_env("owner", &[]), pause_sscrt) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Already paused"),
+        _ => panic!("Must return generic error"),
+    }
+    assert_eq!(price_status(&deps).status, PriceStatus::Paused);
+
+    let mut env = mock_env("feeder1", &[]);
+    env.block.time = 110;
+    let msg = HandleMsg::FeedPrice {
+        prices: vec![(sscrt(), Decimal::from_ratio(11u64, 1u64))],
+        quote_currency: None,
+    };
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Feed paused"),
+        _ => panic!("Must return generic error"),
+    }
+    match latest_sscrt_price(&deps) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Feed paused"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // Other assets are still fed
+    let mut env = mock_env("feeder2", &[]);
+    env.block.time = 110;
+    let msg = HandleMsg::FeedPrice {
+        prices: vec![(sscrt2(), Decimal::from_ratio(20u64, 1u64))],
+        quote_currency: None,
+    };
+    handle(&mut deps, env, msg).unwrap();
+
+    // Only the owner unpauses
+    match handle(&mut deps, mock_env("guardian", &[]), unpause_sscrt.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+    let res = handle(&mut deps, mock_env("owner", &[]), unpause_sscrt.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "unpause"),
+            log("asset_key", "native_token_sscrt")
+        ]
+    );
+    match handle(&mut deps, mock_env("owner", &[]), unpause_sscrt) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Not paused"),
+        _ => panic!("Must return generic error"),
+    }
+    assert_eq!(latest_sscrt_price(&deps).unwrap(), price_info(10, 100));
+
+    // The global pause covers all assets
+    let res = handle(
+        &mut deps,
+        mock_env("guardian", &[]),
+        HandleMsg::Pause { asset_info: None },
+    )
+    .unwrap();
+    assert_eq!(res.log, vec![log("action", "pause")]);
+
+    let res = query(&deps, QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.guardian, Some(HumanAddr::from("guardian")));
+    assert!(config.paused);
+
+    let res = query(
+        &deps,
+        QueryMsg::LatestPrices {
+            asset_infos: vec![sscrt(), sscrt2()],
+            time: None,
+        },
+    )
+    .unwrap();
+    let res: LatestPricesResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res.prices
+            .into_iter()
+            .map(|result| result.error)
+            .collect::<Vec<Option<String>>>(),
+        vec![
+            Some("Feed paused".to_string()),
+            Some("Feed paused".to_string())
+        ]
+    );
+
+    let mut env = mock_env("feeder1", &[]);
+    env.block.time = 120;
+    let msg = HandleMsg::FeedPrice {
+        prices: vec![(sscrt(), Decimal::from_ratio(12u64, 1u64))],
+        quote_currency: None,
+    };
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Feed paused"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let res = handle(
+        &mut deps,
+        mock_env("owner", &[]),
+        HandleMsg::Unpause { asset_info: None },
+    )
+    .unwrap();
+    assert_eq!(res.log, vec![log("action", "unpause")]);
+    feed_sscrt_at(&mut deps, 12, 120);
+    assert_eq!(latest_sscrt_price(&deps).unwrap(), price_info(12, 120));
+    assert_eq!(price_status(&deps).status, PriceStatus::Active);
+
+    // An update without the guardian keeps it
+    let update_guardian =
+        |guardian: Option<&str>, remove_guardian: Option<bool>| HandleMsg::UpdateConfig {
+            owner: None,
+            guardian: guardian.map(HumanAddr::from),
+            remove_guardian,
+            history_retention: None,
+            quorum: None,
+            max_price_age: None,
+            restrict_reads: None,
+        };
+    handle(
+        &mut deps,
+        mock_env("owner", &[]),
+        update_guardian(None, None),
+    )
+    .unwrap();
+    let res = query(&deps, QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.guardian, Some(HumanAddr::from("guardian")));
+
+    match handle(
+        &mut deps,
+        mock_env("owner", &[]),
+        update_guardian(Some("guardian2"), Some(true)),
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cannot set and remove the guardian at once")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    handle(
+        &mut deps,
+        mock_env("owner", &[]),
+        update_guardian(None, Some(true)),
+    )
+    .unwrap();
+    let res = query(&deps, QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.guardian, None);
+    match handle(
+        &mut deps,
+        mock_env("guardian", &[]),
+        HandleMsg::Pause { asset_info: None },
+    ) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+}
+
+fn restrict_reads(deps: &mut Extern<MockStorage, MockApi, MockQuerier>) {
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        guardian: None,
+        remove_guardian: None,
+        history_retention: None,
+        quorum: None,
+        max_price_age: None,
+        restrict_reads: Some(true),
+    };
+    handle(deps, mock_env("owner", &[]), msg).unwrap();
+
+    let msg = HandleMsg::SetViewingKey {
+        key: "key".to_string(),
+        padding: None,
+    };
+    let res = handle(deps, mock_env("consumer", &[]), msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![log("action", "set_viewing_key"), log("success", true)]
+    );
+}
+
+fn consumer_query(key: &str, query: QueryMsg) -> QueryMsg {
+    QueryMsg::WithViewingKey {
+        consumer: HumanAddr::from("consumer"),
+        key: key.to_string(),
+        query: Box::new(query),
+    }
+}
+
+fn latest_sscrt_price_of_consumer(
+    deps: &Extern<MockStorage, MockApi, MockQuerier>,
+) -> StdResult<Decimal> {
+    let msg = consumer_query(
+        "key",
+        QueryMsg::LatestPrice {
+            asset_info: sscrt(),
+            time: None,
+        },
+    );
+    query(deps, msg)
+        .and_then(|res| from_binary::<LatestPriceResponse>(&res))
+        .map(|res| res.price)
+}
+
+#[test]
+fn test_restricted_reads() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+
+    feed_sscrt_at(&mut deps, 10, 100);
+    restrict_reads(&mut deps);
+
+    match latest_sscrt_price(&deps) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Authenticated query required"),
+        _ => panic!("Must return generic error"),
+    }
+    // Assets stay public
+    query(
+        &deps,
+        QueryMsg::Asset {
+            asset_info: sscrt(),
+        },
+    )
+    .unwrap();
+
+    let latest_price = QueryMsg::LatestPrice {
+        asset_info: sscrt(),
+        time: None,
+    };
+    match query(&deps, consumer_query("wrong", latest_price.clone())) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid viewing key"),
+        _ => panic!("Must return generic error"),
+    }
+    match latest_sscrt_price_of_consumer(&deps) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Read access denied"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let add_consumer = HandleMsg::AddConsumer {
+        consumer: HumanAddr::from("consumer"),
+    };
+    match handle(&mut deps, mock_env("consumer", &[]), add_consumer.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+    let res = handle(&mut deps, mock_env("owner", &[]), add_consumer.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![log("action", "add_consumer"), log("consumer", "consumer")]
+    );
+    match handle(&mut deps, mock_env("owner", &[]), add_consumer) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Consumer already allowed"),
+        _ => panic!("Must return generic error"),
+    }
+    assert_eq!(
+        latest_sscrt_price_of_consumer(&deps).unwrap(),
+        Decimal::from_ratio(10u64, 1u64)
+    );
+
+    let nested = consumer_query("key", consumer_query("key", latest_price));
+    match query(&deps, nested) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Nested authenticated query"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let remove_consumer = HandleMsg::RemoveConsumer {
+        consumer: HumanAddr::from("consumer"),
+    };
+    let res = handle(&mut deps, mock_env("owner", &[]), remove_consumer.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "remove_consumer"),
+            log("consumer", "consumer")
+        ]
+    );
+    match handle(&mut deps, mock_env("owner", &[]), remove_consumer) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Consumer not allowed"),
+        _ => panic!("Must return generic error"),
+    }
+    match latest_sscrt_price_of_consumer(&deps) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Read access denied"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+fn payment_env(sender: &str, amount: u128, time: u64) -> Env {
+    let mut env = mock_env(sender, &[Coin::new(amount, "uscrt")]);
+    env.block.time = time;
+    env
+}
+
+fn feeder_rewards(deps: &Extern<MockStorage, MockApi, MockQuerier>, feeder: &str) -> Uint128 {
+    let res = query(
+        deps,
+        QueryMsg::FeederRewards {
+            feeder: HumanAddr::from(feeder),
+        },
+    )
+    .unwrap();
+    from_binary::<FeederRewardsResponse>(&res).unwrap().rewards
+}
+
+#[test]
+fn test_pay_access_and_claim_feeder_rewards() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    register_test_assets(&mut deps);
+
+    restrict_reads(&mut deps);
+
+    let pay = HandleMsg::PayAccess { consumer: None };
+    match handle(&mut deps, payment_env("consumer", 100, 100), pay.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Access fee not set"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let set_access_fee = |denom: &str, amount: u128| HandleMsg::SetAccessFee {
+        access_fee: Some(AccessFee {
+            asset_info: AssetInfo::NativeToken {
+                denom: denom.to_string(),
+            },
+            amount: Uint128(amount),
+            period: 60,
+        }),
+    };
+    match handle(
+        &mut deps,
+        mock_env("consumer", &[]),
+        set_access_fee("uscrt", 100),
+    ) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+    match handle(
+        &mut deps,
+        mock_env("owner", &[]),
+        set_access_fee("uscrt", 0),
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid access fee"),
+        _ => panic!("Must return generic error"),
+    }
+    let res = handle(
+        &mut deps,
+        mock_env("owner", &[]),
+        set_access_fee("uscrt", 100),
+    )
+    .unwrap();
+    assert_eq!(res.log, vec![log("action", "set_access_fee")]);
+
+    match handle(&mut deps, payment_env("consumer", 50, 100), pay.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Payment below the access fee")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // Two periods from now, the remainder of the split goes to the first feeder
+    let res = handle(&mut deps, payment_env("consumer", 205, 100), pay).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "pay_access"),
+            log("consumer", "consumer"),
+            log("amount", 205),
+            log("paid_until", 220),
+        ]
+    );
+    let res = query(
+        &deps,
+        QueryMsg::Consumer {
+            consumer: HumanAddr::from("consumer"),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<ConsumerResponse>(&res).unwrap(),
+        ConsumerResponse {
+            allowed: false,
+            paid_until: 220,
+        }
+    );
+    assert_eq!(feeder_rewards(&deps, "feeder1"), Uint128(103));
+    assert_eq!(feeder_rewards(&deps, "feeder2"), Uint128(102));
+
+    // The access ends once a price is fed at its end
+    feed_sscrt_at(&mut deps, 10, 200);
+    assert_eq!(
+        latest_sscrt_price_of_consumer(&deps).unwrap(),
+        Decimal::from_ratio(10u64, 1u64)
+    );
+    feed_sscrt_at(&mut deps, 11, 220);
+    match latest_sscrt_price_of_consumer(&deps) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Read access denied"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // Paid by another address, from the block time after expiry
+    let msg = HandleMsg::PayAccess {
+        consumer: Some(HumanAddr::from("consumer")),
+    };
+    let res = handle(&mut deps, payment_env("sponsor", 100, 230), msg).unwrap();
+    assert_eq!(res.log[3], log("paid_until", 290));
+    assert_eq!(
+        latest_sscrt_price_of_consumer(&deps).unwrap(),
+        Decimal::from_ratio(11u64, 1u64)
+    );
+
+    match handle(
+        &mut deps,
+        mock_env("owner", &[]),
+        set_access_fee("uusd", 100),
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Feeder rewards not claimed"),
+        _ => panic!("Must return generic error"),
+    }
+    handle(
+        &mut deps,
+        mock_env("owner", &[]),
+        set_access_fee("uscrt", 200),
+    )
+    .unwrap();
+
+    let claim = HandleMsg::ClaimFeederRewards {};
+    let res = handle(&mut deps, mock_env("feeder1", &[]), claim.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
+            to_address: HumanAddr::from("feeder1"),
+            amount: vec![Coin::new(153, "uscrt")],
+        })]
+    );
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "claim_feeder_rewards"),
+            log("feeder", "feeder1"),
+            log("amount", 153),
+        ]
+    );
+    match handle(&mut deps, mock_env("feeder1", &[]), claim.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No rewards"),
+        _ => panic!("Must return generic error"),
+    }
+    handle(&mut deps, mock_env("feeder2", &[]), claim).unwrap();
+    assert_eq!(feeder_rewards(&deps, "feeder2"), Uint128::zero());
+
+    // SNIP-20 payments once all rewards are claimed
+    let msg = HandleMsg::SetAccessFee {
+        access_fee: Some(AccessFee {
+            asset_info: AssetInfo::Token {
+                contract_addr: HumanAddr::from("token"),
+                token_code_hash: "token_code_hash".to_string(),
+                viewing_key: "".to_string(),
+            },
+            amount: Uint128(100),
+            period: 60,
+        }),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+
+    let receive = HandleMsg::Receive {
+        from: HumanAddr::from("consumer"),
+        msg: Some(to_binary(&Cw20HookMsg::PayAccess { consumer: None }).unwrap()),
+        amount: Uint128(100),
+    };
+    match handle(&mut deps, mock_env("other_token", &[]), receive.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid asset"),
+        _ => panic!("Must return generic error"),
+    }
+    match handle(
+        &mut deps,
+        payment_env("consumer", 100, 240),
+        HandleMsg::PayAccess { consumer: None },
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid asset"),
+        _ => panic!("Must return generic error"),
+    }
+    let mut env = mock_env("token", &[]);
+    env.block.time = 240;
+    let res = handle(&mut deps, env, receive).unwrap();
+    assert_eq!(res.log[3], log("paid_until", 350));
+    assert_eq!(feeder_rewards(&deps, "feeder1"), Uint128(50));
+}
+
+#[test]
+fn test_latest_price_heartbeat() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_oracle(&mut deps);
+
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: sscrt(),
+        feeder: HumanAddr::from("feeder1"),
+        heartbeat: Some(60),
+        metadata: asset_metadata("SCRT"),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+
+    feed_sscrt_at(&mut deps, 10, 100);
+
+    let latest_price = |time: Option<u64>| {
+        let res = query(
+            &deps,
+            QueryMsg::LatestPrice {
+                asset_info: sscrt(),
+                time,
+            },
+        )
+        .unwrap();
+        from_binary::<LatestPriceResponse>(&res).unwrap()
+    };
+
+    assert_eq!(
+        latest_price(Some(160)),
+        LatestPriceResponse {
+            price: Decimal::from_ratio(10u64, 1u64),
+            last_updated_time: 100,
+            quote_currency: "USD".to_string(),
+            decimals: 6,
+            feeder: HumanAddr::from("feeder1"),
+            heartbeat: Some(60),
+            is_stale: false,
+            round_id: Some(1),
+        }
+    );
+    assert!(latest_price(Some(161)).is_stale);
+    // Without a time, staleness is evaluated at the last block seen by the contract
+    assert!(!latest_price(None).is_stale);
+
+    let checked = |deps: &Extern<MockStorage, MockApi, MockQuerier>, time: u64| {
+        query(
+            deps,
+            QueryMsg::LatestPriceChecked {
+                asset_info: sscrt(),
+                time,
+            },
+        )
+    };
+    assert!(checked(&deps, 160).is_ok());
+    match checked(&deps, 161) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Price is stale"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // A feed of another asset moves the time seen by the contract past an older time
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: sscrt2(),
+        feeder: HumanAddr::from("feeder1"),
+        heartbeat: None,
+        metadata: asset_metadata("SCRT2"),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    let mut env = mock_env("feeder1", &[]);
+    env.block.time = 200;
+    let msg = HandleMsg::FeedPrice {
+        prices: vec![(sscrt2(), Decimal::one())],
+        quote_currency: None,
+    };
+    handle(&mut deps, env, msg).unwrap();
+    for time in [None, Some(150)].iter() {
+        let res = query(
+            &deps,
+            QueryMsg::LatestPrice {
+                asset_info: sscrt(),
+                time: *time,
+            },
+        )
+        .unwrap();
+        assert!(from_binary::<LatestPriceResponse>(&res).unwrap().is_stale);
+    }
+
+    // Registered again without a heartbeat
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: sscrt(),
+        feeder: HumanAddr::from("feeder1"),
+        heartbeat: None,
+        metadata: asset_metadata("SCRT"),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    feed_sscrt_at(&mut deps, 10, 100);
+    assert!(checked(&deps, 1000).is_ok());
+}
+
+#[test]
+fn test_latest_price_with_max_time_limits() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner"),
+        history_retention: None,
+        quorum: None,
+        max_price_age: Some(u64::MAX),
+        guardian: None,
+        restrict_reads: None,
+        access_fee: None,
+    };
+    init(&mut deps, mock_env("addr", &[]), msg).unwrap();
+
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: sscrt(),
+        feeder: HumanAddr::from("feeder1"),
+        heartbeat: Some(u64::MAX),
+        metadata: asset_metadata("SCRT"),
+    };
+    handle(&mut deps, mock_env("owner", &[]), msg).unwrap();
+    feed_sscrt_at(&mut deps, 10, 100);
+
+    // The limits saturate instead of overflowing
+    let res = query(
+        &deps,
+        QueryMsg::LatestPriceChecked {
+            asset_info: sscrt(),
+            time: u64::MAX,
+        },
+    )
+    .unwrap();
+    let latest_price: LatestPriceResponse = from_binary(&res).unwrap();
+    assert_eq!(latest_price.last_updated_time, 100);
+    assert!(!latest_price.is_stale);
+}
@@ -1,9 +1,10 @@
 use cosmwasm_std::testing::{mock_env, MockApi, MockQuerier, MockStorage};
-use cosmwasm_std::{Extern, HumanAddr};
+use cosmwasm_std::{Binary, Extern, HumanAddr};
+use k256::ecdsa::{signature::Signer, Signature, SigningKey};
 
 use prediction::{
     asset::AssetInfo,
-    oracle::{HandleMsg, InitMsg},
+    oracle::{AssetMetadata, HandleMsg, InitMsg, SignedPriceReport},
 };
 
 use crate::contract::{handle, init};
@@ -11,6 +12,12 @@ use crate::contract::{handle, init};
 pub fn init_oracle(deps: &mut Extern<MockStorage, MockApi, MockQuerier>) {
     let msg = InitMsg {
         owner: HumanAddr::from("owner"),
+        history_retention: None,
+        quorum: None,
+        max_price_age: None,
+        guardian: None,
+        restrict_reads: None,
+        access_fee: None,
     };
 
     let env = mock_env("owner_addr", &[]);
@@ -18,14 +25,51 @@ pub fn init_oracle(deps: &mut Extern<MockStorage, MockApi, MockQuerier>) {
     init(deps, env, msg).unwrap();
 }
 
+pub fn asset_metadata(symbol: &str) -> AssetMetadata {
+    AssetMetadata {
+        symbol: symbol.to_string(),
+        decimals: 6,
+        quote_currency: "USD".to_string(),
+        description: None,
+    }
+}
+
+/// Deterministic secp256k1 key of a feeder
+pub fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32]).unwrap()
+}
+
+pub fn compressed_pubkey(key: &SigningKey) -> Binary {
+    Binary::from(key.verifying_key().to_encoded_point(true).as_bytes())
+}
+
+/// Signature of the report for the mock oracle
+pub fn sign_report(key: &SigningKey, report: &SignedPriceReport) -> Binary {
+    let env = mock_env("", &[]);
+    sign_report_for(key, report, &env.contract.address, &env.block.chain_id)
+}
+
+pub fn sign_report_for(
+    key: &SigningKey,
+    report: &SignedPriceReport,
+    oracle: &HumanAddr,
+    chain_id: &str,
+) -> Binary {
+    let signature: Signature = key.sign(&report.signing_bytes(oracle, chain_id).unwrap());
+    Binary::from(signature.as_ref())
+}
+
 fn register_native_token(
     deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
     denom: String,
     feeder: HumanAddr,
 ) {
+    let symbol = denom.to_uppercase();
     let msg = HandleMsg::RegisterAsset {
         asset_info: AssetInfo::NativeToken { denom },
         feeder,
+        heartbeat: None,
+        metadata: asset_metadata(&symbol),
     };
 
     let env = mock_env("owner", &[]);
@@ -38,6 +82,7 @@ fn register_snip20_token(
     contract_addr: HumanAddr,
     feeder: HumanAddr,
 ) {
+    let symbol = contract_addr.as_str().to_uppercase();
     let msg = HandleMsg::RegisterAsset {
         asset_info: AssetInfo::Token {
             contract_addr,
@@ -45,6 +90,8 @@ fn register_snip20_token(
             viewing_key: String::from("viewing_key"),
         },
         feeder,
+        heartbeat: None,
+        metadata: asset_metadata(&symbol),
     };
 
     let env = mock_env("owner", &[]);
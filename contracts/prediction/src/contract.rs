@@ -1,15 +1,34 @@
 use cosmwasm_std::{
-    from_binary, to_binary, Api, Binary, Decimal, Env, Extern, HandleResult, HumanAddr,
-    InitResponse, Querier, StdError, StdResult, Storage, Uint128,
+    from_binary, to_binary, Api, Binary, CosmosMsg, Decimal, Env, Extern, HandleResult, HumanAddr,
+    InitResponse, Querier, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
+use secret_toolkit::snip20::HandleMsg as Snip20HandleMsg;
 
-use crate::handler::{bet, claim, create_viewing_key, revoke_permit, set_viewing_key};
-use crate::manage::{execute_round, pause, start_genesis_round, update_config, withdraw};
-use crate::query::{permit_queries, query_bet, query_config, query_round, query_state};
-use crate::state::{read_config, store_config, store_state, Config};
+use crate::handler::{
+    bet, bet_from_balance, bet_strike_market, cancel_standing_order, claim, claim_strike_market,
+    claim_to_balance, commit_bet, create_viewing_key, deposit, reveal_bet, revoke_permit,
+    set_standing_order, set_viewing_key, withdraw_balance,
+};
+use crate::manage::{
+    cancel_queued, create_strike_market, execute_queued, execute_round, pause, price_updated,
+    process_standing_orders, set_buckets, set_oracle_viewing_key, settle_strike_market,
+    start_genesis_round, transfer_funds, update_auto_settle, update_commit_reveal, update_config,
+    update_timelock, validate_bucket_bounds, validate_commit_reveal, withdraw,
+};
+use crate::migration::{
+    assert_import_sealed, import, query_export, query_import_status, seal_import,
+};
+use crate::query::{
+    check_quote_currency, permit_queries, query_balance, query_bet, query_config,
+    query_pending_actions, query_round, query_standing_order, query_standing_order_progress,
+    query_state, query_strike_bet, query_strike_market, query_strike_markets,
+};
+use crate::state::{
+    read_config, store_config, store_import_status, store_state, Config, ImportStatus,
+};
 use prediction::{
     asset::AssetInfoRaw,
-    prediction::{Cw20HookMsg, HandleMsg, InitMsg, Position, QueryMsg, State},
+    prediction::{Cw20HookMsg, HandleMsg, InitMsg, QueryMsg, State},
     rand::sha_256,
 };
 
@@ -26,9 +45,13 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         return Err(StdError::generic_err("Invalid grace interval"));
     }
 
+    let bucket_bounds = msg.bucket_bounds.unwrap_or_default();
+    validate_bucket_bounds(&bucket_bounds)?;
+    validate_commit_reveal(&msg.commit_reveal, msg.interval)?;
+
     let prng_seed_hashed = sha_256(&msg.prng_seed.0);
 
-    let config = Config {
+    let mut config = Config {
         contract_addr: deps.api.canonical_address(&env.contract.address)?,
         owner_addr: deps.api.canonical_address(&env.message.sender)?,
         operator_addr: deps.api.canonical_address(&msg.operator_addr)?,
@@ -40,10 +63,36 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         interval: msg.interval,
         grace_interval: msg.grace_interval,
         prng_seed: prng_seed_hashed.to_vec(),
+        bucket_bounds,
+        commit_reveal: msg.commit_reveal,
+        timelock: msg.timelock,
+        quote_currency: msg.quote_currency,
+        price_decimals: None,
+        auto_settle: msg.auto_settle.unwrap_or(false),
     };
+    check_quote_currency(deps, &mut config)?;
 
     store_config(&mut deps.storage, &config)?;
 
+    // The viewing key of the bet asset is used to read the balance handed over on upgrades
+    let mut messages = vec![];
+    if let AssetInfoRaw::Token {
+        contract_addr,
+        token_code_hash,
+        viewing_key,
+    } = config.bet_asset
+    {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&contract_addr)?,
+            callback_code_hash: token_code_hash,
+            msg: to_binary(&Snip20HandleMsg::SetViewingKey {
+                key: viewing_key,
+                padding: None,
+            })?,
+            send: vec![],
+        }));
+    }
+
     store_state(
         &mut deps.storage,
         &State {
@@ -53,17 +102,62 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         },
     )?;
 
-    Ok(InitResponse::default())
+    store_import_status(
+        &mut deps.storage,
+        &ImportStatus {
+            sealed: !msg.import.unwrap_or(false),
+            ..ImportStatus::default()
+        },
+    )?;
+
+    Ok(InitResponse {
+        messages,
+        log: vec![],
+    })
 }
 
 pub fn handle<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     msg: HandleMsg,
+) -> HandleResult {
+    match msg {
+        HandleMsg::Import { data } => import(deps, env, data),
+        HandleMsg::SealImport { checksum } => seal_import(deps, env, checksum),
+        // Skipped until sealed rather than reverting the feed of the oracle
+        HandleMsg::PriceUpdated {
+            asset_info,
+            round_id,
+            price,
+            updated_at,
+        } => price_updated(deps, env, asset_info, round_id, price, updated_at),
+        _ => {
+            assert_import_sealed(&deps.storage)?;
+            handle_sealed(deps, env, msg)
+        }
+    }
+}
+
+fn handle_sealed<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: HandleMsg,
 ) -> HandleResult {
     match msg {
         HandleMsg::Receive { amount, msg, from } => receive_cw20(deps, env, from, amount, msg),
-        HandleMsg::Bet { position } => try_bet(deps, env, position),
+        HandleMsg::Bet { position } => {
+            let amount = sent_bet_amount(deps, &env)?;
+            bet(deps, env.clone(), env.message.sender, position, amount)
+        }
+        HandleMsg::CommitBet { commitment } => {
+            let amount = sent_bet_amount(deps, &env)?;
+            commit_bet(deps, env.clone(), env.message.sender, commitment, amount)
+        }
+        HandleMsg::RevealBet {
+            epoch,
+            position,
+            salt,
+        } => reveal_bet(deps, env, epoch, position, salt),
         HandleMsg::UpdateConfig {
             owner_addr,
             operator_addr,
@@ -73,6 +167,7 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             fee_rate,
             interval,
             grace_interval,
+            quote_currency,
         } => update_config(
             deps,
             env,
@@ -84,15 +179,81 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             fee_rate,
             interval,
             grace_interval,
+            quote_currency,
         ),
         HandleMsg::Claim { epoch } => claim(deps, env, epoch),
+        HandleMsg::ClaimToBalance { epoch } => claim_to_balance(deps, env, epoch),
+        HandleMsg::BetFromBalance { position, amount } => {
+            bet_from_balance(deps, env, position, amount)
+        }
+        HandleMsg::Deposit {} => {
+            let amount = sent_bet_amount(deps, &env)?;
+            deposit(deps, env.message.sender, amount)
+        }
+        HandleMsg::WithdrawBalance { amount } => withdraw_balance(deps, env, amount),
         HandleMsg::Withdraw {} => withdraw(deps, env),
         HandleMsg::ExecuteRound {} => execute_round(deps, env),
         HandleMsg::Pause {} => pause(deps, env),
         HandleMsg::StartGenesisRound {} => start_genesis_round(deps, env),
+        HandleMsg::SetBuckets { bounds } => set_buckets(deps, env, bounds),
+        HandleMsg::UpdateCommitReveal { commit_reveal } => {
+            update_commit_reveal(deps, env, commit_reveal)
+        }
+        HandleMsg::UpdateAutoSettle { auto_settle } => update_auto_settle(deps, env, auto_settle),
+        HandleMsg::SetOracleViewingKey { key } => set_oracle_viewing_key(deps, env, key),
+        HandleMsg::UpdateTimelock { timelock } => update_timelock(deps, env, timelock),
+        HandleMsg::ExecuteQueued { id } => execute_queued(deps, env, id),
+        HandleMsg::CancelQueued { id } => cancel_queued(deps, env, id),
+        HandleMsg::CreateStrikeMarket {
+            strike_price,
+            bet_deadline,
+            expiry,
+            grace_interval,
+            max_price_age,
+        } => create_strike_market(
+            deps,
+            env,
+            strike_price,
+            bet_deadline,
+            expiry,
+            grace_interval,
+            max_price_age,
+        ),
+        HandleMsg::BetStrikeMarket { id, position } => {
+            let amount = sent_bet_amount(deps, &env)?;
+            bet_strike_market(deps, env.clone(), env.message.sender, id, position, amount)
+        }
+        HandleMsg::SettleStrikeMarket { id } => settle_strike_market(deps, env, id),
+        HandleMsg::ClaimStrikeMarket { id } => claim_strike_market(deps, env, id),
+        HandleMsg::SetStandingOrder {
+            position,
+            strategy,
+            amount_per_round,
+            max_rounds,
+        } => {
+            let budget = sent_bet_amount(deps, &env)?;
+            set_standing_order(
+                deps,
+                env.message.sender,
+                position,
+                strategy,
+                amount_per_round,
+                max_rounds,
+                budget,
+            )
+        }
+        HandleMsg::CancelStandingOrder {} => cancel_standing_order(deps, env),
+        HandleMsg::ProcessStandingOrders { limit } => process_standing_orders(deps, env, limit),
         HandleMsg::CreateViewingKey { entropy, .. } => create_viewing_key(deps, env, entropy),
         HandleMsg::SetViewingKey { key, .. } => set_viewing_key(deps, env, key),
         HandleMsg::RevokePermit { permit_name, .. } => revoke_permit(deps, env, permit_name),
+        HandleMsg::TransferFunds { recipient, amount } => {
+            transfer_funds(deps, env, recipient, amount)
+        }
+        // Dispatched by `handle` before the import check
+        HandleMsg::Import { .. }
+        | HandleMsg::SealImport { .. }
+        | HandleMsg::PriceUpdated { .. } => Err(StdError::generic_err("Unexpected message")),
     }
 }
 
@@ -105,44 +266,60 @@ fn receive_cw20<S: Storage, A: Api, Q: Querier>(
     msg: Option<Binary>,
 ) -> HandleResult {
     if let Some(bin_msg) = msg {
-        match from_binary(&bin_msg)? {
-            Cw20HookMsg::Bet { position } => {
-                let config = read_config(&deps.storage)?;
-                match config.bet_asset {
-                    AssetInfoRaw::NativeToken { .. } => Err(StdError::generic_err("invalid asset")),
-                    AssetInfoRaw::Token { contract_addr, .. } => {
-                        if env.message.sender == deps.api.human_address(&contract_addr)? {
-                            bet(deps, env, from, position, amount)
-                        } else {
-                            Err(StdError::generic_err("invalid asset"))
-                        }
-                    }
+        let config = read_config(&deps.storage)?;
+        match config.bet_asset {
+            AssetInfoRaw::NativeToken { .. } => return Err(StdError::generic_err("invalid asset")),
+            AssetInfoRaw::Token { contract_addr, .. } => {
+                if env.message.sender != deps.api.human_address(&contract_addr)? {
+                    return Err(StdError::generic_err("invalid asset"));
                 }
             }
         }
+
+        match from_binary(&bin_msg)? {
+            Cw20HookMsg::Bet { position } => bet(deps, env, from, position, amount),
+            Cw20HookMsg::Deposit {} => deposit(deps, from, amount),
+            Cw20HookMsg::CommitBet { commitment } => {
+                commit_bet(deps, env, from, commitment, amount)
+            }
+            Cw20HookMsg::BetStrikeMarket { id, position } => {
+                bet_strike_market(deps, env, from, id, position, amount)
+            }
+            Cw20HookMsg::SetStandingOrder {
+                position,
+                strategy,
+                amount_per_round,
+                max_rounds,
+            } => set_standing_order(
+                deps,
+                from,
+                position,
+                strategy,
+                amount_per_round,
+                max_rounds,
+                amount,
+            ),
+        }
     } else {
         Err(StdError::generic_err("data should be given"))
     }
 }
 
-fn try_bet<S: Storage, A: Api, Q: Querier>(
-    deps: &mut Extern<S, A, Q>,
-    env: Env,
-    position: Position,
-) -> HandleResult {
+/// Amount of native bet asset sent with the message
+fn sent_bet_amount<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: &Env,
+) -> StdResult<Uint128> {
     let config = read_config(&deps.storage)?;
 
     match config.bet_asset {
-        AssetInfoRaw::NativeToken { denom } => {
-            let amount: Uint128 = env
-                .message
-                .sent_funds
-                .iter()
-                .find(|c| c.denom == denom)
-                .map(|c| Uint128::from(c.amount))
-                .unwrap_or_else(Uint128::zero);
-            bet(deps, env.clone(), env.message.sender, position, amount)
-        }
+        AssetInfoRaw::NativeToken { denom } => Ok(env
+            .message
+            .sent_funds
+            .iter()
+            .find(|c| c.denom == denom)
+            .map(|c| Uint128::from(c.amount))
+            .unwrap_or_else(Uint128::zero)),
         AssetInfoRaw::Token { .. } => Err(StdError::generic_err("invalid asset")),
     }
 }
@@ -156,6 +333,19 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
         QueryMsg::State {} => to_binary(&query_state(deps)?),
         QueryMsg::Round { epoch } => to_binary(&query_round(deps, epoch)?),
         QueryMsg::Bet { epoch, user, key } => to_binary(&query_bet(deps, epoch, user, key)?),
+        QueryMsg::StrikeMarket { id } => to_binary(&query_strike_market(deps, id)?),
+        QueryMsg::StrikeMarkets { start_after, limit } => {
+            to_binary(&query_strike_markets(deps, start_after, limit)?)
+        }
+        QueryMsg::StrikeBet { id, user, key } => to_binary(&query_strike_bet(deps, id, user, key)?),
+        QueryMsg::Balance { user, key } => to_binary(&query_balance(deps, user, key)?),
+        QueryMsg::StandingOrder { user, key } => to_binary(&query_standing_order(deps, user, key)?),
+        QueryMsg::StandingOrderProgress {} => to_binary(&query_standing_order_progress(deps)?),
+        QueryMsg::PendingActions { start_after, limit } => {
+            to_binary(&query_pending_actions(deps, start_after, limit)?)
+        }
+        QueryMsg::Export { key, page } => to_binary(&query_export(deps, key, page)?),
+        QueryMsg::ImportStatus {} => to_binary(&query_import_status(deps)?),
         QueryMsg::WithPermit { permit, query } => permit_queries(deps, permit, query),
     }
 }
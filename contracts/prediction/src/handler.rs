@@ -1,15 +1,17 @@
 use cosmwasm_std::{
-    log, Api, Env, Extern, HandleResponse, HandleResult, HumanAddr, Querier, StdError, Storage,
-    Uint128,
+    log, Api, Binary, Env, Extern, HandleResponse, HandleResult, HumanAddr, Querier, StdError,
+    StdResult, Storage, Uint128,
 };
 
 use crate::state::{
-    read_bet, read_config, read_round, read_state, store_bet, store_round, store_viewing_key, Bet,
-    Config, Round, PREFIX_REVOKED_PERMITS,
+    add_revoked_permit, add_standing_order, read_balance, read_bet, read_config, read_round,
+    read_standing_order, read_state, read_strike_bet, read_strike_market, remove_standing_order,
+    store_balance, store_bet, store_round, store_strike_bet, store_strike_market,
+    store_viewing_key, Bet, Config, RevokedPermit, Round, StandingOrder, PREFIX_REVOKED_PERMITS,
 };
 use prediction::{
     asset::Asset,
-    prediction::{Position, State},
+    prediction::{bet_commitment, Position, State, Strategy},
     viewing_key::ViewingKey,
 };
 use secret_toolkit::permit::RevokedPermits;
@@ -21,6 +23,129 @@ pub fn bet<S: Storage, A: Api, Q: Querier>(
     position: Position,
     amount: Uint128,
 ) -> HandleResult {
+    let (epoch, mut round) = read_betting_round(deps, env, &user, amount)?;
+
+    if round.commit_reveal.is_some() {
+        return Err(StdError::generic_err("Commit required"));
+    }
+
+    let outcome = round.outcome_index(&position)?;
+    round.total_amount = round.total_amount + amount;
+    round.outcome_amounts[outcome] = round.outcome_amounts[outcome] + amount;
+
+    store_round(&mut deps.storage, epoch, &round)?;
+
+    store_bet(
+        &mut deps.storage,
+        epoch,
+        deps.api.canonical_address(&user)?,
+        &Bet {
+            amount,
+            position: Some(position.clone()),
+            commitment: None,
+            claimed: false,
+        },
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "bet"),
+            log("amount", amount),
+            log("position", position),
+        ],
+        data: None,
+    })
+}
+
+pub fn commit_bet<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    user: HumanAddr,
+    commitment: Binary,
+    amount: Uint128,
+) -> HandleResult {
+    let (epoch, mut round) = read_betting_round(deps, env, &user, amount)?;
+
+    if round.commit_reveal.is_none() {
+        return Err(StdError::generic_err("Commit-reveal disabled"));
+    }
+
+    // Only the stake is known until reveal
+    round.total_amount = round.total_amount + amount;
+
+    store_round(&mut deps.storage, epoch, &round)?;
+
+    store_bet(
+        &mut deps.storage,
+        epoch,
+        deps.api.canonical_address(&user)?,
+        &Bet {
+            amount,
+            position: None,
+            commitment: Some(commitment),
+            claimed: false,
+        },
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "commit_bet"), log("amount", amount)],
+        data: None,
+    })
+}
+
+pub fn reveal_bet<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    epoch: Uint128,
+    position: Position,
+    salt: String,
+) -> HandleResult {
+    let mut round: Round = read_round(&deps.storage, epoch)?;
+
+    if !round.revealable(env.clone()) {
+        return Err(StdError::generic_err("Cannot reveal"));
+    }
+
+    let user = deps.api.canonical_address(&env.message.sender)?;
+    let mut user_bet = read_bet(&deps.storage, epoch, user.clone())?;
+
+    match (&user_bet.position, &user_bet.commitment) {
+        (None, Some(commitment)) => {
+            if bet_commitment(epoch, &env.message.sender, &position, &salt) != *commitment {
+                return Err(StdError::generic_err("Invalid reveal"));
+            }
+        }
+        _ => return Err(StdError::generic_err("Nothing to reveal")),
+    }
+
+    let outcome = round.outcome_index(&position)?;
+    round.outcome_amounts[outcome] = round.outcome_amounts[outcome] + user_bet.amount;
+    store_round(&mut deps.storage, epoch, &round)?;
+
+    user_bet.position = Some(position.clone());
+    store_bet(&mut deps.storage, epoch, user, &user_bet)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "reveal_bet"),
+            log("epoch", epoch),
+            log("amount", user_bet.amount),
+            log("position", position),
+        ],
+        data: None,
+    })
+}
+
+/// Load the current betting round after checking the user can bet on it
+fn read_betting_round<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: Env,
+    user: &HumanAddr,
+    amount: Uint128,
+) -> StdResult<(Uint128, Round)> {
     if amount.is_zero() {
         return Err(StdError::generic_err("Amount is zero"));
     }
@@ -30,7 +155,7 @@ pub fn bet<S: Storage, A: Api, Q: Querier>(
         return Err(StdError::generic_err("Paused"));
     }
 
-    let mut round: Round = read_round(&deps.storage, state.epoch)?;
+    let round: Round = read_round(&deps.storage, state.epoch)?;
 
     if round.bettable(env) == false {
         return Err(StdError::generic_err("Cannot bet"));
@@ -39,51 +164,72 @@ pub fn bet<S: Storage, A: Api, Q: Querier>(
     let user_bet = read_bet(
         &deps.storage,
         state.epoch,
-        deps.api.canonical_address(&user)?,
+        deps.api.canonical_address(user)?,
     );
 
     if user_bet.is_ok() {
         return Err(StdError::generic_err("Already bet"));
     }
 
-    round.total_amount = round.total_amount + amount;
-
-    if position == Position::Up {
-        round.up_amount = round.up_amount + amount;
-    } else {
-        round.down_amount = round.down_amount + amount;
-    }
+    Ok((state.epoch, round))
+}
 
-    store_round(&mut deps.storage, state.epoch, &round)?;
+pub fn claim<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    epoch: Uint128,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let (user_bet, claim_amount) = take_claim(deps, env.clone(), &config, epoch)?;
 
-    store_bet(
-        &mut deps.storage,
-        state.epoch,
-        deps.api.canonical_address(&user)?,
-        &Bet {
-            amount,
-            position: position.clone(),
-            claimed: false,
-        },
-    )?;
+    let return_asset = Asset {
+        amount: claim_amount,
+        info: config.bet_asset.to_normal(deps)?,
+    };
 
     Ok(HandleResponse {
-        messages: vec![],
+        messages: vec![return_asset.into_msg(env.contract.address, env.message.sender)?],
         log: vec![
-            log("action", "bet"),
-            log("amount", amount),
-            log("position", position),
+            log("action", "claim"),
+            log("epoch", epoch),
+            log("amount", user_bet.amount),
+            log("claim_amount", claim_amount),
         ],
         data: None,
     })
 }
 
-pub fn claim<S: Storage, A: Api, Q: Querier>(
+pub fn claim_to_balance<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     epoch: Uint128,
 ) -> HandleResult {
     let config: Config = read_config(&deps.storage)?;
+    let (user_bet, claim_amount) = take_claim(deps, env.clone(), &config, epoch)?;
+
+    let user_raw = deps.api.canonical_address(&env.message.sender)?;
+    let balance = read_balance(&deps.storage, &user_raw)? + claim_amount;
+    store_balance(&mut deps.storage, &user_raw, balance)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "claim_to_balance"),
+            log("epoch", epoch),
+            log("amount", user_bet.amount),
+            log("claim_amount", claim_amount),
+        ],
+        data: None,
+    })
+}
+
+/// Mark the sender's bet claimed and return it with the claimable amount
+fn take_claim<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    config: &Config,
+    epoch: Uint128,
+) -> StdResult<(Bet, Uint128)> {
     let round: Round = read_round(&deps.storage, epoch)?;
 
     if !round.claimable(env.clone()) && !round.refundable(env.clone(), config.grace_interval) {
@@ -107,7 +253,163 @@ pub fn claim<S: Storage, A: Api, Q: Querier>(
         deps.api.canonical_address(&env.message.sender)?,
         &user_bet,
     )?;
-    let claim_amount = round.claimable_amount(env.clone(), user_bet.clone(), config.grace_interval);
+    let claim_amount = round.claimable_amount(env, user_bet.clone(), config.grace_interval);
+
+    if claim_amount.is_zero() {
+        return Err(StdError::generic_err("Nothing to claim"));
+    }
+
+    Ok((user_bet, claim_amount))
+}
+
+pub fn bet_from_balance<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    position: Position,
+    amount: Uint128,
+) -> HandleResult {
+    let user_raw = deps.api.canonical_address(&env.message.sender)?;
+    let balance = (read_balance(&deps.storage, &user_raw)? - amount)
+        .map_err(|_| StdError::generic_err("Insufficient balance"))?;
+    store_balance(&mut deps.storage, &user_raw, balance)?;
+
+    bet(deps, env.clone(), env.message.sender, position, amount)
+}
+
+pub fn deposit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    user: HumanAddr,
+    amount: Uint128,
+) -> HandleResult {
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Amount is zero"));
+    }
+
+    let user_raw = deps.api.canonical_address(&user)?;
+    let balance = read_balance(&deps.storage, &user_raw)? + amount;
+    store_balance(&mut deps.storage, &user_raw, balance)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "deposit"), log("amount", amount)],
+        data: None,
+    })
+}
+
+pub fn withdraw_balance<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    amount: Option<Uint128>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let user_raw = deps.api.canonical_address(&env.message.sender)?;
+    let balance = read_balance(&deps.storage, &user_raw)?;
+    let amount = amount.unwrap_or(balance);
+
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Amount is zero"));
+    }
+
+    let balance = (balance - amount).map_err(|_| StdError::generic_err("Insufficient balance"))?;
+    store_balance(&mut deps.storage, &user_raw, balance)?;
+
+    let return_asset = Asset {
+        amount,
+        info: config.bet_asset.to_normal(deps)?,
+    };
+
+    Ok(HandleResponse {
+        messages: vec![return_asset.into_msg(env.contract.address, env.message.sender)?],
+        log: vec![log("action", "withdraw_balance"), log("amount", amount)],
+        data: None,
+    })
+}
+
+pub fn bet_strike_market<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    user: HumanAddr,
+    id: u64,
+    position: Position,
+    amount: Uint128,
+) -> HandleResult {
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Amount is zero"));
+    }
+
+    let state: State = read_state(&deps.storage)?;
+    if state.paused {
+        return Err(StdError::generic_err("Paused"));
+    }
+
+    let mut market = read_strike_market(&deps.storage, id)?;
+
+    if !market.bettable(env) {
+        return Err(StdError::generic_err("Cannot bet"));
+    }
+
+    let user_raw = deps.api.canonical_address(&user)?;
+    if read_strike_bet(&deps.storage, id, &user_raw).is_ok() {
+        return Err(StdError::generic_err("Already bet"));
+    }
+
+    let outcome = market.round.outcome_index(&position)?;
+    market.round.total_amount = market.round.total_amount + amount;
+    market.round.outcome_amounts[outcome] = market.round.outcome_amounts[outcome] + amount;
+
+    store_strike_market(&mut deps.storage, &market)?;
+
+    store_strike_bet(
+        &mut deps.storage,
+        id,
+        &user_raw,
+        &Bet {
+            amount,
+            position: Some(position.clone()),
+            commitment: None,
+            claimed: false,
+        },
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "bet_strike_market"),
+            log("id", id),
+            log("amount", amount),
+            log("position", position),
+        ],
+        data: None,
+    })
+}
+
+pub fn claim_strike_market<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    id: u64,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let market = read_strike_market(&deps.storage, id)?;
+
+    if !market.round.claimable(env.clone())
+        && !market.round.refundable(env.clone(), market.grace_interval)
+    {
+        return Err(StdError::generic_err("Not able to claim"));
+    }
+
+    let user_raw = deps.api.canonical_address(&env.message.sender)?;
+    let mut user_bet = read_strike_bet(&deps.storage, id, &user_raw)?;
+
+    if user_bet.claimed {
+        return Err(StdError::generic_err("Already claimed"));
+    }
+
+    user_bet.claimed = true;
+    store_strike_bet(&mut deps.storage, id, &user_raw, &user_bet)?;
+    let claim_amount =
+        market
+            .round
+            .claimable_amount(env.clone(), user_bet.clone(), market.grace_interval);
 
     if claim_amount.is_zero() {
         return Err(StdError::generic_err("Nothing to claim"));
@@ -121,8 +423,8 @@ pub fn claim<S: Storage, A: Api, Q: Querier>(
     Ok(HandleResponse {
         messages: vec![return_asset.into_msg(env.contract.address, env.message.sender)?],
         log: vec![
-            log("action", "claim"),
-            log("epoch", epoch),
+            log("action", "claim_strike_market"),
+            log("id", id),
             log("amount", user_bet.amount),
             log("claim_amount", claim_amount),
         ],
@@ -130,6 +432,84 @@ pub fn claim<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+pub fn set_standing_order<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    user: HumanAddr,
+    position: Option<Position>,
+    strategy: Option<Strategy>,
+    amount_per_round: Uint128,
+    max_rounds: u64,
+    budget: Uint128,
+) -> HandleResult {
+    if position.is_some() == strategy.is_some()
+        || amount_per_round.is_zero()
+        || max_rounds == 0
+        || budget < amount_per_round
+    {
+        return Err(StdError::generic_err("Invalid standing order"));
+    }
+
+    let user_raw = deps.api.canonical_address(&user)?;
+    if read_standing_order(&deps.storage, &user_raw).is_ok() {
+        return Err(StdError::generic_err("Standing order exists"));
+    }
+
+    add_standing_order(
+        &mut deps.storage,
+        &user_raw,
+        StandingOrder {
+            index: 0,
+            position,
+            strategy,
+            amount_per_round,
+            remaining_rounds: max_rounds,
+            budget,
+            last_epoch: Uint128::zero(),
+        },
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "set_standing_order"),
+            log("amount_per_round", amount_per_round),
+            log("max_rounds", max_rounds),
+            log("budget", budget),
+        ],
+        data: None,
+    })
+}
+
+pub fn cancel_standing_order<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let user_raw = deps.api.canonical_address(&env.message.sender)?;
+    let order = read_standing_order(&deps.storage, &user_raw)
+        .map_err(|_| StdError::generic_err("No standing order"))?;
+
+    remove_standing_order(&mut deps.storage, &user_raw)?;
+
+    let mut messages = vec![];
+    if !order.budget.is_zero() {
+        let return_asset = Asset {
+            amount: order.budget,
+            info: config.bet_asset.to_normal(deps)?,
+        };
+        messages.push(return_asset.into_msg(env.contract.address, env.message.sender)?);
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![
+            log("action", "cancel_standing_order"),
+            log("refund_amount", order.budget),
+        ],
+        data: None,
+    })
+}
+
 pub fn create_viewing_key<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
@@ -180,6 +560,13 @@ pub fn revoke_permit<S: Storage, A: Api, Q: Querier>(
         &env.message.sender,
         &permit_name,
     );
+    add_revoked_permit(
+        &mut deps.storage,
+        &RevokedPermit {
+            user: env.message.sender,
+            permit_name,
+        },
+    )?;
 
     Ok(HandleResponse {
         messages: vec![],
@@ -1,8 +1,9 @@
 pub mod contract;
 mod handler;
 mod manage;
+mod migration;
 mod query;
-mod state;
+pub mod state;
 
 #[cfg(test)]
 mod tests;
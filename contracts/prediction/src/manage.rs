@@ -1,13 +1,29 @@
 use cosmwasm_std::{
-    log, Api, Decimal, Env, Extern, HandleResponse, HandleResult, HumanAddr, Querier, StdError,
-    Storage, Uint128,
+    log, to_binary, Api, CanonicalAddr, CosmosMsg, Decimal, Env, Extern, HandleResponse,
+    HandleResult, HumanAddr, Querier, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 
-use crate::query::query_price;
+use crate::query::{check_quote_currency, query_price, query_price_at};
 use crate::state::{
-    read_config, read_round, read_state, store_config, store_round, store_state, Config, Round,
+    read_bet, read_config, read_import_status, read_queued_action, read_queued_action_count,
+    read_round, read_standing_order, read_standing_order_count, read_standing_order_cursor,
+    read_standing_order_owner, read_state, read_strike_market, read_strike_market_count,
+    remove_queued_action, remove_standing_order, store_bet, store_config, store_oracle_viewing_key,
+    store_queued_action, store_queued_action_count, store_round, store_standing_order,
+    store_standing_order_cursor, store_state, store_strike_market, store_strike_market_count, Bet,
+    Config, Round, StandingOrderCursor, StrikeMarket,
 };
-use prediction::{asset::Asset, prediction::State};
+use prediction::{
+    asset::{Asset, AssetInfo},
+    oracle::HandleMsg as OracleHandleMsg,
+    prediction::{AdminAction, CommitRevealConfig, QueuedAction, State, TimelockConfig},
+};
+
+/// Upper limit of bucket bounds, a round has at most one more bucket than bounds
+const MAX_BUCKET_BOUNDS: usize = 15;
+/// Standing orders processed per call, the rest is left to `ProcessStandingOrders`
+const DEFAULT_STANDING_ORDER_LIMIT: u32 = 10;
+const MAX_STANDING_ORDER_LIMIT: u32 = 30;
 
 pub fn update_config<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -20,58 +36,276 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
     fee_rate: Option<Decimal>,
     interval: Option<u64>,
     grace_interval: Option<u64>,
+    quote_currency: Option<String>,
 ) -> HandleResult {
-    let mut config: Config = read_config(&deps.storage)?;
+    let config: Config = read_config(&deps.storage)?;
 
     // permission check
     if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
         return Err(StdError::unauthorized());
     }
 
-    if let Some(owner_addr) = owner_addr {
-        config.owner_addr = deps.api.canonical_address(&owner_addr)?;
-    }
+    let action = AdminAction::UpdateConfig {
+        owner_addr,
+        operator_addr,
+        treasury_addr,
+        oracle_addr,
+        oracle_code_hash,
+        fee_rate,
+        interval,
+        grace_interval,
+        quote_currency,
+    };
+
+    queue_or_apply(deps, env, &config, action, true)
+}
+
+pub fn update_timelock<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    timelock: Option<TimelockConfig>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
 
-    if let Some(operator_addr) = operator_addr {
-        config.operator_addr = deps.api.canonical_address(&operator_addr)?;
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
     }
 
-    if let Some(treasury_addr) = treasury_addr {
-        config.treasury_addr = deps.api.canonical_address(&treasury_addr)?;
+    queue_or_apply(
+        deps,
+        env,
+        &config,
+        AdminAction::UpdateTimelock { timelock },
+        true,
+    )
+}
+
+pub fn execute_queued<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    id: u64,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let queued = read_queued_action(&deps.storage, id)?
+        .ok_or_else(|| StdError::generic_err("No queued action"))?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != *action_admin(&config, &queued.action) {
+        return Err(StdError::unauthorized());
     }
 
-    if let Some(oracle_addr) = oracle_addr {
-        config.oracle_addr = deps.api.canonical_address(&oracle_addr)?;
-        config.oracle_code_hash = oracle_code_hash.unwrap();
+    if env.block.time < queued.eta {
+        return Err(StdError::generic_err("Timelock not expired"));
     }
 
-    if let Some(fee_rate) = fee_rate {
-        if fee_rate > Decimal::one() {
-            return Err(StdError::generic_err("Invalid fee rate"));
-        }
-        config.fee_rate = fee_rate;
+    remove_queued_action(&mut deps.storage, id);
+
+    let mut res = apply_admin_action(deps, env, queued.action)?;
+    res.log.push(log("queued_id", id));
+
+    Ok(res)
+}
+
+pub fn cancel_queued<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    id: u64,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let queued = read_queued_action(&deps.storage, id)?
+        .ok_or_else(|| StdError::generic_err("No queued action"))?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != *action_admin(&config, &queued.action) {
+        return Err(StdError::unauthorized());
     }
 
-    if let Some(interval) = interval {
-        config.interval = interval;
+    remove_queued_action(&mut deps.storage, id);
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "cancel_queued"), log("id", id)],
+        data: None,
+    })
+}
+
+/// Queued bucket changes belong to the operator, the other actions to the owner
+fn action_admin<'a>(config: &'a Config, action: &AdminAction) -> &'a CanonicalAddr {
+    match action {
+        AdminAction::SetBuckets { .. } => &config.operator_addr,
+        _ => &config.owner_addr,
     }
+}
+
+/// Queue the action while the timelock is enabled, apply it right away otherwise
+fn queue_or_apply<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    config: &Config,
+    action: AdminAction,
+    timelocked: bool,
+) -> HandleResult {
+    let min_delay = match &config.timelock {
+        Some(timelock) if timelocked && timelock.min_delay > 0 => timelock.min_delay,
+        _ => return apply_admin_action(deps, env, action),
+    };
+
+    // Invalid changes are rejected now instead of after the delay
+    apply_to_config(&deps.api, &mut config.clone(), &action)?;
+
+    let queued = QueuedAction {
+        id: read_queued_action_count(&deps.storage)? + 1,
+        action,
+        eta: env.block.time + min_delay,
+    };
+
+    store_queued_action(&mut deps.storage, &queued)?;
+    store_queued_action_count(&mut deps.storage, queued.id)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "queue_action"),
+            log("id", queued.id),
+            log("eta", queued.eta),
+        ],
+        data: None,
+    })
+}
+
+fn apply_admin_action<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    action: AdminAction,
+) -> HandleResult {
+    let mut config: Config = read_config(&deps.storage)?;
 
-    if let Some(grace_interval) = grace_interval {
-        if grace_interval > config.interval {
-            return Err(StdError::generic_err("Invalid grace interval"));
+    let logs = match &action {
+        AdminAction::UpdateConfig { .. } => vec![log("action", "update_config")],
+        AdminAction::UpdateTimelock { timelock } => vec![
+            log("action", "update_timelock"),
+            log("enabled", timelock.is_some()),
+        ],
+        AdminAction::Withdraw { amount } => return withdraw_fee(deps, env, &config, *amount),
+        AdminAction::TransferFunds { recipient, amount } => {
+            return hand_over_funds(deps, env, &config, recipient.clone(), *amount)
         }
-        config.grace_interval = grace_interval;
-    }
+        AdminAction::SetBuckets { bounds } => vec![
+            log("action", "set_buckets"),
+            log("bucket_count", bounds.len() + 1),
+        ],
+        AdminAction::UpdateCommitReveal { commit_reveal } => vec![
+            log("action", "update_commit_reveal"),
+            log("enabled", commit_reveal.is_some()),
+        ],
+        AdminAction::UpdateAutoSettle { auto_settle } => vec![
+            log("action", "update_auto_settle"),
+            log("enabled", *auto_settle),
+        ],
+        AdminAction::SetOracleViewingKey { key } => {
+            store_oracle_viewing_key(&mut deps.storage, key)?;
+            return Ok(HandleResponse {
+                messages: vec![oracle_viewing_key_msg(&deps.api, &config, key.clone())?],
+                log: vec![log("action", "set_oracle_viewing_key")],
+                data: None,
+            });
+        }
+    };
 
+    apply_to_config(&deps.api, &mut config, &action)?;
+    if let AdminAction::UpdateConfig { .. } = action {
+        check_quote_currency(deps, &mut config)?;
+    }
     store_config(&mut deps.storage, &config)?;
 
     Ok(HandleResponse {
         messages: vec![],
-        log: vec![log("action", "update_config")],
+        log: logs,
         data: None,
     })
 }
 
+fn apply_to_config<A: Api>(api: &A, config: &mut Config, action: &AdminAction) -> StdResult<()> {
+    match action {
+        AdminAction::UpdateConfig {
+            owner_addr,
+            operator_addr,
+            treasury_addr,
+            oracle_addr,
+            oracle_code_hash,
+            fee_rate,
+            interval,
+            grace_interval,
+            quote_currency,
+        } => {
+            if let Some(owner_addr) = owner_addr {
+                config.owner_addr = api.canonical_address(owner_addr)?;
+            }
+
+            if let Some(operator_addr) = operator_addr {
+                config.operator_addr = api.canonical_address(operator_addr)?;
+            }
+
+            if let Some(treasury_addr) = treasury_addr {
+                config.treasury_addr = api.canonical_address(treasury_addr)?;
+            }
+
+            if let Some(oracle_addr) = oracle_addr {
+                config.oracle_addr = api.canonical_address(oracle_addr)?;
+                config.oracle_code_hash = oracle_code_hash
+                    .clone()
+                    .ok_or_else(|| StdError::generic_err("Oracle code hash required"))?;
+            }
+
+            if let Some(fee_rate) = fee_rate {
+                if *fee_rate > Decimal::one() {
+                    return Err(StdError::generic_err("Invalid fee rate"));
+                }
+                config.fee_rate = *fee_rate;
+            }
+
+            if let Some(interval) = interval {
+                config.interval = *interval;
+            }
+
+            if let Some(grace_interval) = grace_interval {
+                if *grace_interval > config.interval {
+                    return Err(StdError::generic_err("Invalid grace interval"));
+                }
+                config.grace_interval = *grace_interval;
+            }
+
+            if let Some(quote_currency) = quote_currency {
+                config.quote_currency = Some(quote_currency.clone());
+            }
+
+            validate_commit_reveal(&config.commit_reveal, config.interval)
+        }
+        AdminAction::UpdateTimelock { timelock } => {
+            config.timelock = timelock.clone();
+            Ok(())
+        }
+        AdminAction::SetBuckets { bounds } => {
+            validate_bucket_bounds(bounds)?;
+            config.bucket_bounds = bounds.clone();
+            Ok(())
+        }
+        AdminAction::UpdateCommitReveal { commit_reveal } => {
+            validate_commit_reveal(commit_reveal, config.interval)?;
+            config.commit_reveal = commit_reveal.clone();
+            Ok(())
+        }
+        AdminAction::UpdateAutoSettle { auto_settle } => {
+            config.auto_settle = *auto_settle;
+            Ok(())
+        }
+        AdminAction::Withdraw { .. }
+        | AdminAction::SetOracleViewingKey { .. }
+        | AdminAction::TransferFunds { .. } => Ok(()),
+    }
+}
+
 pub fn execute_round<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
@@ -83,13 +317,11 @@ pub fn execute_round<S: Storage, A: Api, Q: Querier>(
         return Err(StdError::unauthorized());
     }
 
-    let mut state: State = read_state(&deps.storage)?;
+    let state: State = read_state(&deps.storage)?;
     if state.paused {
         return Err(StdError::generic_err("Paused"));
     }
-    let progressing_epoch = (state.epoch - Uint128(1))?;
-    let betting_epoch = state.epoch;
-    let mut round: Round = read_round(&deps.storage, progressing_epoch)?;
+    let round: Round = read_round(&deps.storage, (state.epoch - Uint128(1))?)?;
 
     if round.expired(env.clone(), config.grace_interval) {
         return Err(StdError::generic_err("Expired"));
@@ -99,43 +331,106 @@ pub fn execute_round<S: Storage, A: Api, Q: Querier>(
         return Err(StdError::generic_err("Cannot execute"));
     }
 
-    let price_reference_data = query_price(deps, config.clone())?;
+    let price_reference_data = query_price(deps, config.clone(), env.block.time)?;
     if price_reference_data.last_updated_time < round.start_time {
         return Err(StdError::generic_err("Price not updated"));
     }
-    let close_price = price_reference_data.price;
 
-    round.close_price = Some(close_price);
+    progress_round(
+        deps,
+        env,
+        &config,
+        price_reference_data.price,
+        price_reference_data.round_id,
+    )
+}
+
+/// Oracle hook executing the ended round with the first price after its end.
+/// Skipped instead of failing, a failing hook would revert the feed of the oracle
+pub fn price_updated<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    asset_info: AssetInfo,
+    round_id: u64,
+    price: Decimal,
+    updated_at: u64,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+
+    let skipped = skipped_hook(None);
+
+    // An error would revert the feed of the oracle, so foreign hooks are skipped
+    if deps.api.canonical_address(&env.message.sender)? != config.oracle_addr
+        || !config.auto_settle
+        || !read_import_status(&deps.storage)?.sealed
+        || asset_info.to_raw(deps).ok() != Some(config.bet_asset.clone())
+    {
+        return Ok(skipped);
+    }
+
+    let state: State = read_state(&deps.storage)?;
+    if state.paused || state.epoch.is_zero() {
+        return Ok(skipped);
+    }
 
-    if !round.is_genesis
-        && close_price != round.open_price.unwrap()
-        && !round.up_amount.is_zero()
-        && !round.down_amount.is_zero()
+    let round: Round = read_round(&deps.storage, (state.epoch - Uint128(1))?)?;
+    if round.expired(env.clone(), config.grace_interval)
+        || !round.executable(env.clone())
+        || updated_at < round.end_time
     {
-        let open_price = round.open_price.unwrap();
-        let mut fee = round.total_amount * config.fee_rate;
-        round.reward_amount = (round.total_amount - fee)?;
-
-        if close_price > open_price {
-            if round.reward_amount < round.up_amount {
-                round.reward_amount = round.up_amount;
-                fee = round.down_amount;
-            }
-        } else {
-            if round.reward_amount < round.down_amount {
-                round.reward_amount = round.down_amount;
-                fee = round.up_amount;
-            }
-        }
+        return Ok(skipped);
+    }
 
-        state.total_fee = state.total_fee + fee;
+    // The asset may have been registered again with another unit, or fed again in the block
+    match query_price(deps, config.clone(), env.block.time) {
+        Ok(latest) if latest.round_id == Some(round_id) && latest.price == price => {}
+        Ok(_) => return Ok(skipped),
+        Err(err) => return Ok(skipped_hook(Some(err))),
     }
 
-    // Store result of round
-    store_round(&mut deps.storage, progressing_epoch, &round)?;
+    // Every fallible step of the round precedes its storage writes, so a skipped hook stores nothing
+    match progress_round(deps, env, &config, price, Some(round_id)) {
+        Ok(res) => Ok(res),
+        Err(err) => Ok(skipped_hook(Some(err))),
+    }
+}
 
+fn skipped_hook(error: Option<StdError>) -> HandleResponse {
+    let mut logs = vec![log("action", "price_updated"), log("settled", false)];
+    if let Some(error) = error {
+        logs.push(log("error", error));
+    }
+
+    HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: None,
+    }
+}
+
+/// Settle the progressing round with the close price, lock the betting round and start a new round
+fn progress_round<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    config: &Config,
+    close_price: Decimal,
+    close_round_id: Option<u64>,
+) -> HandleResult {
+    let mut state: State = read_state(&deps.storage)?;
+    let progressing_epoch = (state.epoch - Uint128(1))?;
+    let betting_epoch = state.epoch;
+    let mut round: Round = read_round(&deps.storage, progressing_epoch)?;
     let mut betting_round: Round = read_round(&deps.storage, betting_epoch)?;
+
+    round.settle(close_price)?;
+    round.close_round_id = close_round_id;
+    state.total_fee = state.total_fee + round.distribute(config.fee_rate)?;
+
     betting_round.open_price = Some(close_price);
+    betting_round.open_round_id = close_round_id;
+
+    // Store result of round
+    store_round(&mut deps.storage, progressing_epoch, &round)?;
 
     // Lock betting round
     store_round(&mut deps.storage, betting_epoch, &betting_round)?;
@@ -144,34 +439,154 @@ pub fn execute_round<S: Storage, A: Api, Q: Querier>(
     state.epoch = state.epoch + Uint128(1);
     store_state(&mut deps.storage, &state)?;
 
-    let new_round = Round {
-        start_time: env.block.time,
-        lock_time: env.block.time + config.interval,
-        end_time: env.block.time + config.interval * 2,
-        open_price: None,
-        close_price: None,
-        total_amount: Uint128::zero(),
-        reward_amount: Uint128::zero(),
-        up_amount: Uint128::zero(),
-        down_amount: Uint128::zero(),
-        is_genesis: false,
-    };
+    let new_round = Round::new(env.block.time, config, false);
 
     // Start new round
     store_round(&mut deps.storage, state.epoch, &new_round)?;
 
+    let (placed, messages) =
+        place_standing_orders(deps, &env, config, DEFAULT_STANDING_ORDER_LIMIT as usize)?;
+
     Ok(HandleResponse {
-        messages: vec![],
+        messages,
         log: vec![
             log("action", "execute"),
             log("epoch_finish", progressing_epoch),
             log("epoch_lock", betting_epoch),
             log("close_price", close_price),
+            log("standing_orders", placed),
+        ],
+        data: None,
+    })
+}
+
+pub fn process_standing_orders<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    limit: Option<u32>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+
+    let state: State = read_state(&deps.storage)?;
+    if state.paused {
+        return Err(StdError::generic_err("Paused"));
+    }
+
+    if !read_round(&deps.storage, state.epoch)?.bettable(env.clone()) {
+        return Err(StdError::generic_err("Cannot bet"));
+    }
+
+    let limit = limit
+        .unwrap_or(DEFAULT_STANDING_ORDER_LIMIT)
+        .min(MAX_STANDING_ORDER_LIMIT) as usize;
+    let (placed, messages) = place_standing_orders(deps, &env, &config, limit)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![
+            log("action", "process_standing_orders"),
+            log("epoch", state.epoch),
+            log("standing_orders", placed),
         ],
         data: None,
     })
 }
 
+/// Place standing order bets on the betting round from the cursor, up to `limit` orders.
+/// Exhausted orders are removed and their remaining budget is refunded.
+fn place_standing_orders<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    config: &Config,
+    limit: usize,
+) -> StdResult<(u64, Vec<CosmosMsg>)> {
+    let state: State = read_state(&deps.storage)?;
+    let epoch = state.epoch;
+
+    let mut cursor = read_standing_order_cursor(&deps.storage)?;
+    if cursor.epoch != epoch {
+        cursor = StandingOrderCursor { epoch, next: 0 };
+        store_standing_order_cursor(&mut deps.storage, &cursor)?;
+    }
+
+    let mut round: Round = read_round(&deps.storage, epoch)?;
+    // Strategies follow the last settled round, the previous round is still running
+    let last_winning_outcome = (epoch - Uint128(2))
+        .and_then(|last_epoch| read_round(&deps.storage, last_epoch))
+        .ok()
+        .and_then(|last_round| last_round.winning_outcome);
+
+    let mut count = read_standing_order_count(&deps.storage)?;
+    let mut placed = 0u64;
+    let mut messages = vec![];
+
+    for _ in 0..limit {
+        if cursor.next >= count {
+            break;
+        }
+
+        let user_raw = read_standing_order_owner(&deps.storage, cursor.next)?;
+        let mut order = read_standing_order(&deps.storage, &user_raw)?;
+
+        // Committed bets need a hidden position, so standing orders skip those rounds
+        let position = order
+            .position(&round, last_winning_outcome)
+            .filter(|_| round.commit_reveal.is_none() && order.last_epoch != epoch);
+        let outcome = position
+            .as_ref()
+            .and_then(|position| round.outcome_index(position).ok())
+            .filter(|_| read_bet(&deps.storage, epoch, user_raw.clone()).is_err());
+
+        if let Some(outcome) = outcome {
+            let amount = order.amount_per_round;
+            order.budget = (order.budget - amount)?;
+            round.total_amount = round.total_amount + amount;
+            round.outcome_amounts[outcome] = round.outcome_amounts[outcome] + amount;
+
+            store_bet(
+                &mut deps.storage,
+                epoch,
+                user_raw.clone(),
+                &Bet {
+                    amount,
+                    position,
+                    commitment: None,
+                    claimed: false,
+                },
+            )?;
+
+            order.remaining_rounds -= 1;
+            order.last_epoch = epoch;
+            placed += 1;
+        }
+
+        if order.exhausted() {
+            // The last order takes the place of the removed one
+            remove_standing_order(&mut deps.storage, &user_raw)?;
+            count -= 1;
+
+            if !order.budget.is_zero() {
+                let return_asset = Asset {
+                    amount: order.budget,
+                    info: config.bet_asset.to_normal(deps)?,
+                };
+                messages.push(return_asset.into_msg(
+                    env.contract.address.clone(),
+                    deps.api.human_address(&user_raw)?,
+                )?);
+            }
+        } else {
+            store_standing_order(&mut deps.storage, &user_raw, &order)?;
+            cursor.next += 1;
+        }
+    }
+
+    store_round(&mut deps.storage, epoch, &round)?;
+    store_standing_order_cursor(&mut deps.storage, &cursor)?;
+
+    Ok((placed, messages))
+}
+
 pub fn withdraw<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
@@ -183,29 +598,102 @@ pub fn withdraw<S: Storage, A: Api, Q: Querier>(
         return Err(StdError::unauthorized());
     }
 
+    let state: State = read_state(&deps.storage)?;
+    if state.total_fee.is_zero() {
+        return Err(StdError::generic_err("No stacked fee"));
+    }
+
+    // Small withdrawals skip the timelock
+    let timelocked = config.timelock.as_ref().map_or(false, |timelock| {
+        state.total_fee > timelock.withdraw_threshold
+    });
+    let action = AdminAction::Withdraw {
+        amount: state.total_fee,
+    };
+
+    queue_or_apply(deps, env, &config, action, timelocked)
+}
+
+fn withdraw_fee<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    config: &Config,
+    amount: Uint128,
+) -> HandleResult {
     let mut state: State = read_state(&deps.storage)?;
 
-    let total_fee = state.total_fee;
-    if total_fee > Uint128::zero() {
-        let return_asset = Asset {
-            amount: total_fee,
-            info: config.bet_asset.to_normal(deps)?,
-        };
-        state.total_fee = Uint128::zero();
-
-        store_state(&mut deps.storage, &state)?;
-
-        Ok(HandleResponse {
-            messages: vec![return_asset.into_msg(
-                env.contract.address,
-                deps.api.human_address(&config.treasury_addr)?,
-            )?],
-            log: vec![log("action", "withdraw"), log("amount", total_fee)],
-            data: None,
-        })
-    } else {
-        Err(StdError::generic_err("No stacked fee"))
+    // Fee may have been withdrawn while the action was queued
+    let amount = std::cmp::min(amount, state.total_fee);
+    if amount.is_zero() {
+        return Err(StdError::generic_err("No stacked fee"));
+    }
+
+    let return_asset = Asset {
+        amount,
+        info: config.bet_asset.to_normal(deps)?,
+    };
+    state.total_fee = (state.total_fee - amount)?;
+
+    store_state(&mut deps.storage, &state)?;
+
+    Ok(HandleResponse {
+        messages: vec![return_asset.into_msg(
+            env.contract.address,
+            deps.api.human_address(&config.treasury_addr)?,
+        )?],
+        log: vec![log("action", "withdraw"), log("amount", amount)],
+        data: None,
+    })
+}
+
+pub fn transfer_funds<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    recipient: HumanAddr,
+    amount: Uint128,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    if !read_state(&deps.storage)?.paused {
+        return Err(StdError::generic_err("Not paused"));
+    }
+
+    let action = AdminAction::TransferFunds { recipient, amount };
+
+    queue_or_apply(deps, env, &config, action, true)
+}
+
+fn hand_over_funds<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    config: &Config,
+    recipient: HumanAddr,
+    amount: Uint128,
+) -> HandleResult {
+    // The instance may have been resumed while the action was queued
+    if !read_state(&deps.storage)?.paused {
+        return Err(StdError::generic_err("Not paused"));
     }
+
+    let asset = Asset {
+        amount,
+        info: config.bet_asset.to_normal(deps)?,
+    };
+
+    Ok(HandleResponse {
+        messages: vec![asset.into_msg(env.contract.address, recipient.clone())?],
+        log: vec![
+            log("action", "transfer_funds"),
+            log("recipient", recipient),
+            log("amount", amount),
+        ],
+        data: None,
+    })
 }
 
 pub fn pause<S: Storage, A: Api, Q: Querier>(deps: &mut Extern<S, A, Q>, env: Env) -> HandleResult {
@@ -254,35 +742,13 @@ pub fn start_genesis_round<S: Storage, A: Api, Q: Querier>(
     store_round(
         &mut deps.storage,
         epoch,
-        &Round {
-            start_time: env.block.time - config.interval,
-            lock_time: env.block.time,
-            end_time: env.block.time + config.interval,
-            open_price: None,
-            close_price: None,
-            total_amount: Uint128::zero(),
-            reward_amount: Uint128::zero(),
-            up_amount: Uint128::zero(),
-            down_amount: Uint128::zero(),
-            is_genesis: true,
-        },
+        &Round::new(env.block.time - config.interval, &config, true),
     )?;
 
     store_round(
         &mut deps.storage,
         epoch + Uint128(1),
-        &Round {
-            start_time: env.block.time,
-            lock_time: env.block.time + config.interval,
-            end_time: env.block.time + config.interval * 2,
-            open_price: None,
-            close_price: None,
-            total_amount: Uint128::zero(),
-            reward_amount: Uint128::zero(),
-            up_amount: Uint128::zero(),
-            down_amount: Uint128::zero(),
-            is_genesis: false,
-        },
+        &Round::new(env.block.time, &config, false),
     )?;
 
     state.paused = false;
@@ -295,3 +761,236 @@ pub fn start_genesis_round<S: Storage, A: Api, Q: Querier>(
         data: None,
     })
 }
+
+pub fn set_buckets<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    bounds: Vec<Decimal>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.operator_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    queue_or_apply(deps, env, &config, AdminAction::SetBuckets { bounds }, true)
+}
+
+pub fn update_auto_settle<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    auto_settle: bool,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    queue_or_apply(
+        deps,
+        env,
+        &config,
+        AdminAction::UpdateAutoSettle { auto_settle },
+        true,
+    )
+}
+
+pub fn set_oracle_viewing_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    key: String,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    queue_or_apply(
+        deps,
+        env,
+        &config,
+        AdminAction::SetOracleViewingKey { key },
+        true,
+    )
+}
+
+/// Set the viewing key of this contract at the oracle
+pub fn oracle_viewing_key_msg<A: Api>(
+    api: &A,
+    config: &Config,
+    key: String,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: api.human_address(&config.oracle_addr)?,
+        callback_code_hash: config.oracle_code_hash.clone(),
+        msg: to_binary(&OracleHandleMsg::SetViewingKey { key, padding: None })?,
+        send: vec![],
+    }))
+}
+
+pub fn update_commit_reveal<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    commit_reveal: Option<CommitRevealConfig>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    queue_or_apply(
+        deps,
+        env,
+        &config,
+        AdminAction::UpdateCommitReveal { commit_reveal },
+        true,
+    )
+}
+
+/// Committed bets must be revealed before the round can be executed
+pub fn validate_commit_reveal(
+    commit_reveal: &Option<CommitRevealConfig>,
+    interval: u64,
+) -> StdResult<()> {
+    if let Some(commit_reveal) = commit_reveal {
+        if commit_reveal.reveal_interval == 0 || commit_reveal.reveal_interval > interval {
+            return Err(StdError::generic_err("Invalid reveal interval"));
+        }
+
+        if commit_reveal.unrevealed_penalty_rate > Decimal::one() {
+            return Err(StdError::generic_err("Invalid penalty rate"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Bounds are multipliers of the open price and must be strictly increasing.
+/// `[0.99, 1.01]` defines buckets below 99%, between 99% and 101%, and from 101% of the open price.
+pub fn validate_bucket_bounds(bounds: &[Decimal]) -> StdResult<()> {
+    if bounds.len() > MAX_BUCKET_BOUNDS {
+        return Err(StdError::generic_err("Too many buckets"));
+    }
+
+    let mut prev = Decimal::zero();
+    for bound in bounds.iter() {
+        if *bound <= prev {
+            return Err(StdError::generic_err("Invalid bucket bounds"));
+        }
+        prev = *bound;
+    }
+
+    Ok(())
+}
+
+pub fn create_strike_market<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    strike_price: Decimal,
+    bet_deadline: u64,
+    expiry: u64,
+    grace_interval: u64,
+    max_price_age: u64,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.operator_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    if strike_price.is_zero() {
+        return Err(StdError::generic_err("Invalid strike price"));
+    }
+
+    if bet_deadline <= env.block.time || expiry < bet_deadline {
+        return Err(StdError::generic_err("Invalid market period"));
+    }
+
+    let id = read_strike_market_count(&deps.storage)? + 1;
+    store_strike_market(
+        &mut deps.storage,
+        &StrikeMarket::new(
+            id,
+            env.block.time,
+            strike_price,
+            bet_deadline,
+            expiry,
+            grace_interval,
+            max_price_age,
+        ),
+    )?;
+    store_strike_market_count(&mut deps.storage, id)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "create_strike_market"),
+            log("id", id),
+            log("strike_price", strike_price),
+            log("bet_deadline", bet_deadline),
+            log("expiry", expiry),
+        ],
+        data: None,
+    })
+}
+
+pub fn settle_strike_market<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    id: u64,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.operator_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut market = read_strike_market(&deps.storage, id)?;
+
+    if market.round.expired(env.clone(), market.grace_interval) {
+        return Err(StdError::generic_err("Expired"));
+    }
+
+    if !market.round.executable(env.clone()) {
+        return Err(StdError::generic_err("Cannot execute"));
+    }
+
+    // The price at expiry is final once the oracle is fed after it
+    let price_reference_data = query_price(deps, config.clone(), env.block.time)?;
+    if price_reference_data.last_updated_time <= market.round.end_time {
+        return Err(StdError::generic_err("Price not updated"));
+    }
+    let price = query_price_at(deps, config.clone(), market.round.end_time)?;
+    let price_age = market.round.end_time - price.last_updated_time;
+    if price_age > market.max_price_age {
+        return Err(StdError::generic_err("Price at expiry is too old"));
+    }
+    let close_price = price.price;
+
+    market.round.settle(close_price)?;
+    let fee = market.round.distribute(config.fee_rate)?;
+    store_strike_market(&mut deps.storage, &market)?;
+
+    let mut state: State = read_state(&deps.storage)?;
+    state.total_fee = state.total_fee + fee;
+    store_state(&mut deps.storage, &state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "settle_strike_market"),
+            log("id", id),
+            log("close_price", close_price),
+        ],
+        data: None,
+    })
+}
@@ -0,0 +1,401 @@
+use cosmwasm_std::{
+    from_binary, log, to_binary, Api, Binary, CanonicalAddr, Env, Extern, HandleResponse,
+    HandleResult, Querier, StdError, StdResult, Storage, Uint128,
+};
+use secret_toolkit::{permit::RevokedPermits, snip20::balance_query};
+use serde::{Deserialize, Serialize};
+
+use crate::manage::oracle_viewing_key_msg;
+use crate::query::validate_viewing_key;
+use crate::state::{
+    add_revoked_permit, add_standing_order, read_account, read_account_count, read_balance,
+    read_bet, read_bet_index, read_bet_index_count, read_config, read_import_status,
+    read_oracle_viewing_key, read_queued_action, read_queued_action_count, read_revoked_permit,
+    read_revoked_permit_count, read_round, read_standing_order, read_state, read_strike_bet,
+    read_strike_market, read_strike_market_count, read_viewing_key, register_account,
+    remove_standing_order, store_balance, store_bet, store_config, store_import_status,
+    store_oracle_viewing_key, store_queued_action, store_queued_action_count, store_round,
+    store_standing_order_cursor, store_state, store_strike_bet, store_strike_market,
+    store_strike_market_count, store_viewing_key_hash, Bet, BetKey, Config, RevokedPermit, Round,
+    StandingOrder, StandingOrderCursor, StrikeMarket, PREFIX_REVOKED_PERMITS,
+};
+use prediction::{
+    asset::AssetInfoRaw,
+    prediction::{ExportPage, ExportResponse, ImportStatusResponse, QueuedAction, State},
+    rand::sha_256,
+};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+const BLOCK_SIZE: usize = 256;
+
+/// Exported record, imported in the same form
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportRecord {
+    Config(Config),
+    State(State),
+    Round {
+        epoch: Uint128,
+        round: Round,
+    },
+    Bet {
+        key: BetKey,
+        user: CanonicalAddr,
+        bet: Bet,
+    },
+    Account {
+        user: CanonicalAddr,
+        viewing_key_hash: Option<Binary>,
+        balance: Uint128,
+        standing_order: Option<StandingOrder>,
+    },
+    StrikeMarket(StrikeMarket),
+    QueuedAction(QueuedAction),
+    /// Bet asset held by the exporting instance, required by the importing one before sealing
+    Funds(Uint128),
+    /// Registered again at the oracle for the importing instance
+    OracleViewingKey(String),
+    RevokedPermit(RevokedPermit),
+}
+
+pub fn query_export<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    key: String,
+    page: ExportPage,
+) -> StdResult<ExportResponse> {
+    let config: Config = read_config(&deps.storage)?;
+    let owner = deps.api.human_address(&config.owner_addr)?;
+    if !validate_viewing_key(deps, owner, key)? {
+        return Err(StdError::generic_err("Invalid viewing key"));
+    }
+
+    let storage = &deps.storage;
+    let (records, last) = match page {
+        ExportPage::Config {} => (vec![ExportRecord::Config(config)], None),
+        ExportPage::State {} => {
+            let mut records = vec![
+                ExportRecord::State(read_state(storage)?),
+                ExportRecord::Funds(bet_asset_balance(deps, &config)?),
+            ];
+            if let Some(key) = read_oracle_viewing_key(storage)? {
+                records.push(ExportRecord::OracleViewingKey(key));
+            }
+            (records, None)
+        }
+        ExportPage::Rounds { start_after, limit } => {
+            let epoch = read_state(storage)?.epoch.u128() as u64;
+            export_page(start_after, limit, 1, epoch + 1, |epoch| {
+                let epoch = Uint128(epoch as u128);
+                Ok(Some(ExportRecord::Round {
+                    epoch,
+                    round: read_round(storage, epoch)?,
+                }))
+            })?
+        }
+        ExportPage::Bets { start_after, limit } => {
+            let count = read_bet_index_count(storage)?;
+            export_page(start_after, limit, 0, count, |index| {
+                let bet_index = read_bet_index(storage, index)?;
+                let bet = match &bet_index.key {
+                    BetKey::Round { epoch } => read_bet(storage, *epoch, bet_index.user.clone())?,
+                    BetKey::StrikeMarket { id } => read_strike_bet(storage, *id, &bet_index.user)?,
+                };
+                Ok(Some(ExportRecord::Bet {
+                    key: bet_index.key,
+                    user: bet_index.user,
+                    bet,
+                }))
+            })?
+        }
+        ExportPage::Accounts { start_after, limit } => {
+            let count = read_account_count(storage)?;
+            export_page(start_after, limit, 0, count, |index| {
+                let user = read_account(storage, index)?;
+                Ok(Some(ExportRecord::Account {
+                    viewing_key_hash: read_viewing_key(storage, &user)?,
+                    balance: read_balance(storage, &user)?,
+                    standing_order: read_standing_order(storage, &user).ok(),
+                    user,
+                }))
+            })?
+        }
+        ExportPage::StrikeMarkets { start_after, limit } => {
+            let count = read_strike_market_count(storage)?;
+            export_page(start_after, limit, 1, count + 1, |id| {
+                Ok(Some(ExportRecord::StrikeMarket(read_strike_market(
+                    storage, id,
+                )?)))
+            })?
+        }
+        ExportPage::QueuedActions { start_after, limit } => {
+            let count = read_queued_action_count(storage)?;
+            export_page(start_after, limit, 1, count + 1, |id| {
+                Ok(read_queued_action(storage, id)?.map(ExportRecord::QueuedAction))
+            })?
+        }
+        ExportPage::RevokedPermits { start_after, limit } => {
+            let count = read_revoked_permit_count(storage)?;
+            export_page(start_after, limit, 0, count, |index| {
+                Ok(Some(ExportRecord::RevokedPermit(read_revoked_permit(
+                    storage, index,
+                )?)))
+            })?
+        }
+    };
+
+    let data = to_binary(&records)?;
+
+    Ok(ExportResponse {
+        checksum: Binary(sha_256(data.as_slice()).to_vec()),
+        data,
+        last,
+    })
+}
+
+/// Records of the indexes from `first` to `end` exclusive, missing records are skipped
+fn export_page<F>(
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    first: u64,
+    end: u64,
+    mut read: F,
+) -> StdResult<(Vec<ExportRecord>, Option<u64>)>
+where
+    F: FnMut(u64) -> StdResult<Option<ExportRecord>>,
+{
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map_or(first, |index| index + 1);
+
+    let mut records = vec![];
+    let mut last = None;
+    for index in start..end {
+        if records.len() == limit {
+            break;
+        }
+        if let Some(record) = read(index)? {
+            records.push(record);
+            last = Some(index);
+        }
+    }
+
+    Ok((records, last))
+}
+
+pub fn import<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    data: Binary,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut status = read_import_status(&deps.storage)?;
+    if status.sealed {
+        return Err(StdError::generic_err("Import sealed"));
+    }
+
+    let records: Vec<ExportRecord> = from_binary(&data)?;
+    let record_count = records.len();
+    let mut messages = vec![];
+    for record in records.into_iter() {
+        // Viewing keys at the oracle belong to the contract address
+        if let ExportRecord::OracleViewingKey(key) = &record {
+            messages.push(oracle_viewing_key_msg(&deps.api, &config, key.clone())?);
+        }
+        if let ExportRecord::Funds(amount) = &record {
+            status.funds = *amount;
+        }
+        import_record(&mut deps.storage, &config, record)?;
+    }
+
+    status.pages += 1;
+    status.checksum = chain_checksum(&status.checksum, &sha_256(data.as_slice()));
+    store_import_status(&mut deps.storage, &status)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![
+            log("action", "import"),
+            log("records", record_count),
+            log("checksum", status.checksum.to_base64()),
+        ],
+        data: None,
+    })
+}
+
+pub fn seal_import<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    checksum: Binary,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut status = read_import_status(&deps.storage)?;
+    if status.sealed {
+        return Err(StdError::generic_err("Import sealed"));
+    }
+
+    if status.checksum != checksum {
+        return Err(StdError::generic_err("Checksum mismatch"));
+    }
+
+    // The funds of the old instance are transferred with `transfer_funds` before sealing
+    if bet_asset_balance(deps, &config)? < status.funds {
+        return Err(StdError::generic_err(
+            "Insufficient funds for imported liabilities",
+        ));
+    }
+
+    // Standing orders were re-indexed by the import, so the betting round is processed again
+    // from the first order. Orders which already bet in it are skipped by their `last_epoch`.
+    let state = read_state(&deps.storage)?;
+    store_standing_order_cursor(
+        &mut deps.storage,
+        &StandingOrderCursor {
+            epoch: state.epoch,
+            next: 0,
+        },
+    )?;
+
+    status.sealed = true;
+    store_import_status(&mut deps.storage, &status)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "seal_import"), log("pages", status.pages)],
+        data: None,
+    })
+}
+
+pub fn query_import_status<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ImportStatusResponse> {
+    let status = read_import_status(&deps.storage)?;
+
+    Ok(ImportStatusResponse {
+        sealed: status.sealed,
+        pages: status.pages,
+        checksum: status.checksum,
+        funds: status.funds,
+    })
+}
+
+/// Balance of the bet asset held by this instance
+fn bet_asset_balance<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: &Config,
+) -> StdResult<Uint128> {
+    let contract_addr = deps.api.human_address(&config.contract_addr)?;
+    match &config.bet_asset {
+        AssetInfoRaw::NativeToken { denom } => {
+            Ok(deps.querier.query_balance(contract_addr, denom)?.amount)
+        }
+        AssetInfoRaw::Token {
+            contract_addr: token_addr,
+            token_code_hash,
+            viewing_key,
+        } => Ok(balance_query(
+            &deps.querier,
+            contract_addr,
+            viewing_key.clone(),
+            BLOCK_SIZE,
+            token_code_hash.clone(),
+            deps.api.human_address(token_addr)?,
+        )?
+        .amount),
+    }
+}
+
+/// Checksum of the imported pages, `sha256(checksum || page_checksum)`
+pub fn chain_checksum(checksum: &Binary, page_checksum: &[u8]) -> Binary {
+    Binary(sha_256(&[checksum.as_slice(), page_checksum].concat()).to_vec())
+}
+
+fn import_record<S: Storage>(
+    storage: &mut S,
+    config: &Config,
+    record: ExportRecord,
+) -> StdResult<()> {
+    match record {
+        // The new instance keeps its own address and owner
+        ExportRecord::Config(imported) => store_config(
+            storage,
+            &Config {
+                contract_addr: config.contract_addr.clone(),
+                owner_addr: config.owner_addr.clone(),
+                ..imported
+            },
+        ),
+        ExportRecord::State(state) => store_state(storage, &state),
+        ExportRecord::Round { epoch, round } => store_round(storage, epoch, &round),
+        ExportRecord::Bet { key, user, bet } => match key {
+            BetKey::Round { epoch } => store_bet(storage, epoch, user, &bet),
+            BetKey::StrikeMarket { id } => store_strike_bet(storage, id, &user, &bet),
+        },
+        ExportRecord::Account {
+            user,
+            viewing_key_hash,
+            balance,
+            standing_order,
+        } => {
+            register_account(storage, &user)?;
+
+            if let Some(hash) = viewing_key_hash {
+                store_viewing_key_hash(storage, &user, hash.as_slice())?;
+            }
+
+            store_balance(storage, &user, balance)?;
+
+            if let Some(order) = standing_order {
+                if read_standing_order(storage, &user).is_ok() {
+                    remove_standing_order(storage, &user)?;
+                }
+                add_standing_order(storage, &user, order)?;
+            }
+
+            Ok(())
+        }
+        ExportRecord::StrikeMarket(market) => {
+            if market.id > read_strike_market_count(storage)? {
+                store_strike_market_count(storage, market.id)?;
+            }
+            store_strike_market(storage, &market)
+        }
+        ExportRecord::QueuedAction(action) => {
+            if action.id > read_queued_action_count(storage)? {
+                store_queued_action_count(storage, action.id)?;
+            }
+            store_queued_action(storage, &action)
+        }
+        ExportRecord::Funds(_) => Ok(()),
+        ExportRecord::OracleViewingKey(key) => store_oracle_viewing_key(storage, &key),
+        ExportRecord::RevokedPermit(revoked) => {
+            RevokedPermits::revoke_permit(
+                storage,
+                PREFIX_REVOKED_PERMITS,
+                &revoked.user,
+                &revoked.permit_name,
+            );
+            add_revoked_permit(storage, &revoked)
+        }
+    }
+}
+
+/// Handlers other than the import fail until the import is sealed
+pub fn assert_import_sealed<S: Storage>(storage: &S) -> StdResult<()> {
+    if read_import_status(storage)?.sealed {
+        Ok(())
+    } else {
+        Err(StdError::generic_err("Import not sealed"))
+    }
+}
@@ -4,15 +4,27 @@ use cosmwasm_std::{
 };
 
 use crate::state::{
-    read_bet, read_config, read_round, read_state, read_viewing_key, Bet, Config, Round,
+    read_balance, read_bet, read_config, read_oracle_viewing_key, read_queued_action,
+    read_queued_action_count, read_round, read_standing_order, read_standing_order_count,
+    read_standing_order_cursor, read_state, read_strike_bet, read_strike_market,
+    read_strike_market_count, read_viewing_key, Bet, Config, Round, StandingOrder, StrikeMarket,
     PREFIX_REVOKED_PERMITS,
 };
 use prediction::{
-    oracle::{PriceInfo, QueryMsg as OracleQueryMsg},
-    prediction::{ConfigResponse, QueryWithPermit, State},
+    oracle::{
+        AssetResponse, LatestPriceResponse, PriceInfo, QueryMsg as OracleQueryMsg, FEED_PAUSED,
+    },
+    prediction::{
+        AdminAction, BalanceResponse, ConfigResponse, QueryWithPermit, QueuedAction,
+        StandingOrderProgressResponse, State,
+    },
     viewing_key::ViewingKey,
 };
 use secret_toolkit::permit::{validate, Permission, Permit};
+use serde::de::DeserializeOwned;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 
 pub fn query_config<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
@@ -29,6 +41,11 @@ pub fn query_config<S: Storage, A: Api, Q: Querier>(
         fee_rate: config.fee_rate,
         interval: config.interval,
         grace_interval: config.grace_interval,
+        bucket_bounds: config.bucket_bounds,
+        commit_reveal: config.commit_reveal,
+        timelock: config.timelock,
+        quote_currency: config.quote_currency,
+        auto_settle: config.auto_settle,
     };
 
     Ok(resp)
@@ -70,31 +87,237 @@ pub fn query_bet_raw<S: Storage, A: Api, Q: Querier>(
     Ok(bet)
 }
 
+pub fn query_strike_market<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    id: u64,
+) -> StdResult<StrikeMarket> {
+    read_strike_market(&deps.storage, id)
+}
+
+pub fn query_strike_markets<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<StrikeMarket>> {
+    let count = read_strike_market_count(&deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.unwrap_or(0) + 1;
+
+    (start..=count)
+        .take(limit)
+        .map(|id| read_strike_market(&deps.storage, id))
+        .collect()
+}
+
+pub fn query_strike_bet<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    id: u64,
+    user: HumanAddr,
+    key: String,
+) -> StdResult<Bet> {
+    let is_valid = validate_viewing_key(deps, user.clone(), key)?;
+    if is_valid {
+        read_strike_bet(&deps.storage, id, &deps.api.canonical_address(&user)?)
+    } else {
+        Err(StdError::generic_err("Invalid viewing key"))
+    }
+}
+
+pub fn query_balance<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    user: HumanAddr,
+    key: String,
+) -> StdResult<BalanceResponse> {
+    let is_valid = validate_viewing_key(deps, user.clone(), key)?;
+    if is_valid {
+        query_balance_raw(deps, user)
+    } else {
+        Err(StdError::generic_err("Invalid viewing key"))
+    }
+}
+
+pub fn query_balance_raw<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    user: HumanAddr,
+) -> StdResult<BalanceResponse> {
+    Ok(BalanceResponse {
+        amount: read_balance(&deps.storage, &deps.api.canonical_address(&user)?)?,
+    })
+}
+
+pub fn query_standing_order<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    user: HumanAddr,
+    key: String,
+) -> StdResult<StandingOrder> {
+    let is_valid = validate_viewing_key(deps, user.clone(), key)?;
+    if is_valid {
+        read_standing_order(&deps.storage, &deps.api.canonical_address(&user)?)
+    } else {
+        Err(StdError::generic_err("Invalid viewing key"))
+    }
+}
+
+pub fn query_standing_order_progress<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<StandingOrderProgressResponse> {
+    let state: State = read_state(&deps.storage)?;
+    let cursor = read_standing_order_cursor(&deps.storage)?;
+
+    Ok(StandingOrderProgressResponse {
+        epoch: state.epoch,
+        processed: if cursor.epoch == state.epoch {
+            cursor.next
+        } else {
+            0
+        },
+        total: read_standing_order_count(&deps.storage)?,
+    })
+}
+
+/// Executed and cancelled actions are skipped
+pub fn query_pending_actions<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<QueuedAction>> {
+    let count = read_queued_action_count(&deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.unwrap_or(0) + 1;
+
+    let mut actions = vec![];
+    for id in start..=count {
+        if actions.len() == limit {
+            break;
+        }
+        if let Some(mut action) = read_queued_action(&deps.storage, id)? {
+            // The oracle viewing key stays private while queued
+            if let AdminAction::SetOracleViewingKey { key } = &mut action.action {
+                key.clear();
+            }
+            actions.push(action);
+        }
+    }
+
+    Ok(actions)
+}
+
+/// Latest price of the bet asset, fails if it is stale at `time`
 pub fn query_price<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     config: Config,
+    time: u64,
+) -> StdResult<LatestPriceResponse> {
+    let msg = OracleQueryMsg::LatestPriceChecked {
+        asset_info: config.bet_asset.to_normal(&deps)?,
+        time,
+    };
+    let price: LatestPriceResponse = query_oracle(deps, config.clone(), msg)?;
+    check_price_unit(&config, &price.quote_currency, price.decimals)?;
+
+    Ok(price)
+}
+
+/// Price of the bet asset recorded at or before `timestamp`
+pub fn query_price_at<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: Config,
+    timestamp: u64,
 ) -> StdResult<PriceInfo> {
-    let price_data: PriceInfo = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+    let msg = OracleQueryMsg::PriceAt {
+        asset_info: config.bet_asset.to_normal(&deps)?,
+        timestamp,
+    };
+    let price: PriceInfo = query_oracle(deps, config.clone(), msg)?;
+    check_price_unit(&config, &price.quote_currency, price.decimals)?;
+
+    Ok(price)
+}
+
+/// Fail if the asset was registered again with another unit since the quote currency check
+fn check_price_unit(config: &Config, quote_currency: &str, decimals: u8) -> StdResult<()> {
+    if let Some(expected) = &config.quote_currency {
+        if quote_currency != expected {
+            return Err(StdError::generic_err("Oracle quote currency mismatch"));
+        }
+    }
+    if let Some(expected) = config.price_decimals {
+        if decimals != expected {
+            return Err(StdError::generic_err("Oracle decimals mismatch"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Authenticated by the oracle viewing key if set, a paused feed fails with "Oracle feed paused"
+fn query_oracle<S: Storage, A: Api, Q: Querier, T: DeserializeOwned>(
+    deps: &Extern<S, A, Q>,
+    config: Config,
+    mut msg: OracleQueryMsg,
+) -> StdResult<T> {
+    if let Some(key) = read_oracle_viewing_key(&deps.storage)? {
+        msg = OracleQueryMsg::WithViewingKey {
+            consumer: deps.api.human_address(&config.contract_addr)?,
+            key,
+            query: Box::new(msg),
+        };
+    }
+
+    deps.querier
+        .query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: deps.api.human_address(&config.oracle_addr)?,
+            callback_code_hash: config.oracle_code_hash,
+            msg: to_binary(&msg)?,
+        }))
+        .map_err(|err| {
+            // The querier may wrap the error of the oracle in its own message
+            if err.to_string().contains(FEED_PAUSED) {
+                StdError::generic_err("Oracle feed paused")
+            } else {
+                err
+            }
+        })
+}
+
+/// Fail unless the oracle prices the bet asset in the configured quote currency,
+/// and record the decimals of its prices
+pub fn check_quote_currency<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: &mut Config,
+) -> StdResult<()> {
+    let quote_currency = match &config.quote_currency {
+        Some(quote_currency) => quote_currency,
+        None => return Ok(()),
+    };
+
+    let asset: AssetResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: deps.api.human_address(&config.oracle_addr)?,
-        callback_code_hash: config.oracle_code_hash,
-        msg: to_binary(&OracleQueryMsg::LatestPrice {
+        callback_code_hash: config.oracle_code_hash.clone(),
+        msg: to_binary(&OracleQueryMsg::Asset {
             asset_info: config.bet_asset.to_normal(&deps)?,
         })?,
     }))?;
+    if &asset.metadata.quote_currency != quote_currency {
+        return Err(StdError::generic_err("Oracle quote currency mismatch"));
+    }
+    config.price_decimals = Some(asset.metadata.decimals);
 
-    Ok(price_data)
+    Ok(())
 }
 
-fn validate_viewing_key<S: Storage, A: Api, Q: Querier>(
+pub fn validate_viewing_key<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     user: HumanAddr,
     key: String,
 ) -> StdResult<bool> {
     let vk = ViewingKey(key);
     let canonical_addr = deps.api.canonical_address(&user)?;
-    let expected_key = read_viewing_key(&deps.storage, &canonical_addr)?;
 
-    Ok(vk.check_viewing_key(&expected_key.to_hashed()))
+    Ok(match read_viewing_key(&deps.storage, &canonical_addr)? {
+        Some(hashed_key) => vk.check_viewing_key(hashed_key.as_slice()),
+        None => false,
+    })
 }
 
 pub fn permit_queries<S: Storage, A: Api, Q: Querier>(
@@ -120,5 +343,42 @@ pub fn permit_queries<S: Storage, A: Api, Q: Querier>(
 
             to_binary(&query_bet_raw(deps, epoch, account)?)
         }
+        QueryWithPermit::StrikeBet { id } => {
+            if !permit.check_permission(&Permission::Owner) {
+                return Err(StdError::generic_err(format!(
+                    "No permission to query balance, got permissions {:?}",
+                    permit.params.permissions
+                )));
+            }
+
+            to_binary(&read_strike_bet(
+                &deps.storage,
+                id,
+                &deps.api.canonical_address(&account)?,
+            )?)
+        }
+        QueryWithPermit::StandingOrder {} => {
+            if !permit.check_permission(&Permission::Owner) {
+                return Err(StdError::generic_err(format!(
+                    "No permission to query balance, got permissions {:?}",
+                    permit.params.permissions
+                )));
+            }
+
+            to_binary(&read_standing_order(
+                &deps.storage,
+                &deps.api.canonical_address(&account)?,
+            )?)
+        }
+        QueryWithPermit::Balance {} => {
+            if !permit.check_permission(&Permission::Balance) {
+                return Err(StdError::generic_err(format!(
+                    "No permission to query balance, got permissions {:?}",
+                    permit.params.permissions
+                )));
+            }
+
+            to_binary(&query_balance_raw(deps, account)?)
+        }
     }
 }
@@ -1,17 +1,39 @@
-use cosmwasm_std::{CanonicalAddr, Decimal, Env, StdResult, Storage, Uint128};
+use cosmwasm_std::{
+    Binary, CanonicalAddr, Decimal, Env, HumanAddr, StdError, StdResult, Storage, Uint128,
+};
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use prediction::{
     asset::AssetInfoRaw,
-    prediction::{Position, State},
+    math::{decimal_mul, decimal_sub},
+    prediction::{CommitRevealConfig, Position, QueuedAction, State, Strategy, TimelockConfig},
     viewing_key::ViewingKey,
 };
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_STATE: &[u8] = b"state";
 static PREFIX_ROUND: &[u8] = b"round";
+static KEY_STRIKE_MARKET_COUNT: &[u8] = b"strike_market_count";
+static PREFIX_STRIKE_MARKET: &[u8] = b"strike_market";
+static PREFIX_STRIKE_BET: &[u8] = b"strike_bet";
+static KEY_STANDING_ORDER_COUNT: &[u8] = b"standing_order_count";
+static KEY_STANDING_ORDER_CURSOR: &[u8] = b"standing_order_cursor";
+static PREFIX_STANDING_ORDER: &[u8] = b"standing_order";
+static PREFIX_STANDING_ORDER_OWNER: &[u8] = b"standing_order_owner";
+static PREFIX_BALANCE: &[u8] = b"balance";
+static KEY_QUEUED_ACTION_COUNT: &[u8] = b"queued_action_count";
+static KEY_BET_INDEX_COUNT: &[u8] = b"bet_index_count";
+static PREFIX_BET_INDEX: &[u8] = b"bet_index";
+static KEY_ACCOUNT_COUNT: &[u8] = b"account_count";
+static PREFIX_ACCOUNT: &[u8] = b"account";
+static PREFIX_ACCOUNT_INDEX: &[u8] = b"account_index";
+static KEY_IMPORT_STATUS: &[u8] = b"import_status";
+static KEY_ORACLE_VIEWING_KEY: &[u8] = b"oracle_viewing_key";
+static PREFIX_QUEUED_ACTION: &[u8] = b"queued_action";
+static KEY_REVOKED_PERMIT_COUNT: &[u8] = b"revoked_permit_count";
+static PREFIX_REVOKED_PERMIT_INDEX: &[u8] = b"revoked_permit_index";
 pub const PREFIX_REVOKED_PERMITS: &str = "revoked_permits";
 pub const PREFIX_VIEW_KEY: &[u8] = b"viewingkey";
 
@@ -28,6 +50,13 @@ pub struct Config {
     pub interval: u64,
     pub grace_interval: u64,
     pub prng_seed: Vec<u8>,
+    pub bucket_bounds: Vec<Decimal>,
+    pub commit_reveal: Option<CommitRevealConfig>,
+    pub timelock: Option<TimelockConfig>,
+    pub quote_currency: Option<String>,
+    /// Decimals of the oracle prices, recorded by the quote currency check
+    pub price_decimals: Option<u8>,
+    pub auto_settle: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -37,14 +66,159 @@ pub struct Round {
     pub end_time: u64,
     pub open_price: Option<Decimal>,
     pub close_price: Option<Decimal>,
+    /// Oracle rounds of the open and close prices
+    pub open_round_id: Option<u64>,
+    pub close_round_id: Option<u64>,
     pub total_amount: Uint128,
     pub reward_amount: Uint128,
-    pub up_amount: Uint128,
-    pub down_amount: Uint128,
+    /// Bucket bounds relative to the open price, empty for up/down rounds
+    pub bucket_bounds: Vec<Decimal>,
+    /// Bet amount of each outcome
+    pub outcome_amounts: Vec<Uint128>,
+    pub winning_outcome: Option<u8>,
+    /// Positions are committed while betting and revealed after lock
+    pub commit_reveal: Option<CommitRevealConfig>,
     pub is_genesis: bool,
 }
 
 impl Round {
+    pub fn new(start_time: u64, config: &Config, is_genesis: bool) -> Self {
+        let outcome_count = if config.bucket_bounds.is_empty() {
+            2
+        } else {
+            config.bucket_bounds.len() + 1
+        };
+
+        Round {
+            start_time,
+            lock_time: start_time + config.interval,
+            end_time: start_time + config.interval * 2,
+            open_price: None,
+            close_price: None,
+            open_round_id: None,
+            close_round_id: None,
+            total_amount: Uint128::zero(),
+            reward_amount: Uint128::zero(),
+            bucket_bounds: config.bucket_bounds.clone(),
+            outcome_amounts: vec![Uint128::zero(); outcome_count],
+            winning_outcome: None,
+            commit_reveal: config.commit_reveal.clone(),
+            is_genesis,
+        }
+    }
+
+    pub fn outcome_index(&self, position: &Position) -> StdResult<usize> {
+        let valid = match position {
+            Position::Bucket(index) => {
+                !self.bucket_bounds.is_empty() && (*index as usize) < self.outcome_amounts.len()
+            }
+            _ => self.bucket_bounds.is_empty(),
+        };
+
+        if valid {
+            Ok(position.outcome() as usize)
+        } else {
+            Err(StdError::generic_err("Invalid position"))
+        }
+    }
+
+    /// Record close price and resolve the winning outcome
+    pub fn settle(&mut self, close_price: Decimal) -> StdResult<()> {
+        self.close_price = Some(close_price);
+        self.winning_outcome = match self.open_price {
+            Some(open_price) if self.bucket_bounds.is_empty() => {
+                if close_price > open_price {
+                    Some(Position::Up.outcome())
+                } else if close_price < open_price {
+                    Some(Position::Down.outcome())
+                } else {
+                    None
+                }
+            }
+            Some(open_price) => {
+                let mut bucket = 0u8;
+                for bound in self.bucket_bounds.iter() {
+                    if close_price >= decimal_mul(open_price, *bound)? {
+                        bucket += 1;
+                    }
+                }
+                Some(bucket)
+            }
+            None => None,
+        };
+
+        Ok(())
+    }
+
+    /// Split the revealed pool into winner reward and fee, returns the fee
+    /// including the penalty of unrevealed bets
+    pub fn distribute(&mut self, fee_rate: Decimal) -> StdResult<Uint128> {
+        let unrevealed_amount = (self.total_amount - self.revealed_amount())?;
+        let penalty = (unrevealed_amount - unrevealed_amount * self.unrevealed_refund_rate()?)?;
+
+        let winning_amount = self.winning_amount();
+        if self.is_genesis || winning_amount.is_zero() || self.staked_outcomes() < 2 {
+            return Ok(penalty);
+        }
+
+        let pool = self.revealed_amount();
+        let mut fee = pool * fee_rate;
+        self.reward_amount = (pool - fee)?;
+
+        if self.reward_amount < winning_amount {
+            self.reward_amount = winning_amount;
+            fee = (pool - winning_amount)?;
+        }
+
+        Ok(fee + penalty)
+    }
+
+    /// Sum of bets with a known position
+    pub fn revealed_amount(&self) -> Uint128 {
+        self.outcome_amounts
+            .iter()
+            .fold(Uint128::zero(), |total, amount| total + *amount)
+    }
+
+    fn unrevealed_refund_rate(&self) -> StdResult<Decimal> {
+        match &self.commit_reveal {
+            Some(commit_reveal) => {
+                decimal_sub(Decimal::one(), commit_reveal.unrevealed_penalty_rate)
+            }
+            None => Ok(Decimal::one()),
+        }
+    }
+
+    /// End of the betting phase including the reveal window
+    pub fn reveal_end_time(&self) -> u64 {
+        match &self.commit_reveal {
+            Some(commit_reveal) => self.lock_time + commit_reveal.reveal_interval,
+            None => self.lock_time,
+        }
+    }
+
+    /// Opens at lock, even if the round is executed later
+    pub fn revealable(&self, env: Env) -> bool {
+        self.commit_reveal.is_some()
+            && env.block.time > self.lock_time
+            && self.close_price.is_none()
+            && env.block.time <= self.reveal_end_time()
+    }
+
+    pub fn winning_amount(&self) -> Uint128 {
+        self.winning_outcome
+            .map(|outcome| self.outcome_amounts[outcome as usize])
+            .unwrap_or_else(Uint128::zero)
+    }
+
+    /// Number of outcomes which have at least one bet
+    pub fn staked_outcomes(&self) -> usize {
+        self.outcome_amounts
+            .iter()
+            .filter(|amount| !amount.is_zero())
+            .count()
+    }
+
     pub fn bettable(&self, env: Env) -> bool {
         !self.is_genesis
             && env.block.time >= self.start_time
@@ -56,37 +230,43 @@ impl Round {
         env.block.time >= self.end_time
             && self.open_price.is_some()
             && self.close_price.is_some()
-            && Some(self.open_price) != Some(self.close_price)
+            && self.staked_outcomes() > 1
+            && !self.winning_amount().is_zero()
     }
 
     pub fn refundable(&self, env: Env, grace_interval: u64) -> bool {
         (env.block.time >= self.end_time
             && self.open_price.is_some()
             && self.close_price.is_some()
-            && Some(self.open_price) == Some(self.close_price))
+            && self.winning_amount().is_zero())
             || (self.close_price.is_none() && env.block.time > self.end_time + grace_interval)
-            || (env.block.time > self.lock_time
-                && (self.up_amount.is_zero() || self.down_amount.is_zero()))
+            || (env.block.time > self.reveal_end_time() && self.staked_outcomes() < 2)
     }
 
     pub fn claimable_amount(&self, env: Env, user_bet: Bet, grace_interval: u64) -> Uint128 {
+        let position = match &user_bet.position {
+            Some(position) => position,
+            // Unrevealed bets are refunded minus the penalty once the round is settled,
+            // or in full if the round expired without settlement
+            None => {
+                return if self.close_price.is_some() {
+                    user_bet.amount
+                        * self
+                            .unrevealed_refund_rate()
+                            .unwrap_or_else(|_| Decimal::zero())
+                } else if self.expired(env, grace_interval) {
+                    user_bet.amount
+                } else {
+                    Uint128::zero()
+                };
+            }
+        };
+
         if self.claimable(env.clone()) {
-            let win_bet_amount = if Some(self.close_price) > Some(self.open_price)
-                && user_bet.position == Position::Up
-            {
-                self.up_amount
-            } else if Some(self.close_price) < Some(self.open_price)
-                && user_bet.position == Position::Down
-            {
-                self.down_amount
+            if self.winning_outcome == Some(position.outcome()) {
+                self.reward_amount * Decimal::from_ratio(user_bet.amount, self.winning_amount())
             } else {
                 Uint128::zero()
-            };
-
-            if win_bet_amount.is_zero() {
-                Uint128::zero()
-            } else {
-                self.reward_amount * Decimal::from_ratio(user_bet.amount, win_bet_amount)
             }
         } else if self.refundable(env, grace_interval) {
             user_bet.amount
@@ -106,13 +286,144 @@ impl Round {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StrikeMarket {
+    pub id: u64,
+    /// Betting pools of the market, the open price is the strike price,
+    /// lock time is the betting deadline and end time is the expiry
+    pub round: Round,
+    /// Seconds after expiry to settle the market, independent of the rounds
+    pub grace_interval: u64,
+    /// Maximum seconds between the price at expiry and the expiry
+    pub max_price_age: u64,
+}
+
+impl StrikeMarket {
+    pub fn new(
+        id: u64,
+        start_time: u64,
+        strike_price: Decimal,
+        bet_deadline: u64,
+        expiry: u64,
+        grace_interval: u64,
+        max_price_age: u64,
+    ) -> Self {
+        StrikeMarket {
+            id,
+            round: Round {
+                start_time,
+                lock_time: bet_deadline,
+                end_time: expiry,
+                open_price: Some(strike_price),
+                close_price: None,
+                open_round_id: None,
+                close_round_id: None,
+                total_amount: Uint128::zero(),
+                reward_amount: Uint128::zero(),
+                bucket_bounds: vec![],
+                outcome_amounts: vec![Uint128::zero(); 2],
+                winning_outcome: None,
+                commit_reveal: None,
+                is_genesis: false,
+            },
+            grace_interval,
+            max_price_age,
+        }
+    }
+
+    pub fn bettable(&self, env: Env) -> bool {
+        env.block.time <= self.round.lock_time && self.round.close_price.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StandingOrder {
+    /// Position in the processing order
+    pub index: u64,
+    pub position: Option<Position>,
+    pub strategy: Option<Strategy>,
+    pub amount_per_round: Uint128,
+    pub remaining_rounds: u64,
+    pub budget: Uint128,
+    /// Epoch of the last placed bet
+    pub last_epoch: Uint128,
+}
+
+impl StandingOrder {
+    /// Position to bet on `round`, `None` skips the round
+    pub fn position(&self, round: &Round, last_winning_outcome: Option<u8>) -> Option<Position> {
+        let outcome = match (&self.position, &self.strategy) {
+            (Some(position), _) => return Some(position.clone()),
+            (None, Some(Strategy::FollowWinner)) => last_winning_outcome?,
+            (None, Some(Strategy::AgainstWinner)) if round.bucket_bounds.is_empty() => {
+                1 - last_winning_outcome.filter(|outcome| *outcome < 2)?
+            }
+            _ => return None,
+        };
+
+        if round.bucket_bounds.is_empty() {
+            match outcome {
+                0 => Some(Position::Up),
+                1 => Some(Position::Down),
+                _ => None,
+            }
+        } else {
+            Some(Position::Bucket(outcome))
+        }
+    }
+
+    pub fn exhausted(&self) -> bool {
+        self.remaining_rounds == 0 || self.budget < self.amount_per_round
+    }
+}
+
+/// Standing orders before `next` have been processed for the betting round `epoch`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StandingOrderCursor {
+    pub epoch: Uint128,
+    pub next: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Bet {
     pub amount: Uint128,
-    pub position: Position,
+    /// Position of the bet, unknown until a committed bet is revealed
+    pub position: Option<Position>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<Binary>,
     pub claimed: bool,
 }
 
+/// Where a bet is stored, indexed in placing order for exports
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BetKey {
+    Round { epoch: Uint128 },
+    StrikeMarket { id: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BetIndex {
+    pub key: BetKey,
+    pub user: CanonicalAddr,
+}
+
+/// Permit revoked by `user`, indexed for exports
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RevokedPermit {
+    pub user: HumanAddr,
+    pub permit_name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ImportStatus {
+    pub sealed: bool,
+    pub pages: u64,
+    pub checksum: Binary,
+    /// Bet asset exported by the old instance, held before sealing
+    pub funds: Uint128,
+}
+
 pub fn store_config<S: Storage>(storage: &mut S, data: &Config) -> StdResult<()> {
     Singleton::new(storage, KEY_CONFIG).save(data)
 }
@@ -140,10 +451,15 @@ pub fn store_bet<S: Storage>(
     user: CanonicalAddr,
     data: &Bet,
 ) -> StdResult<()> {
-    Bucket::new(PREFIX_ROUND, storage).save(
-        &[user.as_slice(), &epoch.u128().to_be_bytes()].concat(),
-        data,
-    )
+    let key = [user.as_slice(), &epoch.u128().to_be_bytes()].concat();
+    if ReadonlyBucket::<S, Bet>::new(PREFIX_ROUND, storage)
+        .may_load(&key)?
+        .is_none()
+    {
+        add_bet_index(storage, BetKey::Round { epoch }, &user)?;
+    }
+
+    Bucket::new(PREFIX_ROUND, storage).save(&key, data)
 }
 
 pub fn read_bet<S: Storage>(storage: &S, epoch: Uint128, user: CanonicalAddr) -> StdResult<Bet> {
@@ -151,14 +467,279 @@ pub fn read_bet<S: Storage>(storage: &S, epoch: Uint128, user: CanonicalAddr) ->
         .load(&[user.as_slice(), &epoch.u128().to_be_bytes()].concat())
 }
 
+pub fn store_strike_market_count<S: Storage>(storage: &mut S, count: u64) -> StdResult<()> {
+    Singleton::new(storage, KEY_STRIKE_MARKET_COUNT).save(&count)
+}
+pub fn read_strike_market_count<S: Storage>(storage: &S) -> StdResult<u64> {
+    Ok(ReadonlySingleton::new(storage, KEY_STRIKE_MARKET_COUNT)
+        .may_load()?
+        .unwrap_or(0))
+}
+
+pub fn store_strike_market<S: Storage>(storage: &mut S, data: &StrikeMarket) -> StdResult<()> {
+    Bucket::new(PREFIX_STRIKE_MARKET, storage).save(&data.id.to_be_bytes(), data)
+}
+pub fn read_strike_market<S: Storage>(storage: &S, id: u64) -> StdResult<StrikeMarket> {
+    ReadonlyBucket::new(PREFIX_STRIKE_MARKET, storage).load(&id.to_be_bytes())
+}
+
+pub fn store_queued_action_count<S: Storage>(storage: &mut S, count: u64) -> StdResult<()> {
+    Singleton::new(storage, KEY_QUEUED_ACTION_COUNT).save(&count)
+}
+pub fn read_queued_action_count<S: Storage>(storage: &S) -> StdResult<u64> {
+    Ok(ReadonlySingleton::new(storage, KEY_QUEUED_ACTION_COUNT)
+        .may_load()?
+        .unwrap_or(0))
+}
+
+pub fn store_queued_action<S: Storage>(storage: &mut S, data: &QueuedAction) -> StdResult<()> {
+    Bucket::new(PREFIX_QUEUED_ACTION, storage).save(&data.id.to_be_bytes(), data)
+}
+pub fn read_queued_action<S: Storage>(storage: &S, id: u64) -> StdResult<Option<QueuedAction>> {
+    ReadonlyBucket::new(PREFIX_QUEUED_ACTION, storage).may_load(&id.to_be_bytes())
+}
+pub fn remove_queued_action<S: Storage>(storage: &mut S, id: u64) {
+    Bucket::<S, QueuedAction>::new(PREFIX_QUEUED_ACTION, storage).remove(&id.to_be_bytes())
+}
+
+pub fn store_strike_bet<S: Storage>(
+    storage: &mut S,
+    id: u64,
+    user: &CanonicalAddr,
+    data: &Bet,
+) -> StdResult<()> {
+    let key = [user.as_slice(), &id.to_be_bytes()].concat();
+    if ReadonlyBucket::<S, Bet>::new(PREFIX_STRIKE_BET, storage)
+        .may_load(&key)?
+        .is_none()
+    {
+        add_bet_index(storage, BetKey::StrikeMarket { id }, user)?;
+    }
+
+    Bucket::new(PREFIX_STRIKE_BET, storage).save(&key, data)
+}
+pub fn read_strike_bet<S: Storage>(storage: &S, id: u64, user: &CanonicalAddr) -> StdResult<Bet> {
+    ReadonlyBucket::new(PREFIX_STRIKE_BET, storage)
+        .load(&[user.as_slice(), &id.to_be_bytes()].concat())
+}
+
+pub fn store_balance<S: Storage>(
+    storage: &mut S,
+    user: &CanonicalAddr,
+    amount: Uint128,
+) -> StdResult<()> {
+    register_account(storage, user)?;
+    Bucket::new(PREFIX_BALANCE, storage).save(user.as_slice(), &amount)
+}
+pub fn read_balance<S: Storage>(storage: &S, user: &CanonicalAddr) -> StdResult<Uint128> {
+    Ok(ReadonlyBucket::new(PREFIX_BALANCE, storage)
+        .may_load(user.as_slice())?
+        .unwrap_or_else(Uint128::zero))
+}
+
+/// Only the hash of the key is kept, so that it can be exported
 pub fn store_viewing_key<S: Storage>(
     storage: &mut S,
     user: &CanonicalAddr,
     key: &ViewingKey,
 ) -> StdResult<()> {
-    Bucket::new(PREFIX_VIEW_KEY, storage).save(&user.as_slice(), key)
+    store_viewing_key_hash(storage, user, &key.to_hashed())
+}
+
+pub fn store_viewing_key_hash<S: Storage>(
+    storage: &mut S,
+    user: &CanonicalAddr,
+    hash: &[u8],
+) -> StdResult<()> {
+    register_account(storage, user)?;
+    Bucket::new(PREFIX_VIEW_KEY, storage).save(&user.as_slice(), &Binary::from(hash))
+}
+
+pub fn read_viewing_key<S: Storage>(
+    storage: &S,
+    user: &CanonicalAddr,
+) -> StdResult<Option<Binary>> {
+    ReadonlyBucket::new(PREFIX_VIEW_KEY, storage).may_load(&user.as_slice())
+}
+
+fn add_bet_index<S: Storage>(storage: &mut S, key: BetKey, user: &CanonicalAddr) -> StdResult<()> {
+    register_account(storage, user)?;
+
+    let count = read_bet_index_count(storage)?;
+    Bucket::new(PREFIX_BET_INDEX, storage).save(
+        &count.to_be_bytes(),
+        &BetIndex {
+            key,
+            user: user.clone(),
+        },
+    )?;
+    Singleton::new(storage, KEY_BET_INDEX_COUNT).save(&(count + 1))
+}
+pub fn read_bet_index_count<S: Storage>(storage: &S) -> StdResult<u64> {
+    Ok(ReadonlySingleton::new(storage, KEY_BET_INDEX_COUNT)
+        .may_load()?
+        .unwrap_or(0))
+}
+pub fn read_bet_index<S: Storage>(storage: &S, index: u64) -> StdResult<BetIndex> {
+    ReadonlyBucket::new(PREFIX_BET_INDEX, storage).load(&index.to_be_bytes())
+}
+
+/// Accounts with a viewing key, balance, bet or standing order, in first seen order
+pub fn register_account<S: Storage>(storage: &mut S, user: &CanonicalAddr) -> StdResult<()> {
+    if ReadonlyBucket::<S, u64>::new(PREFIX_ACCOUNT_INDEX, storage)
+        .may_load(user.as_slice())?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    let count = read_account_count(storage)?;
+    Bucket::new(PREFIX_ACCOUNT_INDEX, storage).save(user.as_slice(), &count)?;
+    Bucket::new(PREFIX_ACCOUNT, storage).save(&count.to_be_bytes(), user)?;
+    Singleton::new(storage, KEY_ACCOUNT_COUNT).save(&(count + 1))
+}
+pub fn read_account_count<S: Storage>(storage: &S) -> StdResult<u64> {
+    Ok(ReadonlySingleton::new(storage, KEY_ACCOUNT_COUNT)
+        .may_load()?
+        .unwrap_or(0))
+}
+pub fn read_account<S: Storage>(storage: &S, index: u64) -> StdResult<CanonicalAddr> {
+    ReadonlyBucket::new(PREFIX_ACCOUNT, storage).load(&index.to_be_bytes())
+}
+
+pub fn store_import_status<S: Storage>(storage: &mut S, data: &ImportStatus) -> StdResult<()> {
+    Singleton::new(storage, KEY_IMPORT_STATUS).save(data)
 }
+pub fn read_import_status<S: Storage>(storage: &S) -> StdResult<ImportStatus> {
+    Ok(ReadonlySingleton::new(storage, KEY_IMPORT_STATUS)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+/// Index the permit in revoking order
+pub fn add_revoked_permit<S: Storage>(storage: &mut S, data: &RevokedPermit) -> StdResult<()> {
+    let count = read_revoked_permit_count(storage)?;
+    Bucket::new(PREFIX_REVOKED_PERMIT_INDEX, storage).save(&count.to_be_bytes(), data)?;
+    Singleton::new(storage, KEY_REVOKED_PERMIT_COUNT).save(&(count + 1))
+}
+pub fn read_revoked_permit<S: Storage>(storage: &S, index: u64) -> StdResult<RevokedPermit> {
+    ReadonlyBucket::new(PREFIX_REVOKED_PERMIT_INDEX, storage).load(&index.to_be_bytes())
+}
+pub fn read_revoked_permit_count<S: Storage>(storage: &S) -> StdResult<u64> {
+    Ok(ReadonlySingleton::new(storage, KEY_REVOKED_PERMIT_COUNT)
+        .may_load()?
+        .unwrap_or(0))
+}
+
+/// Viewing key of this contract at the oracle
+pub fn store_oracle_viewing_key<S: Storage>(storage: &mut S, key: &str) -> StdResult<()> {
+    Singleton::new(storage, KEY_ORACLE_VIEWING_KEY).save(&key.to_string())
+}
+pub fn read_oracle_viewing_key<S: Storage>(storage: &S) -> StdResult<Option<String>> {
+    ReadonlySingleton::new(storage, KEY_ORACLE_VIEWING_KEY).may_load()
+}
+
+pub fn store_standing_order_count<S: Storage>(storage: &mut S, count: u64) -> StdResult<()> {
+    Singleton::new(storage, KEY_STANDING_ORDER_COUNT).save(&count)
+}
+pub fn read_standing_order_count<S: Storage>(storage: &S) -> StdResult<u64> {
+    Ok(ReadonlySingleton::new(storage, KEY_STANDING_ORDER_COUNT)
+        .may_load()?
+        .unwrap_or(0))
+}
+
+pub fn store_standing_order_cursor<S: Storage>(
+    storage: &mut S,
+    data: &StandingOrderCursor,
+) -> StdResult<()> {
+    Singleton::new(storage, KEY_STANDING_ORDER_CURSOR).save(data)
+}
+pub fn read_standing_order_cursor<S: Storage>(storage: &S) -> StdResult<StandingOrderCursor> {
+    Ok(ReadonlySingleton::new(storage, KEY_STANDING_ORDER_CURSOR)
+        .may_load()?
+        .unwrap_or(StandingOrderCursor {
+            epoch: Uint128::zero(),
+            next: 0,
+        }))
+}
+
+pub fn store_standing_order<S: Storage>(
+    storage: &mut S,
+    user: &CanonicalAddr,
+    data: &StandingOrder,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_STANDING_ORDER, storage).save(user.as_slice(), data)
+}
+pub fn read_standing_order<S: Storage>(
+    storage: &S,
+    user: &CanonicalAddr,
+) -> StdResult<StandingOrder> {
+    ReadonlyBucket::new(PREFIX_STANDING_ORDER, storage).load(user.as_slice())
+}
+
+pub fn store_standing_order_owner<S: Storage>(
+    storage: &mut S,
+    index: u64,
+    user: &CanonicalAddr,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_STANDING_ORDER_OWNER, storage).save(&index.to_be_bytes(), user)
+}
+pub fn read_standing_order_owner<S: Storage>(storage: &S, index: u64) -> StdResult<CanonicalAddr> {
+    ReadonlyBucket::new(PREFIX_STANDING_ORDER_OWNER, storage).load(&index.to_be_bytes())
+}
+
+/// Append a new standing order of `user`
+pub fn add_standing_order<S: Storage>(
+    storage: &mut S,
+    user: &CanonicalAddr,
+    mut order: StandingOrder,
+) -> StdResult<()> {
+    register_account(storage, user)?;
+
+    let count = read_standing_order_count(storage)?;
+    order.index = count;
+    store_standing_order(storage, user, &order)?;
+    store_standing_order_owner(storage, count, user)?;
+    store_standing_order_count(storage, count + 1)
+}
+
+/// Swap-remove the standing order of `user`.
+/// An order before the cursor is first swapped with the last processed order,
+/// so that unprocessed orders stay after the cursor.
+pub fn remove_standing_order<S: Storage>(storage: &mut S, user: &CanonicalAddr) -> StdResult<()> {
+    let mut index = read_standing_order(storage, user)?.index;
+
+    let mut cursor = read_standing_order_cursor(storage)?;
+    if index < cursor.next {
+        cursor.next -= 1;
+        swap_standing_orders(storage, index, cursor.next)?;
+        store_standing_order_cursor(storage, &cursor)?;
+        index = cursor.next;
+    }
+
+    let last = read_standing_order_count(storage)? - 1;
+    swap_standing_orders(storage, index, last)?;
+
+    Bucket::<S, CanonicalAddr>::new(PREFIX_STANDING_ORDER_OWNER, storage)
+        .remove(&last.to_be_bytes());
+    Bucket::<S, StandingOrder>::new(PREFIX_STANDING_ORDER, storage).remove(user.as_slice());
+    store_standing_order_count(storage, last)
+}
+
+fn swap_standing_orders<S: Storage>(storage: &mut S, a: u64, b: u64) -> StdResult<()> {
+    if a == b {
+        return Ok(());
+    }
+
+    let owner_a = read_standing_order_owner(storage, a)?;
+    let owner_b = read_standing_order_owner(storage, b)?;
+    let mut order_a = read_standing_order(storage, &owner_a)?;
+    let mut order_b = read_standing_order(storage, &owner_b)?;
 
-pub fn read_viewing_key<S: Storage>(storage: &S, user: &CanonicalAddr) -> StdResult<ViewingKey> {
-    ReadonlyBucket::new(PREFIX_VIEW_KEY, storage).load(&user.as_slice())
+    order_a.index = b;
+    order_b.index = a;
+    store_standing_order(storage, &owner_a, &order_a)?;
+    store_standing_order(storage, &owner_b, &order_b)?;
+    store_standing_order_owner(storage, a, &owner_b)?;
+    store_standing_order_owner(storage, b, &owner_a)
 }
@@ -1,17 +1,32 @@
-use cosmwasm_std::testing::{mock_dependencies, mock_env, MOCK_CONTRACT_ADDR};
-use cosmwasm_std::{from_binary, log, Api, Binary, Decimal, HumanAddr, StdError, Uint128};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    coins, from_binary, log, to_binary, Api, BankMsg, Binary, CosmosMsg, Decimal, Extern,
+    HumanAddr, StdError, StdResult, Uint128, WasmMsg,
+};
+use std::str::FromStr;
 
 use prediction::{
     asset::AssetInfo,
-    prediction::{ConfigResponse, HandleMsg, InitMsg, QueryMsg, State},
+    oracle::HandleMsg as OracleHandleMsg,
+    prediction::{
+        bet_commitment, AdminAction, BalanceResponse, CommitRevealConfig, ConfigResponse,
+        ExportPage, ExportResponse, HandleMsg, ImportStatusResponse, InitMsg, Position, QueryMsg,
+        QueuedAction, StandingOrderProgressResponse, State, Strategy, TimelockConfig,
+    },
     rand::sha_256,
     viewing_key::{ViewingKey, VIEWING_KEY_SIZE},
 };
 
 use crate::{
     contract::{handle, init, query},
-    state::{read_config, read_viewing_key, Round},
-    tests::test_utils::{init_prediction, start_genesis_round},
+    migration::chain_checksum,
+    state::{
+        read_bet, read_config, read_round, read_viewing_key, store_round, store_state, Bet, Round,
+        StrikeMarket,
+    },
+    tests::test_utils::{init_msg, init_prediction, start_genesis_round},
 };
 
 #[test]
@@ -30,6 +45,12 @@ fn test_init_failed_if_fee_rate_is_greater_than_100() {
         interval: 18000,
         grace_interval: 18000,
         prng_seed: Binary::from("lolz fun yay".as_bytes()),
+        bucket_bounds: None,
+        commit_reveal: None,
+        timelock: None,
+        quote_currency: None,
+        auto_settle: None,
+        import: None,
     };
 
     let env = mock_env("addr", &[]);
@@ -54,6 +75,12 @@ fn test_init_failed_if_grace_interval_is_greater_than_interval() {
         interval: 18000,
         grace_interval: 18001,
         prng_seed: Binary::from("lolz fun yay".as_bytes()),
+        bucket_bounds: None,
+        commit_reveal: None,
+        timelock: None,
+        quote_currency: None,
+        auto_settle: None,
+        import: None,
     };
 
     let env = mock_env("addr", &[]);
@@ -78,6 +105,12 @@ fn test_init() {
         interval: 18000,
         grace_interval: 18000,
         prng_seed: Binary::from("lolz fun yay".as_bytes()),
+        bucket_bounds: None,
+        commit_reveal: None,
+        timelock: None,
+        quote_currency: None,
+        auto_settle: None,
+        import: None,
     };
 
     let env = mock_env("addr", &[]);
@@ -100,6 +133,11 @@ fn test_init() {
             fee_rate: Decimal::percent(5),
             interval: 18000,
             grace_interval: 18000,
+            bucket_bounds: vec![],
+            commit_reveal: None,
+            timelock: None,
+            quote_currency: None,
+            auto_settle: false,
         },
         config
     );
@@ -137,6 +175,7 @@ fn test_update_config_failed_if_unauthorized() {
         fee_rate: Some(Decimal::percent(3)),
         interval: Some(20000),
         grace_interval: Some(20000),
+        quote_currency: None,
     };
 
     let env = mock_env("addr", &[]);
@@ -163,6 +202,7 @@ fn test_update_config_failed_if_fee_rate_is_greater_than_100() {
         fee_rate: Some(Decimal::percent(101)),
         interval: Some(20000),
         grace_interval: Some(20000),
+        quote_currency: None,
     };
 
     let env = mock_env("owner_addr", &[]);
@@ -186,6 +226,7 @@ fn test_update_config_failed_if_grace_interval_is_greater_than_interval() {
         fee_rate: Some(Decimal::percent(4)),
         interval: Some(20000),
         grace_interval: Some(21000),
+        quote_currency: None,
     };
 
     let env = mock_env("owner_addr", &[]);
@@ -209,6 +250,7 @@ fn test_update_config() {
         fee_rate: Some(Decimal::percent(4)),
         interval: Some(20000),
         grace_interval: Some(19000),
+        quote_currency: None,
     };
 
     let env = mock_env("owner_addr", &[]);
@@ -232,6 +274,11 @@ fn test_update_config() {
             fee_rate: Decimal::percent(4),
             interval: 20000,
             grace_interval: 19000,
+            bucket_bounds: vec![],
+            commit_reveal: None,
+            timelock: None,
+            quote_currency: None,
+            auto_settle: false,
         },
         config
     );
@@ -288,10 +335,14 @@ fn test_start_genesis_round() {
             end_time: env.block.time + 18000,
             open_price: None,
             close_price: None,
+            open_round_id: None,
+            close_round_id: None,
             total_amount: Uint128::zero(),
             reward_amount: Uint128::zero(),
-            up_amount: Uint128::zero(),
-            down_amount: Uint128::zero(),
+            bucket_bounds: vec![],
+            outcome_amounts: vec![Uint128::zero(), Uint128::zero()],
+            winning_outcome: None,
+            commit_reveal: None,
             is_genesis: true,
         },
         genesis_round
@@ -306,10 +357,14 @@ fn test_start_genesis_round() {
             end_time: env.block.time + 36000,
             open_price: None,
             close_price: None,
+            open_round_id: None,
+            close_round_id: None,
             total_amount: Uint128::zero(),
             reward_amount: Uint128::zero(),
-            up_amount: Uint128::zero(),
-            down_amount: Uint128::zero(),
+            bucket_bounds: vec![],
+            outcome_amounts: vec![Uint128::zero(), Uint128::zero()],
+            winning_outcome: None,
+            commit_reveal: None,
             is_genesis: false,
         },
         genesis_round
@@ -417,12 +472,11 @@ fn test_create_viewing_key() {
             .canonical_address(&HumanAddr("user".to_string()))
             .unwrap(),
     )
+    .unwrap()
     .unwrap();
 
-    assert_eq!(
-        res.log,
-        vec![log("action", "create_viewing_key"), log("key", key),]
-    );
+    assert_eq!(res.log[0], log("action", "create_viewing_key"));
+    assert!(ViewingKey(res.log[1].value.clone()).check_viewing_key(key.as_slice()));
 }
 
 #[test]
@@ -448,11 +502,1326 @@ fn test_set_viewing_key() {
             .canonical_address(&HumanAddr("user".to_string()))
             .unwrap(),
     )
+    .unwrap()
     .unwrap();
 
     assert_eq!(
         res.log,
         vec![log("action", "set_viewing_key"), log("success", true),]
     );
-    assert!(actual_vk.check_viewing_key(&key.to_hashed()));
+
+    assert!(actual_vk.check_viewing_key(key.as_slice()));
+}
+
+#[test]
+fn test_set_buckets_failed_if_unauthorized() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+
+    let msg = HandleMsg::SetBuckets {
+        bounds: vec![Decimal::percent(99), Decimal::percent(101)],
+    };
+
+    let env = mock_env("owner_addr", &[]);
+
+    let res = handle(&mut deps, env, msg);
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+}
+
+#[test]
+fn test_set_buckets_failed_if_bounds_are_not_increasing() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+
+    let msg = HandleMsg::SetBuckets {
+        bounds: vec![Decimal::percent(101), Decimal::percent(99)],
+    };
+
+    let env = mock_env("operator_addr", &[]);
+
+    let res = handle(&mut deps, env, msg).unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid bucket bounds"), res);
+}
+
+#[test]
+fn test_bet_bucket() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+
+    let msg = HandleMsg::SetBuckets {
+        bounds: vec![Decimal::percent(99), Decimal::percent(101)],
+    };
+
+    let env = mock_env("operator_addr", &[]);
+
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![log("action", "set_buckets"), log("bucket_count", 3),]
+    );
+
+    start_genesis_round(&mut deps);
+
+    let env = mock_env("user", &coins(100, "sscrt"));
+
+    let res = handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::Bet {
+            position: Position::Up,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid position"), res);
+
+    let res = handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::Bet {
+            position: Position::Bucket(3),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid position"), res);
+
+    let res = handle(
+        &mut deps,
+        env,
+        HandleMsg::Bet {
+            position: Position::Bucket(1),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "bet"),
+            log("amount", 100),
+            log("position", "bucket_1"),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Round { epoch: Uint128(2) }).unwrap();
+    let round: Round = from_binary(&res).unwrap();
+    assert_eq!(round.total_amount, Uint128(100));
+    assert_eq!(
+        round.outcome_amounts,
+        vec![Uint128::zero(), Uint128(100), Uint128::zero()]
+    );
+}
+
+#[test]
+fn test_bucket_round_settlement() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+
+    let mut config = read_config(&deps.storage).unwrap();
+    config.bucket_bounds = vec![Decimal::percent(99), Decimal::percent(101)];
+
+    let mut round = Round::new(1000, &config, false);
+    round.open_price = Some(Decimal::from_str("200").unwrap());
+    round.total_amount = Uint128(1000);
+    round.outcome_amounts = vec![Uint128(100), Uint128(300), Uint128(600)];
+
+    // 203 is above 101% of the open price
+    round.settle(Decimal::from_str("203").unwrap()).unwrap();
+    assert_eq!(round.winning_outcome, Some(2));
+    round.reward_amount = Uint128(900);
+
+    let mut env = mock_env("user", &[]);
+    env.block.time = round.end_time;
+
+    assert_eq!(
+        round.claimable_amount(
+            env.clone(),
+            Bet {
+                amount: Uint128(300),
+                position: Some(Position::Bucket(2)),
+                commitment: None,
+                claimed: false,
+            },
+            100,
+        ),
+        Uint128(450)
+    );
+    assert_eq!(
+        round.claimable_amount(
+            env.clone(),
+            Bet {
+                amount: Uint128(300),
+                position: Some(Position::Bucket(1)),
+                commitment: None,
+                claimed: false,
+            },
+            100,
+        ),
+        Uint128::zero()
+    );
+
+    // Nobody bet on the winning bucket, so everyone is refunded
+    round.settle(Decimal::from_str("199").unwrap()).unwrap();
+    assert_eq!(round.winning_outcome, Some(1));
+    round.outcome_amounts = vec![Uint128(100), Uint128::zero(), Uint128(900)];
+    assert!(!round.claimable(env.clone()));
+    assert_eq!(
+        round.claimable_amount(
+            env,
+            Bet {
+                amount: Uint128(100),
+                position: Some(Position::Bucket(0)),
+                commitment: None,
+                claimed: false,
+            },
+            100,
+        ),
+        Uint128(100)
+    );
+}
+
+#[test]
+fn test_create_strike_market_failed_if_unauthorized() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+
+    let env = mock_env("owner_addr", &[]);
+
+    let msg = HandleMsg::CreateStrikeMarket {
+        strike_price: Decimal::from_str("3.5").unwrap(),
+        bet_deadline: env.block.time + 100,
+        expiry: env.block.time + 200,
+        grace_interval: 600,
+        max_price_age: 60,
+    };
+
+    let res = handle(&mut deps, env, msg);
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+}
+
+#[test]
+fn test_create_strike_market_failed_if_expiry_is_before_bet_deadline() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+
+    let env = mock_env("operator_addr", &[]);
+
+    let msg = HandleMsg::CreateStrikeMarket {
+        strike_price: Decimal::from_str("3.5").unwrap(),
+        bet_deadline: env.block.time + 200,
+        expiry: env.block.time + 100,
+        grace_interval: 600,
+        max_price_age: 60,
+    };
+
+    let res = handle(&mut deps, env, msg).unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid market period"), res);
+}
+
+#[test]
+fn test_strike_market_bet_and_refund() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+    start_genesis_round(&mut deps);
+
+    let env = mock_env("operator_addr", &[]);
+    let bet_deadline = env.block.time + 100;
+    let expiry = env.block.time + 200;
+
+    let msg = HandleMsg::CreateStrikeMarket {
+        strike_price: Decimal::from_str("3.5").unwrap(),
+        bet_deadline,
+        expiry,
+        grace_interval: 600,
+        max_price_age: 60,
+    };
+
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "create_strike_market"),
+            log("id", 1),
+            log("strike_price", "3.5"),
+            log("bet_deadline", bet_deadline),
+            log("expiry", expiry),
+        ]
+    );
+
+    let msg = HandleMsg::BetStrikeMarket {
+        id: 1,
+        position: Position::Up,
+    };
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &coins(100, "sscrt")),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "bet_strike_market"),
+            log("id", 1),
+            log("amount", 100),
+            log("position", "up"),
+        ]
+    );
+
+    let res = handle(&mut deps, mock_env("alice", &coins(100, "sscrt")), msg).unwrap_err();
+    assert_eq!(StdError::generic_err("Already bet"), res);
+
+    let mut env = mock_env("bob", &coins(50, "sscrt"));
+    env.block.time = bet_deadline + 1;
+    let res = handle(
+        &mut deps,
+        env,
+        HandleMsg::BetStrikeMarket {
+            id: 1,
+            position: Position::Down,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Cannot bet"), res);
+
+    let res = query(
+        &deps,
+        QueryMsg::StrikeMarkets {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let markets: Vec<StrikeMarket> = from_binary(&res).unwrap();
+    assert_eq!(markets.len(), 1);
+    assert_eq!(markets[0].round.open_price, Decimal::from_str("3.5").ok());
+    assert_eq!(markets[0].round.total_amount, Uint128(100));
+    assert_eq!(
+        markets[0].round.outcome_amounts,
+        vec![Uint128(100), Uint128::zero()]
+    );
+
+    // One-sided market is refunded once betting is closed
+    let mut env = mock_env("alice", &[]);
+    env.block.time = bet_deadline + 1;
+    let res = handle(&mut deps, env, HandleMsg::ClaimStrikeMarket { id: 1 }).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
+            to_address: HumanAddr::from("alice"),
+            amount: coins(100, "sscrt"),
+        })]
+    );
+}
+
+#[test]
+fn test_update_commit_reveal_failed_if_reveal_interval_is_greater_than_interval() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+
+    let msg = HandleMsg::UpdateCommitReveal {
+        commit_reveal: Some(CommitRevealConfig {
+            reveal_interval: 18001,
+            unrevealed_penalty_rate: Decimal::percent(10),
+        }),
+    };
+
+    let env = mock_env("owner_addr", &[]);
+
+    let res = handle(&mut deps, env, msg).unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid reveal interval"), res);
+}
+
+#[test]
+fn test_commit_and_reveal_bet() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+
+    let msg = HandleMsg::UpdateCommitReveal {
+        commit_reveal: Some(CommitRevealConfig {
+            reveal_interval: 600,
+            unrevealed_penalty_rate: Decimal::percent(10),
+        }),
+    };
+
+    let res = handle(&mut deps, mock_env("owner_addr", &[]), msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![log("action", "update_commit_reveal"), log("enabled", true),]
+    );
+
+    start_genesis_round(&mut deps);
+
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &coins(100, "sscrt")),
+        HandleMsg::Bet {
+            position: Position::Up,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Commit required"), res);
+
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &coins(100, "sscrt")),
+        HandleMsg::CommitBet {
+            commitment: bet_commitment(
+                Uint128(2),
+                &HumanAddr::from("alice"),
+                &Position::Up,
+                "alice salt",
+            ),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.log,
+        vec![log("action", "commit_bet"), log("amount", 100),]
+    );
+
+    handle(
+        &mut deps,
+        mock_env("bob", &coins(300, "sscrt")),
+        HandleMsg::CommitBet {
+            commitment: bet_commitment(
+                Uint128(2),
+                &HumanAddr::from("bob"),
+                &Position::Down,
+                "bob salt",
+            ),
+        },
+    )
+    .unwrap();
+
+    // Carol copies the commitment of alice
+    let alice_commitment = read_bet(
+        &deps.storage,
+        Uint128(2),
+        deps.api
+            .canonical_address(&HumanAddr::from("alice"))
+            .unwrap(),
+    )
+    .unwrap()
+    .commitment
+    .unwrap();
+    handle(
+        &mut deps,
+        mock_env("carol", &coins(100, "sscrt")),
+        HandleMsg::CommitBet {
+            commitment: alice_commitment,
+        },
+    )
+    .unwrap();
+
+    let reveal_msg = HandleMsg::RevealBet {
+        epoch: Uint128(2),
+        position: Position::Up,
+        salt: "alice salt".to_string(),
+    };
+
+    // Positions can not be revealed while betting is open
+    let res = handle(&mut deps, mock_env("alice", &[]), reveal_msg.clone()).unwrap_err();
+    assert_eq!(StdError::generic_err("Cannot reveal"), res);
+
+    // The window opens at lock, before the round is executed
+    let round = read_round(&deps.storage, Uint128(2)).unwrap();
+    let mut env = mock_env("alice", &[]);
+    env.block.time = round.lock_time + 1;
+
+    // The copied commitment is bound to alice
+    let mut carol_env = env.clone();
+    carol_env.message.sender = HumanAddr::from("carol");
+    let res = handle(&mut deps, carol_env, reveal_msg.clone()).unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid reveal"), res);
+
+    let res = handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::RevealBet {
+            epoch: Uint128(2),
+            position: Position::Down,
+            salt: "alice salt".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid reveal"), res);
+
+    let res = handle(&mut deps, env, reveal_msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "reveal_bet"),
+            log("epoch", 2),
+            log("amount", 100),
+            log("position", "up"),
+        ]
+    );
+
+    let mut round = read_round(&deps.storage, Uint128(2)).unwrap();
+    round.open_price = Some(Decimal::from_str("3").unwrap());
+    assert_eq!(round.total_amount, Uint128(500));
+    assert_eq!(round.outcome_amounts, vec![Uint128(100), Uint128::zero()]);
+
+    // Bob and carol never revealed, so the revealed pool is one-sided and refunded.
+    // 10% of their bets goes to the fee.
+    round.settle(Decimal::from_str("4").unwrap()).unwrap();
+    assert_eq!(round.distribute(Decimal::percent(5)).unwrap(), Uint128(40));
+
+    let mut env = mock_env("bob", &[]);
+    env.block.time = round.end_time;
+    let bob_bet = read_bet(
+        &deps.storage,
+        Uint128(2),
+        deps.api.canonical_address(&HumanAddr::from("bob")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bob_bet.position, None);
+    assert_eq!(
+        round.claimable_amount(env.clone(), bob_bet, 18000),
+        Uint128(270)
+    );
+
+    let alice_bet = read_bet(
+        &deps.storage,
+        Uint128(2),
+        deps.api
+            .canonical_address(&HumanAddr::from("alice"))
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(round.claimable_amount(env, alice_bet, 18000), Uint128(100));
+}
+
+#[test]
+fn test_set_standing_order_failed_if_budget_is_less_than_amount_per_round() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+
+    let msg = HandleMsg::SetStandingOrder {
+        position: Some(Position::Up),
+        strategy: None,
+        amount_per_round: Uint128(100),
+        max_rounds: 3,
+    };
+
+    let res = handle(&mut deps, mock_env("alice", &coins(50, "sscrt")), msg).unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid standing order"), res);
+
+    let msg = HandleMsg::SetStandingOrder {
+        position: Some(Position::Up),
+        strategy: Some(Strategy::FollowWinner),
+        amount_per_round: Uint128(100),
+        max_rounds: 3,
+    };
+
+    let res = handle(&mut deps, mock_env("alice", &coins(300, "sscrt")), msg).unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid standing order"), res);
+}
+
+#[test]
+fn test_process_and_cancel_standing_orders() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+    start_genesis_round(&mut deps);
+
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &coins(250, "sscrt")),
+        HandleMsg::SetStandingOrder {
+            position: Some(Position::Up),
+            strategy: None,
+            amount_per_round: Uint128(100),
+            max_rounds: 2,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "set_standing_order"),
+            log("amount_per_round", 100),
+            log("max_rounds", 2),
+            log("budget", 250),
+        ]
+    );
+
+    // No round is settled yet, so the strategy order waits
+    handle(
+        &mut deps,
+        mock_env("bob", &coins(100, "sscrt")),
+        HandleMsg::SetStandingOrder {
+            position: None,
+            strategy: Some(Strategy::FollowWinner),
+            amount_per_round: Uint128(100),
+            max_rounds: 1,
+        },
+    )
+    .unwrap();
+
+    let res = handle(
+        &mut deps,
+        mock_env("keeper", &[]),
+        HandleMsg::ProcessStandingOrders { limit: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "process_standing_orders"),
+            log("epoch", 2),
+            log("standing_orders", 1),
+        ]
+    );
+
+    let round = read_round(&deps.storage, Uint128(2)).unwrap();
+    assert_eq!(round.total_amount, Uint128(100));
+    assert_eq!(round.outcome_amounts, vec![Uint128(100), Uint128::zero()]);
+
+    let alice_bet = read_bet(
+        &deps.storage,
+        Uint128(2),
+        deps.api
+            .canonical_address(&HumanAddr::from("alice"))
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(alice_bet.amount, Uint128(100));
+    assert_eq!(alice_bet.position, Some(Position::Up));
+
+    // Processing again does not bet twice on the same round
+    let res = handle(
+        &mut deps,
+        mock_env("keeper", &[]),
+        HandleMsg::ProcessStandingOrders { limit: None },
+    )
+    .unwrap();
+    assert_eq!(res.log[2], log("standing_orders", 0));
+
+    let res = query(&deps, QueryMsg::StandingOrderProgress {}).unwrap();
+    let progress: StandingOrderProgressResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        progress,
+        StandingOrderProgressResponse {
+            epoch: Uint128(2),
+            processed: 2,
+            total: 2,
+        }
+    );
+
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &[]),
+        HandleMsg::CancelStandingOrder {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
+            to_address: HumanAddr::from("alice"),
+            amount: coins(150, "sscrt"),
+        })]
+    );
+
+    let res = query(&deps, QueryMsg::StandingOrderProgress {}).unwrap();
+    let progress: StandingOrderProgressResponse = from_binary(&res).unwrap();
+    assert_eq!(progress.processed, 1);
+    assert_eq!(progress.total, 1);
+
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &[]),
+        HandleMsg::CancelStandingOrder {},
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("No standing order"), res);
+}
+
+#[test]
+fn test_bet_and_claim_with_balance() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+    start_genesis_round(&mut deps);
+
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &coins(300, "sscrt")),
+        HandleMsg::Deposit {},
+    )
+    .unwrap();
+    assert_eq!(res.log, vec![log("action", "deposit"), log("amount", 300),]);
+
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &[]),
+        HandleMsg::BetFromBalance {
+            position: Position::Up,
+            amount: Uint128(100),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "bet"),
+            log("amount", 100),
+            log("position", "up"),
+        ]
+    );
+
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &[]),
+        HandleMsg::WithdrawBalance {
+            amount: Some(Uint128(50)),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
+            to_address: HumanAddr::from("alice"),
+            amount: coins(50, "sscrt"),
+        })]
+    );
+
+    // Nobody bet down, so the bet is refunded once the round is settled
+    let mut round = read_round(&deps.storage, Uint128(2)).unwrap();
+    round.open_price = Some(Decimal::from_str("3").unwrap());
+    round.settle(Decimal::from_str("4").unwrap()).unwrap();
+    store_round(&mut deps.storage, Uint128(2), &round).unwrap();
+
+    let mut env = mock_env("alice", &[]);
+    env.block.time = round.end_time;
+    let res = handle(
+        &mut deps,
+        env,
+        HandleMsg::ClaimToBalance { epoch: Uint128(2) },
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "claim_to_balance"),
+            log("epoch", 2),
+            log("amount", 100),
+            log("claim_amount", 100),
+        ]
+    );
+
+    handle(
+        &mut deps,
+        mock_env("alice", &[]),
+        HandleMsg::SetViewingKey {
+            key: "alice_key".to_string(),
+            padding: None,
+        },
+    )
+    .unwrap();
+
+    let res = query(
+        &deps,
+        QueryMsg::Balance {
+            user: HumanAddr::from("alice"),
+            key: "alice_key".to_string(),
+        },
+    )
+    .unwrap();
+    let balance: BalanceResponse = from_binary(&res).unwrap();
+    assert_eq!(balance.amount, Uint128(250));
+
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &[]),
+        HandleMsg::WithdrawBalance {
+            amount: Some(Uint128(251)),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Insufficient balance"), res);
+}
+
+#[test]
+fn test_timelocked_admin_actions() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+
+    let mut env = mock_env("owner_addr", &[]);
+    let now = env.block.time;
+
+    // Enabling the timelock is not delayed
+    let res = handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::UpdateTimelock {
+            timelock: Some(TimelockConfig {
+                min_delay: 100,
+                withdraw_threshold: Uint128(1000),
+            }),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.log,
+        vec![log("action", "update_timelock"), log("enabled", true)]
+    );
+
+    let update_msg = |fee_rate| HandleMsg::UpdateConfig {
+        owner_addr: None,
+        operator_addr: None,
+        treasury_addr: None,
+        oracle_addr: None,
+        oracle_code_hash: None,
+        fee_rate: Some(fee_rate),
+        interval: None,
+        grace_interval: None,
+        quote_currency: None,
+    };
+
+    let res = handle(&mut deps, env.clone(), update_msg(Decimal::percent(101))).unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid fee rate"), res);
+
+    let res = handle(&mut deps, env.clone(), update_msg(Decimal::percent(4))).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "queue_action"),
+            log("id", 1),
+            log("eta", now + 100),
+        ]
+    );
+    assert_eq!(
+        read_config(&deps.storage).unwrap().fee_rate,
+        Decimal::percent(5)
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::PendingActions {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pending: Vec<QueuedAction> = from_binary(&res).unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].eta, now + 100);
+
+    let res = handle(&mut deps, env.clone(), HandleMsg::ExecuteQueued { id: 1 }).unwrap_err();
+    assert_eq!(StdError::generic_err("Timelock not expired"), res);
+
+    env.block.time = now + 100;
+    let res = handle(&mut deps, env.clone(), HandleMsg::ExecuteQueued { id: 1 }).unwrap();
+    assert_eq!(
+        res.log,
+        vec![log("action", "update_config"), log("queued_id", 1)]
+    );
+    assert_eq!(
+        read_config(&deps.storage).unwrap().fee_rate,
+        Decimal::percent(4)
+    );
+
+    // Withdrawals up to the threshold are not delayed
+    let mut state = State {
+        epoch: Uint128::zero(),
+        total_fee: Uint128(1000),
+        paused: true,
+    };
+    store_state(&mut deps.storage, &state).unwrap();
+    let res = handle(&mut deps, env.clone(), HandleMsg::Withdraw {}).unwrap();
+    assert_eq!(
+        res.log,
+        vec![log("action", "withdraw"), log("amount", 1000)]
+    );
+
+    state.total_fee = Uint128(1001);
+    store_state(&mut deps.storage, &state).unwrap();
+    let res = handle(&mut deps, env.clone(), HandleMsg::Withdraw {}).unwrap();
+    assert_eq!(res.log[0], log("action", "queue_action"));
+
+    let res = query(
+        &deps,
+        QueryMsg::PendingActions {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pending: Vec<QueuedAction> = from_binary(&res).unwrap();
+    assert_eq!(
+        pending,
+        vec![QueuedAction {
+            id: 2,
+            action: AdminAction::Withdraw {
+                amount: Uint128(1001),
+            },
+            eta: now + 200,
+        }]
+    );
+
+    let res = handle(
+        &mut deps,
+        mock_env("addr", &[]),
+        HandleMsg::CancelQueued { id: 2 },
+    );
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    handle(&mut deps, env.clone(), HandleMsg::CancelQueued { id: 2 }).unwrap();
+    env.block.time = now + 200;
+    let res = handle(&mut deps, env, HandleMsg::ExecuteQueued { id: 2 }).unwrap_err();
+    assert_eq!(StdError::generic_err("No queued action"), res);
+}
+
+#[test]
+fn test_timelocked_round_settings() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_prediction(&mut deps);
+
+    let mut owner_env = mock_env("owner_addr", &[]);
+    let mut operator_env = mock_env("operator_addr", &[]);
+    let now = owner_env.block.time;
+    handle(
+        &mut deps,
+        owner_env.clone(),
+        HandleMsg::UpdateTimelock {
+            timelock: Some(TimelockConfig {
+                min_delay: 100,
+                withdraw_threshold: Uint128(1000),
+            }),
+        },
+    )
+    .unwrap();
+
+    let res = handle(
+        &mut deps,
+        operator_env.clone(),
+        HandleMsg::SetBuckets {
+            bounds: vec![Decimal::percent(99)],
+        },
+    )
+    .unwrap();
+    assert_eq!(res.log[0], log("action", "queue_action"));
+
+    let res = handle(
+        &mut deps,
+        owner_env.clone(),
+        HandleMsg::UpdateCommitReveal {
+            commit_reveal: Some(CommitRevealConfig {
+                reveal_interval: 600,
+                unrevealed_penalty_rate: Decimal::percent(10),
+            }),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.log[0], log("action", "queue_action"));
+
+    let res = handle(
+        &mut deps,
+        owner_env.clone(),
+        HandleMsg::UpdateAutoSettle { auto_settle: true },
+    )
+    .unwrap();
+    assert_eq!(res.log[0], log("action", "queue_action"));
+
+    let res = handle(
+        &mut deps,
+        owner_env.clone(),
+        HandleMsg::SetOracleViewingKey {
+            key: "oracle_key".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.log[0], log("action", "queue_action"));
+    assert_eq!(res.messages, vec![]);
+
+    // Invalid changes are rejected when queued
+    let res = handle(
+        &mut deps,
+        owner_env.clone(),
+        HandleMsg::UpdateConfig {
+            owner_addr: None,
+            operator_addr: None,
+            treasury_addr: None,
+            oracle_addr: Some(HumanAddr::from("new_oracle")),
+            oracle_code_hash: None,
+            fee_rate: None,
+            interval: None,
+            grace_interval: None,
+            quote_currency: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Oracle code hash required"), res);
+
+    let res = query(
+        &deps,
+        QueryMsg::PendingActions {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pending: Vec<QueuedAction> = from_binary(&res).unwrap();
+    assert_eq!(pending.len(), 4);
+    assert_eq!(
+        pending[3].action,
+        AdminAction::SetOracleViewingKey { key: String::new() }
+    );
+
+    let config = read_config(&deps.storage).unwrap();
+    assert!(config.bucket_bounds.is_empty());
+    assert!(config.commit_reveal.is_none());
+    assert!(!config.auto_settle);
+
+    // Queued bucket changes are executed by the operator
+    owner_env.block.time = now + 100;
+    operator_env.block.time = now + 100;
+    let res = handle(
+        &mut deps,
+        owner_env.clone(),
+        HandleMsg::ExecuteQueued { id: 1 },
+    );
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+    let res = handle(&mut deps, operator_env, HandleMsg::ExecuteQueued { id: 1 }).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "set_buckets"),
+            log("bucket_count", 2),
+            log("queued_id", 1),
+        ]
+    );
+
+    for id in 2..=3 {
+        handle(
+            &mut deps,
+            owner_env.clone(),
+            HandleMsg::ExecuteQueued { id },
+        )
+        .unwrap();
+    }
+    let res = handle(&mut deps, owner_env, HandleMsg::ExecuteQueued { id: 4 }).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("oracle_addr"),
+            callback_code_hash: "oracle_code_hash".to_string(),
+            msg: to_binary(&OracleHandleMsg::SetViewingKey {
+                key: "oracle_key".to_string(),
+                padding: None,
+            })
+            .unwrap(),
+            send: vec![],
+        })]
+    );
+
+    let config = read_config(&deps.storage).unwrap();
+    assert_eq!(config.bucket_bounds, vec![Decimal::percent(99)]);
+    assert!(config.commit_reveal.is_some());
+    assert!(config.auto_settle);
+}
+
+#[test]
+fn test_export_and_import() {
+    let mut deps = mock_dependencies(20, &coins(300, "sscrt"));
+
+    init_prediction(&mut deps);
+    start_genesis_round(&mut deps);
+
+    handle(
+        &mut deps,
+        mock_env("alice", &coins(300, "sscrt")),
+        HandleMsg::Deposit {},
+    )
+    .unwrap();
+    handle(
+        &mut deps,
+        mock_env("alice", &[]),
+        HandleMsg::BetFromBalance {
+            position: Position::Up,
+            amount: Uint128(100),
+        },
+    )
+    .unwrap();
+    for user in &["alice", "owner_addr"] {
+        handle(
+            &mut deps,
+            mock_env(*user, &[]),
+            HandleMsg::SetViewingKey {
+                key: format!("{}_key", user),
+                padding: None,
+            },
+        )
+        .unwrap();
+    }
+    handle(
+        &mut deps,
+        mock_env("alice", &[]),
+        HandleMsg::RevokePermit {
+            permit_name: "alice_permit".to_string(),
+            padding: None,
+        },
+    )
+    .unwrap();
+    handle(
+        &mut deps,
+        mock_env("owner_addr", &[]),
+        HandleMsg::SetOracleViewingKey {
+            key: "oracle_key".to_string(),
+        },
+    )
+    .unwrap();
+
+    // Funds are handed over by a paused instance only
+    let transfer_funds = HandleMsg::TransferFunds {
+        recipient: HumanAddr::from("new_instance"),
+        amount: Uint128(300),
+    };
+    let res = handle(
+        &mut deps,
+        mock_env("owner_addr", &[]),
+        transfer_funds.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Not paused"), res);
+
+    handle(&mut deps, mock_env("owner_addr", &[]), HandleMsg::Pause {}).unwrap();
+
+    let res = handle(&mut deps, mock_env("alice", &[]), transfer_funds.clone()).unwrap_err();
+    assert_eq!(StdError::unauthorized(), res);
+
+    let res = handle(&mut deps, mock_env("owner_addr", &[]), transfer_funds).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
+            to_address: HumanAddr::from("new_instance"),
+            amount: coins(300, "sscrt"),
+        })]
+    );
+
+    fn export(
+        deps: &Extern<MockStorage, MockApi, MockQuerier>,
+        page: ExportPage,
+        key: &str,
+    ) -> StdResult<ExportResponse> {
+        query(
+            deps,
+            QueryMsg::Export {
+                key: key.to_string(),
+                page,
+            },
+        )
+        .map(|res| from_binary::<ExportResponse>(&res).unwrap())
+    }
+    fn export_all(deps: &Extern<MockStorage, MockApi, MockQuerier>) -> Vec<ExportResponse> {
+        vec![
+            ExportPage::Config {},
+            ExportPage::State {},
+            ExportPage::Rounds {
+                start_after: None,
+                limit: None,
+            },
+            ExportPage::Bets {
+                start_after: None,
+                limit: None,
+            },
+            ExportPage::Accounts {
+                start_after: None,
+                limit: None,
+            },
+            ExportPage::RevokedPermits {
+                start_after: None,
+                limit: None,
+            },
+        ]
+        .into_iter()
+        .map(|page| export(deps, page, "owner_addr_key").unwrap())
+        .collect()
+    }
+
+    let res = export(&deps, ExportPage::Config {}, "alice_key").unwrap_err();
+    assert_eq!(StdError::generic_err("Invalid viewing key"), res);
+
+    let pages = export_all(&deps);
+    assert_eq!(pages[2].last, Some(2));
+    assert_eq!(pages[3].last, Some(0));
+    assert_eq!(pages[4].last, Some(1));
+    assert_eq!(pages[5].last, Some(0));
+
+    // Instances not waiting for an import are sealed
+    let res = handle(
+        &mut deps,
+        mock_env("owner_addr", &[]),
+        HandleMsg::Import {
+            data: pages[0].data.clone(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Import sealed"), res);
+
+    let mut new_deps = mock_dependencies(20, &[]);
+    let msg = InitMsg {
+        import: Some(true),
+        ..init_msg()
+    };
+    init(&mut new_deps, mock_env("owner_addr", &[]), msg).unwrap();
+
+    let res = handle(
+        &mut new_deps,
+        mock_env("alice", &coins(100, "sscrt")),
+        HandleMsg::Deposit {},
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Import not sealed"), res);
+
+    let mut checksum = Binary::default();
+    for page in pages.iter() {
+        let res = handle(
+            &mut new_deps,
+            mock_env("owner_addr", &[]),
+            HandleMsg::Import {
+                data: page.data.clone(),
+            },
+        )
+        .unwrap();
+        checksum = chain_checksum(&checksum, page.checksum.as_slice());
+
+        // The oracle viewing key is set again for the new address
+        if page == &pages[1] {
+            assert_eq!(
+                res.messages,
+                vec![CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: HumanAddr::from("oracle_addr"),
+                    callback_code_hash: "oracle_code_hash".to_string(),
+                    msg: to_binary(&OracleHandleMsg::SetViewingKey {
+                        key: "oracle_key".to_string(),
+                        padding: None,
+                    })
+                    .unwrap(),
+                    send: vec![],
+                })]
+            );
+        }
+    }
+
+    let res = handle(
+        &mut new_deps,
+        mock_env("owner_addr", &[]),
+        HandleMsg::SealImport {
+            checksum: pages[0].checksum.clone(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Checksum mismatch"), res);
+
+    // The exported balance must be transferred before sealing
+    let res = handle(
+        &mut new_deps,
+        mock_env("owner_addr", &[]),
+        HandleMsg::SealImport {
+            checksum: checksum.clone(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        StdError::generic_err("Insufficient funds for imported liabilities"),
+        res
+    );
+
+    new_deps
+        .querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(300, "sscrt"));
+
+    handle(
+        &mut new_deps,
+        mock_env("owner_addr", &[]),
+        HandleMsg::SealImport {
+            checksum: checksum.clone(),
+        },
+    )
+    .unwrap();
+
+    let res = query(&new_deps, QueryMsg::ImportStatus {}).unwrap();
+    let status: ImportStatusResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        status,
+        ImportStatusResponse {
+            sealed: true,
+            pages: 6,
+            checksum,
+            funds: Uint128(300),
+        }
+    );
+
+    let res = handle(
+        &mut new_deps,
+        mock_env("owner_addr", &[]),
+        HandleMsg::Import {
+            data: pages[0].data.clone(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(StdError::generic_err("Import sealed"), res);
+
+    assert_eq!(
+        query(&new_deps, QueryMsg::State {}).unwrap(),
+        query(&deps, QueryMsg::State {}).unwrap()
+    );
+    // Funds, oracle viewing key and revoked permits are carried over
+    let new_pages = export_all(&new_deps);
+    assert_eq!(new_pages[1], pages[1]);
+    assert_eq!(new_pages[5], pages[5]);
+
+    // Bets and balances are readable with the old viewing key
+    let res = query(
+        &new_deps,
+        QueryMsg::Balance {
+            user: HumanAddr::from("alice"),
+            key: "alice_key".to_string(),
+        },
+    )
+    .unwrap();
+    let balance: BalanceResponse = from_binary(&res).unwrap();
+    assert_eq!(balance.amount, Uint128(200));
+
+    let res = query(
+        &new_deps,
+        QueryMsg::Bet {
+            epoch: Uint128(2),
+            user: HumanAddr::from("alice"),
+            key: "alice_key".to_string(),
+        },
+    )
+    .unwrap();
+    let bet: Bet = from_binary(&res).unwrap();
+    assert_eq!(bet.amount, Uint128(100));
 }
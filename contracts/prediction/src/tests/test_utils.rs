@@ -9,7 +9,13 @@ use prediction::{
 use crate::contract::{handle, init};
 
 pub fn init_prediction(deps: &mut Extern<MockStorage, MockApi, MockQuerier>) {
-    let msg = InitMsg {
+    let env = mock_env("owner_addr", &[]);
+
+    let _res = init(deps, env, init_msg()).unwrap();
+}
+
+pub fn init_msg() -> InitMsg {
+    InitMsg {
         operator_addr: HumanAddr::from("operator_addr"),
         treasury_addr: HumanAddr::from("treasury_addr"),
         bet_asset: AssetInfo::NativeToken {
@@ -21,11 +27,13 @@ pub fn init_prediction(deps: &mut Extern<MockStorage, MockApi, MockQuerier>) {
         interval: 18000,
         grace_interval: 18000,
         prng_seed: Binary::from("lolz fun yay".as_bytes()),
-    };
-
-    let env = mock_env("owner_addr", &[]);
-
-    let _res = init(deps, env, msg).unwrap();
+        bucket_bounds: None,
+        commit_reveal: None,
+        timelock: None,
+        quote_currency: None,
+        auto_settle: None,
+        import: None,
+    }
 }
 
 pub fn start_genesis_round(deps: &mut Extern<MockStorage, MockApi, MockQuerier>) {
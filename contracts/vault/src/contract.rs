@@ -0,0 +1,560 @@
+use cosmwasm_std::{
+    from_binary, log, to_binary, Api, Binary, Coin, CosmosMsg, Decimal, Env, Extern,
+    HandleResponse, HandleResult, HumanAddr, InitResponse, Querier, QueryRequest, StdError,
+    StdResult, Storage, Uint128, WasmMsg, WasmQuery,
+};
+use secret_toolkit::snip20::{balance_query, HandleMsg as Snip20HandleMsg};
+
+use crate::state::{read_config, read_state, store_config, store_state, Config, State};
+use crate::strategy::{choose_position, ClaimStatus, RoundInfo};
+use prediction::{
+    asset::{Asset, AssetInfoRaw},
+    prediction::{
+        ConfigResponse as PredictionConfigResponse, Cw20HookMsg as PredictionCw20HookMsg,
+        HandleMsg as PredictionHandleMsg, Position, QueryMsg as PredictionQueryMsg,
+        State as PredictionState,
+    },
+    utils::ContractInfo,
+    vault::{
+        ConfigResponse, Cw20HookMsg, HandleMsg, InitMsg, NavResponse, OpenBet, QueryMsg,
+        StateResponse, Strategy,
+    },
+};
+
+/// Padding block size of SNIP-20 queries
+const BLOCK_SIZE: usize = 256;
+
+pub fn init<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: InitMsg,
+) -> StdResult<InitResponse> {
+    if msg.bet_rate > Decimal::one() {
+        return Err(StdError::generic_err("Invalid bet rate"));
+    }
+
+    let config = Config {
+        contract_addr: deps.api.canonical_address(&env.contract.address)?,
+        owner_addr: deps.api.canonical_address(&env.message.sender)?,
+        operator_addr: deps.api.canonical_address(&msg.operator_addr)?,
+        prediction_addr: deps.api.canonical_address(&msg.prediction.address)?,
+        prediction_code_hash: msg.prediction.code_hash,
+        bet_asset: msg.bet_asset.to_raw(deps)?,
+        share_token_addr: deps.api.canonical_address(&msg.share_token.address)?,
+        share_token_code_hash: msg.share_token.code_hash.clone(),
+        strategy: msg.strategy,
+        bet_rate: msg.bet_rate,
+    };
+
+    store_config(&mut deps.storage, &config)?;
+
+    store_state(
+        &mut deps.storage,
+        &State {
+            total_shares: Uint128::zero(),
+            open_bets: vec![],
+            last_bet_epoch: Uint128::zero(),
+        },
+    )?;
+
+    // Withdrawals are sent with share tokens
+    let mut messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: msg.share_token.address,
+        callback_code_hash: msg.share_token.code_hash,
+        msg: to_binary(&Snip20HandleMsg::RegisterReceive {
+            code_hash: env.contract_code_hash.clone(),
+            padding: None,
+        })?,
+        send: vec![],
+    })];
+
+    if let AssetInfoRaw::Token {
+        contract_addr,
+        token_code_hash,
+        viewing_key,
+    } = config.bet_asset
+    {
+        let contract_addr = deps.api.human_address(&contract_addr)?;
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.clone(),
+            callback_code_hash: token_code_hash.clone(),
+            msg: to_binary(&Snip20HandleMsg::RegisterReceive {
+                code_hash: env.contract_code_hash,
+                padding: None,
+            })?,
+            send: vec![],
+        }));
+        // The viewing key of the bet asset is used to read the vault balance
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            callback_code_hash: token_code_hash,
+            msg: to_binary(&Snip20HandleMsg::SetViewingKey {
+                key: viewing_key,
+                padding: None,
+            })?,
+            send: vec![],
+        }));
+    }
+
+    Ok(InitResponse {
+        messages,
+        log: vec![],
+    })
+}
+
+pub fn handle<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: HandleMsg,
+) -> HandleResult {
+    match msg {
+        HandleMsg::Receive { from, msg, amount } => receive_cw20(deps, env, from, amount, msg),
+        HandleMsg::Deposit {} => {
+            let config = read_config(&deps.storage)?;
+            let amount = match config.bet_asset {
+                AssetInfoRaw::NativeToken { denom } => env
+                    .message
+                    .sent_funds
+                    .iter()
+                    .find(|c| c.denom == denom)
+                    .map(|c| c.amount)
+                    .unwrap_or_else(Uint128::zero),
+                AssetInfoRaw::Token { .. } => return Err(StdError::generic_err("invalid asset")),
+            };
+            deposit(deps, env.message.sender, amount)
+        }
+        HandleMsg::ExecuteStrategy {} => execute_strategy(deps, env),
+        HandleMsg::UpdateConfig {
+            owner_addr,
+            operator_addr,
+            strategy,
+            bet_rate,
+        } => update_config(deps, env, owner_addr, operator_addr, strategy, bet_rate),
+    }
+}
+
+fn receive_cw20<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    from: HumanAddr,
+    amount: Uint128,
+    msg: Option<Binary>,
+) -> HandleResult {
+    let bin_msg = msg.ok_or_else(|| StdError::generic_err("data should be given"))?;
+    let config = read_config(&deps.storage)?;
+    let sender = deps.api.canonical_address(&env.message.sender)?;
+
+    match from_binary(&bin_msg)? {
+        Cw20HookMsg::Deposit {} => match config.bet_asset {
+            AssetInfoRaw::Token { contract_addr, .. } if contract_addr == sender => {
+                deposit(deps, from, amount)
+            }
+            _ => Err(StdError::generic_err("invalid asset")),
+        },
+        Cw20HookMsg::Withdraw {} => {
+            if config.share_token_addr != sender {
+                return Err(StdError::generic_err("invalid asset"));
+            }
+            withdraw(deps, env, from, amount)
+        }
+    }
+}
+
+fn deposit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    user: HumanAddr,
+    amount: Uint128,
+) -> HandleResult {
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Amount is zero"));
+    }
+
+    let config = read_config(&deps.storage)?;
+    let mut state = read_state(&deps.storage)?;
+
+    // The deposit is already part of the vault balance
+    let nav = (query_nav(deps, &config, &state)?.nav - amount)?;
+    if state.total_shares.is_zero() {
+        // A balance left without shares backs dead shares instead of the first depositor
+        state.total_shares = nav;
+    }
+    let shares = if state.total_shares.is_zero() {
+        amount
+    } else if nav.is_zero() {
+        return Err(StdError::generic_err("Zero net asset value"));
+    } else {
+        amount.multiply_ratio(state.total_shares, nav)
+    };
+
+    if shares.is_zero() {
+        return Err(StdError::generic_err("Amount is too small"));
+    }
+
+    state.total_shares = state.total_shares + shares;
+    store_state(&mut deps.storage, &state)?;
+
+    Ok(HandleResponse {
+        messages: vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.share_token_addr)?,
+            callback_code_hash: config.share_token_code_hash,
+            msg: to_binary(&Snip20HandleMsg::Mint {
+                recipient: user,
+                amount: shares,
+                memo: None,
+                padding: None,
+            })?,
+            send: vec![],
+        })],
+        log: vec![
+            log("action", "deposit"),
+            log("amount", amount),
+            log("shares", shares),
+        ],
+        data: None,
+    })
+}
+
+fn withdraw<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    user: HumanAddr,
+    shares: Uint128,
+) -> HandleResult {
+    let config = read_config(&deps.storage)?;
+    let mut state = read_state(&deps.storage)?;
+
+    if shares.is_zero() || shares > state.total_shares {
+        return Err(StdError::generic_err("Invalid share amount"));
+    }
+
+    let nav = query_nav(deps, &config, &state)?;
+    let amount = nav.nav.multiply_ratio(shares, state.total_shares);
+
+    // Open bets are paid out once their rounds are claimed
+    if amount > nav.idle {
+        return Err(StdError::generic_err("Insufficient liquidity"));
+    }
+
+    state.total_shares = (state.total_shares - shares)?;
+    store_state(&mut deps.storage, &state)?;
+
+    let mut messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: deps.api.human_address(&config.share_token_addr)?,
+        callback_code_hash: config.share_token_code_hash.clone(),
+        msg: to_binary(&Snip20HandleMsg::Burn {
+            amount: shares,
+            memo: None,
+            padding: None,
+        })?,
+        send: vec![],
+    })];
+
+    if !amount.is_zero() {
+        let return_asset = Asset {
+            amount,
+            info: config.bet_asset.to_normal(deps)?,
+        };
+        messages.push(return_asset.into_msg(env.contract.address, user)?);
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![
+            log("action", "withdraw"),
+            log("shares", shares),
+            log("amount", amount),
+        ],
+        data: None,
+    })
+}
+
+fn execute_strategy<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let config = read_config(&deps.storage)?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.operator_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut state = read_state(&deps.storage)?;
+    let nav = query_nav(deps, &config, &state)?;
+    let prediction_config: PredictionConfigResponse =
+        query_prediction(deps, &config, &PredictionQueryMsg::Config {})?;
+    let prediction_state: PredictionState =
+        query_prediction(deps, &config, &PredictionQueryMsg::State {})?;
+
+    let prediction_addr = deps.api.human_address(&config.prediction_addr)?;
+    let mut messages = vec![];
+
+    // Claim finished rounds, lost bets are dropped from the open bets
+    let mut open_bets = vec![];
+    let mut claimed = 0u64;
+    for bet in state.open_bets.into_iter() {
+        let round: RoundInfo = query_prediction(
+            deps,
+            &config,
+            &PredictionQueryMsg::Round { epoch: bet.epoch },
+        )?;
+
+        match round.claim_status(
+            &bet.position,
+            env.block.time,
+            prediction_config.grace_interval,
+        ) {
+            ClaimStatus::Claimable => {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: prediction_addr.clone(),
+                    callback_code_hash: config.prediction_code_hash.clone(),
+                    msg: to_binary(&PredictionHandleMsg::Claim { epoch: bet.epoch })?,
+                    send: vec![],
+                }));
+                claimed += 1;
+            }
+            ClaimStatus::Lost => {}
+            ClaimStatus::Pending => open_bets.push(bet),
+        }
+    }
+    state.open_bets = open_bets;
+
+    let epoch = prediction_state.epoch;
+    let mut bet_amount = Uint128::zero();
+    let mut bet_position = None;
+    if !prediction_state.paused && epoch > state.last_bet_epoch {
+        let betting_round: RoundInfo =
+            query_prediction(deps, &config, &PredictionQueryMsg::Round { epoch })?;
+        let last_round: Option<RoundInfo> = (epoch - Uint128(2))
+            .and_then(|last_epoch| {
+                query_prediction(
+                    deps,
+                    &config,
+                    &PredictionQueryMsg::Round { epoch: last_epoch },
+                )
+            })
+            .ok();
+
+        let amount = std::cmp::min(nav.nav * config.bet_rate, nav.idle);
+        let position = choose_position(&config.strategy, &betting_round, last_round.as_ref())
+            .filter(|_| betting_round.bettable(env.block.time) && !amount.is_zero());
+
+        if let Some(position) = position {
+            messages.push(bet_msg(
+                deps,
+                &config,
+                prediction_addr,
+                position.clone(),
+                amount,
+            )?);
+            state.open_bets.push(OpenBet {
+                epoch,
+                amount,
+                position: position.clone(),
+            });
+            state.last_bet_epoch = epoch;
+            bet_amount = amount;
+            bet_position = Some(position);
+        }
+    }
+
+    store_state(&mut deps.storage, &state)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![
+            log("action", "execute_strategy"),
+            log("claimed", claimed),
+            log("bet_amount", bet_amount),
+            log(
+                "position",
+                bet_position
+                    .map(|position| position.to_string())
+                    .unwrap_or_default(),
+            ),
+        ],
+        data: None,
+    })
+}
+
+fn bet_msg<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: &Config,
+    prediction_addr: HumanAddr,
+    position: Position,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    match &config.bet_asset {
+        AssetInfoRaw::NativeToken { denom } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: prediction_addr,
+            callback_code_hash: config.prediction_code_hash.clone(),
+            msg: to_binary(&PredictionHandleMsg::Bet { position })?,
+            send: vec![Coin {
+                denom: denom.clone(),
+                amount,
+            }],
+        })),
+        AssetInfoRaw::Token {
+            contract_addr,
+            token_code_hash,
+            ..
+        } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(contract_addr)?,
+            callback_code_hash: token_code_hash.clone(),
+            msg: to_binary(&Snip20HandleMsg::Send {
+                recipient: prediction_addr,
+                amount,
+                msg: Some(to_binary(&PredictionCw20HookMsg::Bet { position })?),
+                memo: None,
+                padding: None,
+            })?,
+            send: vec![],
+        })),
+    }
+}
+
+fn update_config<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    owner_addr: Option<HumanAddr>,
+    operator_addr: Option<HumanAddr>,
+    strategy: Option<Strategy>,
+    bet_rate: Option<Decimal>,
+) -> HandleResult {
+    let mut config = read_config(&deps.storage)?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    if let Some(owner_addr) = owner_addr {
+        config.owner_addr = deps.api.canonical_address(&owner_addr)?;
+    }
+
+    if let Some(operator_addr) = operator_addr {
+        config.operator_addr = deps.api.canonical_address(&operator_addr)?;
+    }
+
+    if let Some(strategy) = strategy {
+        config.strategy = strategy;
+    }
+
+    if let Some(bet_rate) = bet_rate {
+        if bet_rate > Decimal::one() {
+            return Err(StdError::generic_err("Invalid bet rate"));
+        }
+        config.bet_rate = bet_rate;
+    }
+
+    store_config(&mut deps.storage, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "update_config")],
+        data: None,
+    })
+}
+
+pub fn query<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    msg: QueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::State {} => to_binary(&query_state(deps)?),
+        QueryMsg::Nav {} => {
+            let config = read_config(&deps.storage)?;
+            let state = read_state(&deps.storage)?;
+            to_binary(&query_nav(deps, &config, &state)?)
+        }
+    }
+}
+
+fn query_config<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ConfigResponse> {
+    let config = read_config(&deps.storage)?;
+
+    Ok(ConfigResponse {
+        owner_addr: deps.api.human_address(&config.owner_addr)?,
+        operator_addr: deps.api.human_address(&config.operator_addr)?,
+        prediction: ContractInfo {
+            address: deps.api.human_address(&config.prediction_addr)?,
+            code_hash: config.prediction_code_hash,
+        },
+        bet_asset: config.bet_asset.to_normal(deps)?,
+        share_token: ContractInfo {
+            address: deps.api.human_address(&config.share_token_addr)?,
+            code_hash: config.share_token_code_hash,
+        },
+        strategy: config.strategy,
+        bet_rate: config.bet_rate,
+    })
+}
+
+fn query_state<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<StateResponse> {
+    let state = read_state(&deps.storage)?;
+
+    Ok(StateResponse {
+        total_shares: state.total_shares,
+        open_bets: state.open_bets,
+    })
+}
+
+/// Open bets are valued at cost until their rounds are settled
+fn query_nav<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: &Config,
+    state: &State,
+) -> StdResult<NavResponse> {
+    let contract_addr = deps.api.human_address(&config.contract_addr)?;
+    let idle = match &config.bet_asset {
+        AssetInfoRaw::NativeToken { denom } => {
+            deps.querier.query_balance(contract_addr, denom)?.amount
+        }
+        AssetInfoRaw::Token {
+            contract_addr: token_addr,
+            token_code_hash,
+            viewing_key,
+        } => {
+            balance_query(
+                &deps.querier,
+                contract_addr,
+                viewing_key.clone(),
+                BLOCK_SIZE,
+                token_code_hash.clone(),
+                deps.api.human_address(token_addr)?,
+            )?
+            .amount
+        }
+    };
+
+    let mut open_value = Uint128::zero();
+    for bet in state.open_bets.iter() {
+        let round: RoundInfo = query_prediction(
+            deps,
+            config,
+            &PredictionQueryMsg::Round { epoch: bet.epoch },
+        )?;
+        open_value = open_value + round.bet_value(bet);
+    }
+
+    Ok(NavResponse {
+        nav: idle + open_value,
+        idle,
+        total_shares: state.total_shares,
+    })
+}
+
+fn query_prediction<S: Storage, A: Api, Q: Querier, T: serde::de::DeserializeOwned>(
+    deps: &Extern<S, A, Q>,
+    config: &Config,
+    msg: &PredictionQueryMsg,
+) -> StdResult<T> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: deps.api.human_address(&config.prediction_addr)?,
+        callback_code_hash: config.prediction_code_hash.clone(),
+        msg: to_binary(msg)?,
+    }))
+}
@@ -0,0 +1,9 @@
+pub mod contract;
+mod state;
+mod strategy;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(target_arch = "wasm32")]
+cosmwasm_std::create_entry_points!(contract);
@@ -0,0 +1,49 @@
+use cosmwasm_std::{CanonicalAddr, Decimal, StdResult, Storage, Uint128};
+use cosmwasm_storage::{ReadonlySingleton, Singleton};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use prediction::{
+    asset::AssetInfoRaw,
+    vault::{OpenBet, Strategy},
+};
+
+static KEY_CONFIG: &[u8] = b"config";
+static KEY_STATE: &[u8] = b"state";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub contract_addr: CanonicalAddr,
+    pub owner_addr: CanonicalAddr,
+    pub operator_addr: CanonicalAddr,
+    pub prediction_addr: CanonicalAddr,
+    pub prediction_code_hash: String,
+    pub bet_asset: AssetInfoRaw,
+    pub share_token_addr: CanonicalAddr,
+    pub share_token_code_hash: String,
+    pub strategy: Strategy,
+    pub bet_rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    /// Minted shares plus the dead shares seeded by a deposit into an unowned balance
+    pub total_shares: Uint128,
+    pub open_bets: Vec<OpenBet>,
+    /// Last epoch the vault bet on
+    pub last_bet_epoch: Uint128,
+}
+
+pub fn store_config<S: Storage>(storage: &mut S, data: &Config) -> StdResult<()> {
+    Singleton::new(storage, KEY_CONFIG).save(data)
+}
+pub fn read_config<S: Storage>(storage: &S) -> StdResult<Config> {
+    ReadonlySingleton::new(storage, KEY_CONFIG).load()
+}
+
+pub fn store_state<S: Storage>(storage: &mut S, data: &State) -> StdResult<()> {
+    Singleton::new(storage, KEY_STATE).save(data)
+}
+pub fn read_state<S: Storage>(storage: &S) -> StdResult<State> {
+    ReadonlySingleton::new(storage, KEY_STATE).load()
+}
@@ -0,0 +1,125 @@
+use cosmwasm_std::{Decimal, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use prediction::{
+    prediction::Position,
+    vault::{OpenBet, Strategy},
+};
+
+/// Fields of a prediction round used by the vault
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoundInfo {
+    pub start_time: u64,
+    pub lock_time: u64,
+    pub end_time: u64,
+    pub open_price: Option<Decimal>,
+    pub close_price: Option<Decimal>,
+    pub bucket_bounds: Vec<Decimal>,
+    pub outcome_amounts: Vec<Uint128>,
+    pub reward_amount: Uint128,
+    pub winning_outcome: Option<u8>,
+    pub is_genesis: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClaimStatus {
+    Pending,
+    /// Reward or refund can be claimed
+    Claimable,
+    Lost,
+}
+
+impl RoundInfo {
+    pub fn bettable(&self, time: u64) -> bool {
+        !self.is_genesis
+            && time >= self.start_time
+            && time <= self.lock_time
+            && self.open_price.is_none()
+    }
+
+    fn staked_outcomes(&self) -> usize {
+        self.outcome_amounts
+            .iter()
+            .filter(|amount| !amount.is_zero())
+            .count()
+    }
+
+    fn winning_amount(&self) -> Uint128 {
+        self.winning_outcome
+            .and_then(|outcome| self.outcome_amounts.get(outcome as usize).copied())
+            .unwrap_or_else(Uint128::zero)
+    }
+
+    /// Mirrors `claimable` and `refundable` of the prediction round for a revealed bet
+    pub fn claim_status(&self, position: &Position, time: u64, grace_interval: u64) -> ClaimStatus {
+        let staked_outcomes = self.staked_outcomes();
+        let winning_amount = self.winning_amount();
+
+        if self.close_price.is_some() && time >= self.end_time {
+            // Refunded if nobody won, otherwise only the winners are paid
+            if staked_outcomes < 2
+                || winning_amount.is_zero()
+                || self.winning_outcome == Some(position.outcome())
+            {
+                ClaimStatus::Claimable
+            } else {
+                ClaimStatus::Lost
+            }
+        } else if (self.close_price.is_none() && time > self.end_time + grace_interval)
+            || (time > self.lock_time && staked_outcomes < 2)
+        {
+            ClaimStatus::Claimable
+        } else {
+            ClaimStatus::Pending
+        }
+    }
+
+    /// Amount the bet pays out once the round is settled, its cost before
+    pub fn bet_value(&self, bet: &OpenBet) -> Uint128 {
+        if self.close_price.is_none() {
+            return bet.amount;
+        }
+
+        let winning_amount = self.winning_amount();
+        if self.staked_outcomes() < 2 || winning_amount.is_zero() {
+            bet.amount
+        } else if self.winning_outcome == Some(bet.position.outcome()) {
+            self.reward_amount * Decimal::from_ratio(bet.amount, winning_amount)
+        } else {
+            Uint128::zero()
+        }
+    }
+}
+
+/// Position to bet on the betting round, `None` skips the round
+pub fn choose_position(
+    strategy: &Strategy,
+    betting_round: &RoundInfo,
+    last_round: Option<&RoundInfo>,
+) -> Option<Position> {
+    if !betting_round.bucket_bounds.is_empty() {
+        return None;
+    }
+
+    let outcome = match strategy {
+        Strategy::Minority => {
+            let up = betting_round.outcome_amounts[0];
+            let down = betting_round.outcome_amounts[1];
+            if up < down {
+                0
+            } else if down < up {
+                1
+            } else {
+                return None;
+            }
+        }
+        Strategy::Momentum => last_round?.winning_outcome?,
+    };
+
+    match outcome {
+        0 => Some(Position::Up),
+        1 => Some(Position::Down),
+        _ => None,
+    }
+}
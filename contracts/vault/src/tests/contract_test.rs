@@ -0,0 +1,282 @@
+use cosmwasm_std::testing::{mock_env, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    coins, from_binary, log, to_binary, BankMsg, CosmosMsg, Decimal, HumanAddr, StdError, Uint128,
+    WasmMsg,
+};
+use secret_toolkit::snip20::HandleMsg as Snip20HandleMsg;
+use std::str::FromStr;
+
+use prediction::{
+    prediction::{HandleMsg as PredictionHandleMsg, Position},
+    vault::{Cw20HookMsg, HandleMsg, NavResponse, OpenBet, QueryMsg, StateResponse, Strategy},
+};
+
+use crate::{
+    contract::{handle, query},
+    tests::{
+        mock_querier::mock_dependencies,
+        test_utils::{init_vault, mock_round},
+    },
+};
+
+#[test]
+fn test_execute_strategy_failed_if_unauthorized() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    init_vault(&mut deps, Strategy::Minority);
+
+    let res = handle(
+        &mut deps,
+        mock_env("addr", &[]),
+        HandleMsg::ExecuteStrategy {},
+    );
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+}
+
+#[test]
+fn test_deposit_and_withdraw() {
+    let mut deps = mock_dependencies(20, &coins(1000, "sscrt"));
+
+    init_vault(&mut deps, Strategy::Minority);
+
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &coins(1000, "sscrt")),
+        HandleMsg::Deposit {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("share_token"),
+            callback_code_hash: "share_token_code_hash".to_string(),
+            msg: to_binary(&Snip20HandleMsg::Mint {
+                recipient: HumanAddr::from("alice"),
+                amount: Uint128(1000),
+                memo: None,
+                padding: None,
+            })
+            .unwrap(),
+            send: vec![],
+        })]
+    );
+
+    // Vault made 500 from bets, bob deposits 500 at the higher share price
+    deps.querier.update_balance(&coins(2000, "sscrt"));
+    let res = handle(
+        &mut deps,
+        mock_env("bob", &coins(500, "sscrt")),
+        HandleMsg::Deposit {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "deposit"),
+            log("amount", 500),
+            log("shares", 333),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Nav {}).unwrap();
+    let nav: NavResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        nav,
+        NavResponse {
+            nav: Uint128(2000),
+            idle: Uint128(2000),
+            total_shares: Uint128(1333),
+        }
+    );
+
+    let withdraw_msg = HandleMsg::Receive {
+        from: HumanAddr::from("alice"),
+        msg: Some(to_binary(&Cw20HookMsg::Withdraw {}).unwrap()),
+        amount: Uint128(1000),
+    };
+
+    let res = handle(&mut deps, mock_env("addr", &[]), withdraw_msg.clone()).unwrap_err();
+    assert_eq!(StdError::generic_err("invalid asset"), res);
+
+    let res = handle(&mut deps, mock_env("share_token", &[]), withdraw_msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("share_token"),
+                callback_code_hash: "share_token_code_hash".to_string(),
+                msg: to_binary(&Snip20HandleMsg::Burn {
+                    amount: Uint128(1000),
+                    memo: None,
+                    padding: None,
+                })
+                .unwrap(),
+                send: vec![],
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                to_address: HumanAddr::from("alice"),
+                amount: coins(1500, "sscrt"),
+            }),
+        ]
+    );
+}
+
+#[test]
+fn test_deposit_into_balance_without_shares() {
+    let mut deps = mock_dependencies(20, &coins(1500, "sscrt"));
+
+    init_vault(&mut deps, Strategy::Minority);
+
+    // The 500 left in the vault back dead shares instead of going to alice
+    let res = handle(
+        &mut deps,
+        mock_env("alice", &coins(1000, "sscrt")),
+        HandleMsg::Deposit {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "deposit"),
+            log("amount", 1000),
+            log("shares", 1000),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Nav {}).unwrap();
+    let nav: NavResponse = from_binary(&res).unwrap();
+    assert_eq!(nav.total_shares, Uint128(1500));
+}
+
+#[test]
+fn test_nav_values_open_bets_by_settled_round() {
+    let mut deps = mock_dependencies(20, &coins(1000, "sscrt"));
+
+    init_vault(&mut deps, Strategy::Minority);
+
+    handle(
+        &mut deps,
+        mock_env("alice", &coins(1000, "sscrt")),
+        HandleMsg::Deposit {},
+    )
+    .unwrap();
+
+    let env = mock_env("operator_addr", &[]);
+    let now = env.block.time;
+    deps.querier
+        .rounds
+        .insert(2, mock_round(now - 10, 300, 100));
+    handle(&mut deps, env, HandleMsg::ExecuteStrategy {}).unwrap();
+    deps.querier.update_balance(&coins(900, "sscrt"));
+
+    let nav = |deps: &_| -> Uint128 {
+        let res = query(deps, QueryMsg::Nav {}).unwrap();
+        from_binary::<NavResponse>(&res).unwrap().nav
+    };
+    assert_eq!(nav(&deps), Uint128(1000));
+
+    // The vault bet down with 100 of the 200 down pool
+    let mut settled_round = mock_round(now - 700, 300, 200);
+    settled_round.open_price = Some(Decimal::from_str("3").unwrap());
+    settled_round.close_price = Some(Decimal::from_str("4").unwrap());
+    settled_round.winning_outcome = Some(0);
+    settled_round.reward_amount = Uint128(475);
+    deps.querier.rounds.insert(2, settled_round.clone());
+    assert_eq!(nav(&deps), Uint128(900));
+
+    settled_round.close_price = Some(Decimal::from_str("2").unwrap());
+    settled_round.winning_outcome = Some(1);
+    deps.querier.rounds.insert(2, settled_round);
+    assert_eq!(nav(&deps), Uint128(1137));
+}
+
+#[test]
+fn test_execute_strategy_bets_minority_and_claims() {
+    let mut deps = mock_dependencies(20, &coins(1000, "sscrt"));
+
+    init_vault(&mut deps, Strategy::Minority);
+
+    handle(
+        &mut deps,
+        mock_env("alice", &coins(1000, "sscrt")),
+        HandleMsg::Deposit {},
+    )
+    .unwrap();
+
+    let env = mock_env("operator_addr", &[]);
+    let now = env.block.time;
+    deps.querier
+        .rounds
+        .insert(2, mock_round(now - 10, 300, 100));
+
+    let res = handle(&mut deps, env.clone(), HandleMsg::ExecuteStrategy {}).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("prediction"),
+            callback_code_hash: "prediction_code_hash".to_string(),
+            msg: to_binary(&PredictionHandleMsg::Bet {
+                position: Position::Down,
+            })
+            .unwrap(),
+            send: coins(100, "sscrt"),
+        })]
+    );
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "execute_strategy"),
+            log("claimed", 0),
+            log("bet_amount", 100),
+            log("position", "down"),
+        ]
+    );
+
+    // Only one bet per round
+    let res = handle(&mut deps, env.clone(), HandleMsg::ExecuteStrategy {}).unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    let res = query(&deps, QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        state.open_bets,
+        vec![OpenBet {
+            epoch: Uint128(2),
+            amount: Uint128(100),
+            position: Position::Down,
+        }]
+    );
+
+    // Down won the round, the next round has no minority yet
+    let mut finished_round = mock_round(now - 700, 300, 200);
+    finished_round.open_price = Some(Decimal::from_str("3").unwrap());
+    finished_round.close_price = Some(Decimal::from_str("2").unwrap());
+    finished_round.winning_outcome = Some(1);
+    deps.querier.rounds.insert(2, finished_round);
+    deps.querier.rounds.insert(3, mock_round(now - 10, 0, 0));
+    deps.querier.state.epoch = Uint128(3);
+
+    let res = handle(&mut deps, env, HandleMsg::ExecuteStrategy {}).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("prediction"),
+            callback_code_hash: "prediction_code_hash".to_string(),
+            msg: to_binary(&PredictionHandleMsg::Claim { epoch: Uint128(2) }).unwrap(),
+            send: vec![],
+        })]
+    );
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "execute_strategy"),
+            log("claimed", 1),
+            log("bet_amount", 0),
+            log("position", ""),
+        ]
+    );
+}
@@ -0,0 +1,101 @@
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Binary, Coin, Decimal, Empty, Extern, HumanAddr, Querier,
+    QuerierResult, QueryRequest, StdError, StdResult, SystemError, Uint128, WasmQuery,
+};
+use std::collections::HashMap;
+
+use prediction::{
+    asset::AssetInfo,
+    prediction::{ConfigResponse, QueryMsg, State},
+};
+
+use crate::strategy::RoundInfo;
+
+/// Answers the prediction queries of the vault from fixed rounds
+pub struct WasmMockQuerier {
+    base: MockQuerier,
+    pub state: State,
+    pub rounds: HashMap<u128, RoundInfo>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+
+        match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { msg, .. }) => Ok(self.query_prediction(msg)),
+            _ => self.base.raw_query(bin_request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier) -> Self {
+        WasmMockQuerier {
+            base,
+            state: State {
+                epoch: Uint128(2),
+                total_fee: Uint128::zero(),
+                paused: false,
+            },
+            rounds: HashMap::new(),
+        }
+    }
+
+    pub fn update_balance(&mut self, balance: &[Coin]) {
+        self.base
+            .update_balance(HumanAddr::from(MOCK_CONTRACT_ADDR), balance.to_vec());
+    }
+
+    fn query_prediction(&self, msg: &Binary) -> StdResult<Binary> {
+        match from_binary(msg)? {
+            QueryMsg::Config {} => to_binary(&ConfigResponse {
+                contract_addr: HumanAddr::from("prediction"),
+                owner_addr: HumanAddr::from("owner"),
+                operator_addr: HumanAddr::from("operator"),
+                treasury_addr: HumanAddr::from("treasury"),
+                bet_asset: AssetInfo::NativeToken {
+                    denom: "sscrt".to_string(),
+                },
+                oracle_addr: HumanAddr::from("oracle"),
+                oracle_code_hash: "oracle_code_hash".to_string(),
+                fee_rate: Decimal::percent(5),
+                interval: 300,
+                grace_interval: 100,
+                bucket_bounds: vec![],
+                commit_reveal: None,
+                timelock: None,
+                quote_currency: None,
+                auto_settle: false,
+            }),
+            QueryMsg::State {} => to_binary(&self.state),
+            QueryMsg::Round { epoch } => match self.rounds.get(&epoch.u128()) {
+                Some(round) => to_binary(round),
+                None => Err(StdError::not_found("round")),
+            },
+            _ => panic!("unsupported query"),
+        }
+    }
+}
+
+pub fn mock_dependencies(
+    canonical_length: usize,
+    contract_balance: &[Coin],
+) -> Extern<MockStorage, MockApi, WasmMockQuerier> {
+    let contract_addr = HumanAddr::from(MOCK_CONTRACT_ADDR);
+
+    Extern {
+        storage: MockStorage::default(),
+        api: MockApi::new(canonical_length),
+        querier: WasmMockQuerier::new(MockQuerier::new(&[(&contract_addr, contract_balance)])),
+    }
+}
@@ -0,0 +1,3 @@
+pub mod contract_test;
+pub mod mock_querier;
+pub mod test_utils;
@@ -0,0 +1,51 @@
+use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
+use cosmwasm_std::{Decimal, Extern, HumanAddr, Uint128};
+
+use prediction::{
+    asset::AssetInfo,
+    utils::ContractInfo,
+    vault::{InitMsg, Strategy},
+};
+
+use crate::contract::init;
+use crate::strategy::RoundInfo;
+use crate::tests::mock_querier::WasmMockQuerier;
+
+pub fn init_vault(deps: &mut Extern<MockStorage, MockApi, WasmMockQuerier>, strategy: Strategy) {
+    let msg = InitMsg {
+        operator_addr: HumanAddr::from("operator_addr"),
+        prediction: ContractInfo {
+            address: HumanAddr::from("prediction"),
+            code_hash: "prediction_code_hash".to_string(),
+        },
+        bet_asset: AssetInfo::NativeToken {
+            denom: "sscrt".to_string(),
+        },
+        share_token: ContractInfo {
+            address: HumanAddr::from("share_token"),
+            code_hash: "share_token_code_hash".to_string(),
+        },
+        strategy,
+        bet_rate: Decimal::percent(10),
+    };
+
+    let env = mock_env("owner_addr", &[]);
+
+    init(deps, env, msg).unwrap();
+}
+
+/// Up/down round starting at `start_time` with 300 seconds interval
+pub fn mock_round(start_time: u64, up_amount: u128, down_amount: u128) -> RoundInfo {
+    RoundInfo {
+        start_time,
+        lock_time: start_time + 300,
+        end_time: start_time + 600,
+        open_price: None,
+        close_price: None,
+        bucket_bounds: vec![],
+        outcome_amounts: vec![Uint128(up_amount), Uint128(down_amount)],
+        reward_amount: Uint128::zero(),
+        winning_outcome: None,
+        is_genesis: false,
+    }
+}
@@ -0,0 +1,311 @@
+use cosmwasm_std::testing::mock_env;
+use cosmwasm_std::{
+    from_binary, to_binary, BankMsg, BlockInfo, Coin, CosmosMsg, Env, HumanAddr, LogAttribute,
+    StdError, StdResult, Uint128, WasmMsg,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::contracts::Contract;
+use crate::querier::{AppQuerier, ContractInstance, Router};
+use crate::storage::AppStorage;
+
+/// Seconds per block when the time is advanced
+pub const BLOCK_TIME: u64 = 5;
+
+/// Logs of the executed messages, in execution order
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AppResponse {
+    pub logs: Vec<(HumanAddr, Vec<LogAttribute>)>,
+}
+
+impl AppResponse {
+    /// Log of the called contract
+    pub fn log(&self) -> &[LogAttribute] {
+        &self.logs[0].1
+    }
+}
+
+/// In-memory chain, executes messages and queries against the stored contracts
+pub struct App {
+    router: Rc<RefCell<Router>>,
+    querier: AppQuerier,
+    block: BlockInfo,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        App::new()
+    }
+}
+
+impl App {
+    pub fn new() -> Self {
+        let router = Rc::new(RefCell::new(Router::default()));
+
+        App {
+            querier: AppQuerier::new(router.clone()),
+            router,
+            block: mock_env("", &[]).block,
+        }
+    }
+
+    pub fn block(&self) -> &BlockInfo {
+        &self.block
+    }
+
+    pub fn advance_time(&mut self, seconds: u64) {
+        self.block.time += seconds;
+        self.block.height += (seconds / BLOCK_TIME).max(1);
+    }
+
+    /// Advance the block time to `time` if it is in the future
+    pub fn advance_to(&mut self, time: u64) {
+        if time > self.block.time {
+            self.advance_time(time - self.block.time);
+        }
+    }
+
+    pub fn store_code(&mut self, code: Box<dyn Contract>) -> u64 {
+        let mut router = self.router.borrow_mut();
+        router.codes.push(Rc::from(code));
+        router.codes.len() as u64
+    }
+
+    pub fn code_hash(&self, contract: &HumanAddr) -> StdResult<String> {
+        match self.router.borrow().contracts.get(contract) {
+            Some(instance) => Ok(instance.code_hash.clone()),
+            None => Err(StdError::not_found(format!("contract {}", contract))),
+        }
+    }
+
+    pub fn init_balance(&mut self, address: &str, amount: Vec<Coin>) {
+        self.router
+            .borrow_mut()
+            .bank
+            .insert(HumanAddr::from(address), amount);
+    }
+
+    pub fn balance(&self, address: &str, denom: &str) -> Uint128 {
+        self.router
+            .borrow()
+            .balance(&HumanAddr::from(address), denom)
+            .amount
+    }
+
+    /// Instantiate a contract of the stored code, the address is `contract{n}`
+    pub fn instantiate<T: Serialize>(
+        &mut self,
+        code_id: u64,
+        sender: &str,
+        msg: &T,
+        send: &[Coin],
+    ) -> StdResult<HumanAddr> {
+        let msg = to_binary(msg)?;
+        self.transact(|app| {
+            app.instantiate_msg(
+                code_id,
+                HumanAddr::from(sender),
+                msg.as_slice(),
+                send.to_vec(),
+            )
+        })
+    }
+
+    /// Execute a message and the messages it returns, reverted as a whole on error
+    pub fn execute<T: Serialize>(
+        &mut self,
+        sender: &str,
+        contract: &HumanAddr,
+        msg: &T,
+        send: &[Coin],
+    ) -> StdResult<AppResponse> {
+        let msg = to_binary(msg)?;
+        self.transact(|app| {
+            let mut response = AppResponse::default();
+            app.execute_msg(
+                HumanAddr::from(sender),
+                contract.clone(),
+                None,
+                msg.as_slice(),
+                send.to_vec(),
+                &mut response,
+            )?;
+            Ok(response)
+        })
+    }
+
+    pub fn query<T: Serialize, R: DeserializeOwned>(
+        &self,
+        contract: &HumanAddr,
+        msg: &T,
+    ) -> StdResult<R> {
+        let res = self.querier.query_wasm(contract, None, &to_binary(msg)?)?;
+        from_binary(&res)
+    }
+
+    fn transact<R, F>(&self, action: F) -> StdResult<R>
+    where
+        F: FnOnce(&Self) -> StdResult<R>,
+    {
+        let snapshot = self.router.borrow().snapshot();
+        let res = action(self);
+        if res.is_err() {
+            self.router.borrow_mut().restore(snapshot);
+        }
+        res
+    }
+
+    fn env(&self, sender: HumanAddr, contract: HumanAddr, send: &[Coin]) -> StdResult<Env> {
+        let mut env = mock_env(sender, send);
+        env.block = self.block.clone();
+        env.contract_code_hash = self.code_hash(&contract)?;
+        env.contract.address = contract;
+        Ok(env)
+    }
+
+    fn instantiate_msg(
+        &self,
+        code_id: u64,
+        sender: HumanAddr,
+        msg: &[u8],
+        send: Vec<Coin>,
+    ) -> StdResult<HumanAddr> {
+        let (code, contract) = {
+            let mut router = self.router.borrow_mut();
+            let code = match router.codes.get(code_id as usize - 1) {
+                Some(code) => code.clone(),
+                None => return Err(StdError::not_found(format!("code {}", code_id))),
+            };
+            let contract = HumanAddr(format!("contract{}", router.contracts.len() + 1));
+            router.contracts.insert(
+                contract.clone(),
+                ContractInstance {
+                    code_id,
+                    code_hash: format!("code_hash_{}", code_id),
+                    storage: AppStorage::default(),
+                },
+            );
+            (code, contract)
+        };
+
+        self.transfer(&sender, &contract, &send)?;
+
+        let env = self.env(sender, contract.clone(), &send)?;
+        let (_, storage) = self.router.borrow().instance(&contract, None)?;
+        let res = code.init(&mut self.querier.deps(storage), env, &msg.into())?;
+
+        let mut response = AppResponse::default();
+        response.logs.push((contract.clone(), res.log));
+        for msg in res.messages.into_iter() {
+            self.process_msg(&contract, msg, &mut response)?;
+        }
+
+        Ok(contract)
+    }
+
+    fn execute_msg(
+        &self,
+        sender: HumanAddr,
+        contract: HumanAddr,
+        code_hash: Option<&str>,
+        msg: &[u8],
+        send: Vec<Coin>,
+        response: &mut AppResponse,
+    ) -> StdResult<()> {
+        let (code, storage) = self.router.borrow().instance(&contract, code_hash)?;
+
+        self.transfer(&sender, &contract, &send)?;
+
+        let env = self.env(sender, contract.clone(), &send)?;
+        let res = code.handle(&mut self.querier.deps(storage), env, &msg.into())?;
+
+        response.logs.push((contract.clone(), res.log));
+        for msg in res.messages.into_iter() {
+            self.process_msg(&contract, msg, response)?;
+        }
+
+        Ok(())
+    }
+
+    fn process_msg(
+        &self,
+        contract: &HumanAddr,
+        msg: CosmosMsg,
+        response: &mut AppResponse,
+    ) -> StdResult<()> {
+        match msg {
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address,
+                to_address,
+                amount,
+            }) => {
+                if from_address != *contract {
+                    return Err(StdError::unauthorized());
+                }
+                self.transfer(&from_address, &to_address, &amount)
+            }
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                callback_code_hash,
+                msg,
+                send,
+            }) => self.execute_msg(
+                contract.clone(),
+                contract_addr,
+                Some(callback_code_hash.as_str()),
+                msg.as_slice(),
+                send,
+                response,
+            ),
+            msg => Err(StdError::generic_err(format!(
+                "Unsupported message: {:?}",
+                msg
+            ))),
+        }
+    }
+
+    fn transfer(&self, from: &HumanAddr, to: &HumanAddr, amount: &[Coin]) -> StdResult<()> {
+        let mut router = self.router.borrow_mut();
+
+        for coin in amount.iter() {
+            let balance = router.balance(from, &coin.denom).amount;
+            if balance < coin.amount {
+                return Err(StdError::generic_err(format!(
+                    "Insufficient {} balance of {}",
+                    coin.denom, from
+                )));
+            }
+
+            set_coin(
+                &mut router.bank,
+                from,
+                &coin.denom,
+                (balance - coin.amount)?,
+            );
+            let balance = router.balance(to, &coin.denom).amount;
+            set_coin(&mut router.bank, to, &coin.denom, balance + coin.amount);
+        }
+
+        Ok(())
+    }
+}
+
+fn set_coin(
+    bank: &mut HashMap<HumanAddr, Vec<Coin>>,
+    address: &HumanAddr,
+    denom: &str,
+    amount: Uint128,
+) {
+    let coins = bank.entry(address.clone()).or_insert_with(Vec::new);
+    match coins.iter_mut().find(|coin| coin.denom == denom) {
+        Some(coin) => coin.amount = amount,
+        None => coins.push(Coin {
+            denom: denom.to_string(),
+            amount,
+        }),
+    }
+}
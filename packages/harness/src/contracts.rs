@@ -0,0 +1,78 @@
+use cosmwasm_std::testing::MockApi;
+use cosmwasm_std::{from_binary, Binary, Env, Extern, HandleResult, InitResponse, StdResult};
+use serde::de::DeserializeOwned;
+
+use crate::querier::AppQuerier;
+use crate::storage::AppStorage;
+
+pub type AppDeps = Extern<AppStorage, MockApi, AppQuerier>;
+
+/// Entry points of a contract code, messages are passed as json
+pub trait Contract {
+    fn init(&self, deps: &mut AppDeps, env: Env, msg: &Binary) -> StdResult<InitResponse>;
+    fn handle(&self, deps: &mut AppDeps, env: Env, msg: &Binary) -> HandleResult;
+    fn query(&self, deps: &AppDeps, msg: &Binary) -> StdResult<Binary>;
+}
+
+pub struct ContractWrapper<I, H, Q> {
+    init_fn: fn(&mut AppDeps, Env, I) -> StdResult<InitResponse>,
+    handle_fn: fn(&mut AppDeps, Env, H) -> HandleResult,
+    query_fn: fn(&AppDeps, Q) -> StdResult<Binary>,
+}
+
+impl<I, H, Q> ContractWrapper<I, H, Q> {
+    pub fn new(
+        init_fn: fn(&mut AppDeps, Env, I) -> StdResult<InitResponse>,
+        handle_fn: fn(&mut AppDeps, Env, H) -> HandleResult,
+        query_fn: fn(&AppDeps, Q) -> StdResult<Binary>,
+    ) -> Self {
+        ContractWrapper {
+            init_fn,
+            handle_fn,
+            query_fn,
+        }
+    }
+}
+
+impl<I, H, Q> Contract for ContractWrapper<I, H, Q>
+where
+    I: DeserializeOwned,
+    H: DeserializeOwned,
+    Q: DeserializeOwned,
+{
+    fn init(&self, deps: &mut AppDeps, env: Env, msg: &Binary) -> StdResult<InitResponse> {
+        (self.init_fn)(deps, env, from_binary(msg)?)
+    }
+
+    fn handle(&self, deps: &mut AppDeps, env: Env, msg: &Binary) -> HandleResult {
+        (self.handle_fn)(deps, env, from_binary(msg)?)
+    }
+
+    fn query(&self, deps: &AppDeps, msg: &Binary) -> StdResult<Binary> {
+        (self.query_fn)(deps, from_binary(msg)?)
+    }
+}
+
+pub fn oracle_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(
+        oracle::contract::init::<AppStorage, MockApi, AppQuerier>,
+        oracle::contract::handle::<AppStorage, MockApi, AppQuerier>,
+        oracle::contract::query::<AppStorage, MockApi, AppQuerier>,
+    ))
+}
+
+pub fn prediction_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(
+        price_prediction::contract::init::<AppStorage, MockApi, AppQuerier>,
+        price_prediction::contract::handle::<AppStorage, MockApi, AppQuerier>,
+        price_prediction::contract::query::<AppStorage, MockApi, AppQuerier>,
+    ))
+}
+
+pub fn snip20_contract() -> Box<dyn Contract> {
+    Box::new(ContractWrapper::new(
+        snip20_reference_impl::contract::init::<AppStorage, MockApi, AppQuerier>,
+        snip20_reference_impl::contract::handle::<AppStorage, MockApi, AppQuerier>,
+        snip20_reference_impl::contract::query::<AppStorage, MockApi, AppQuerier>,
+    ))
+}
@@ -0,0 +1,12 @@
+pub mod app;
+pub mod contracts;
+pub mod querier;
+pub mod storage;
+
+pub use crate::app::{App, AppResponse};
+pub use crate::contracts::{
+    oracle_contract, prediction_contract, snip20_contract, Contract, ContractWrapper,
+};
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,180 @@
+use cosmwasm_std::testing::MockApi;
+use cosmwasm_std::{
+    from_slice, to_binary, AllBalanceResponse, BalanceResponse, BankQuery, Binary, Coin, Empty,
+    Extern, HumanAddr, Querier, QuerierResult, QueryRequest, ReadonlyStorage, StdError, StdResult,
+    SystemError, WasmQuery,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::contracts::{AppDeps, Contract};
+use crate::storage::{AppStorage, StorageData};
+
+pub const CANONICAL_LENGTH: usize = 20;
+
+pub struct ContractInstance {
+    pub code_id: u64,
+    pub code_hash: String,
+    pub storage: AppStorage,
+}
+
+/// Codes, contract instances and bank balances of the chain
+#[derive(Default)]
+pub struct Router {
+    pub codes: Vec<Rc<dyn Contract>>,
+    pub contracts: HashMap<HumanAddr, ContractInstance>,
+    pub bank: HashMap<HumanAddr, Vec<Coin>>,
+}
+
+/// Contract storages and bank balances, restored when a transaction fails
+pub struct Snapshot {
+    storages: HashMap<HumanAddr, (AppStorage, StorageData)>,
+    bank: HashMap<HumanAddr, Vec<Coin>>,
+}
+
+impl Router {
+    pub fn balance(&self, address: &HumanAddr, denom: &str) -> Coin {
+        self.bank
+            .get(address)
+            .and_then(|coins| coins.iter().find(|coin| coin.denom == denom).cloned())
+            .unwrap_or_else(|| Coin::new(0, denom))
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            storages: self
+                .contracts
+                .iter()
+                .map(|(address, instance)| {
+                    let storage = instance.storage.clone();
+                    (address.clone(), (storage.clone(), storage.snapshot()))
+                })
+                .collect(),
+            bank: self.bank.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        // Contracts instantiated after the snapshot are dropped
+        self.contracts
+            .retain(|address, _| snapshot.storages.contains_key(address));
+        for (_, (storage, data)) in snapshot.storages.into_iter() {
+            storage.restore(data);
+        }
+        self.bank = snapshot.bank;
+    }
+
+    /// Code and storage of the contract, the code hash must match if given
+    pub fn instance(
+        &self,
+        address: &HumanAddr,
+        code_hash: Option<&str>,
+    ) -> StdResult<(Rc<dyn Contract>, AppStorage)> {
+        let instance = match self.contracts.get(address) {
+            Some(instance) => instance,
+            None => return Err(StdError::not_found(format!("contract {}", address))),
+        };
+
+        if let Some(code_hash) = code_hash {
+            if code_hash != instance.code_hash {
+                return Err(StdError::generic_err(format!(
+                    "Invalid code hash for contract {}",
+                    address
+                )));
+            }
+        }
+
+        Ok((
+            self.codes[instance.code_id as usize - 1].clone(),
+            instance.storage.clone(),
+        ))
+    }
+}
+
+/// Routes queries of the contracts to the other contracts and the bank
+#[derive(Clone)]
+pub struct AppQuerier {
+    router: Rc<RefCell<Router>>,
+}
+
+impl AppQuerier {
+    pub fn new(router: Rc<RefCell<Router>>) -> Self {
+        AppQuerier { router }
+    }
+
+    pub fn deps(&self, storage: AppStorage) -> AppDeps {
+        Extern {
+            storage,
+            api: MockApi::new(CANONICAL_LENGTH),
+            querier: self.clone(),
+        }
+    }
+
+    pub fn query_wasm(
+        &self,
+        contract_addr: &HumanAddr,
+        code_hash: Option<&str>,
+        msg: &Binary,
+    ) -> StdResult<Binary> {
+        // The router is not borrowed while the contract runs, it may query other contracts
+        let (code, storage) = self.router.borrow().instance(contract_addr, code_hash)?;
+        code.query(&self.deps(storage), msg)
+    }
+}
+
+impl Querier for AppQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+
+        match &request {
+            QueryRequest::Bank(BankQuery::Balance { address, denom }) => {
+                let amount = self.router.borrow().balance(address, denom);
+                Ok(to_binary(&BalanceResponse { amount }))
+            }
+            QueryRequest::Bank(BankQuery::AllBalances { address }) => {
+                let amount = self
+                    .router
+                    .borrow()
+                    .bank
+                    .get(address)
+                    .cloned()
+                    .unwrap_or_default();
+                Ok(to_binary(&AllBalanceResponse { amount }))
+            }
+            QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr,
+                callback_code_hash,
+                msg,
+            }) => {
+                if !self.router.borrow().contracts.contains_key(contract_addr) {
+                    return Err(SystemError::NoSuchContract {
+                        addr: contract_addr.clone(),
+                    });
+                }
+                Ok(self.query_wasm(contract_addr, Some(callback_code_hash.as_str()), msg))
+            }
+            QueryRequest::Wasm(WasmQuery::Raw {
+                contract_addr, key, ..
+            }) => match self.router.borrow().contracts.get(contract_addr) {
+                Some(instance) => Ok(Ok(Binary(
+                    instance.storage.get(key.as_slice()).unwrap_or_default(),
+                ))),
+                None => Err(SystemError::NoSuchContract {
+                    addr: contract_addr.clone(),
+                }),
+            },
+            _ => Err(SystemError::UnsupportedRequest {
+                kind: format!("{:?}", request),
+            }),
+        }
+    }
+}
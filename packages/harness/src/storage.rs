@@ -0,0 +1,36 @@
+use cosmwasm_std::{ReadonlyStorage, Storage};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+pub type StorageData = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// Storage of a contract instance, shared with the querier so other contracts can read it
+#[derive(Clone, Default)]
+pub struct AppStorage(Rc<RefCell<StorageData>>);
+
+impl AppStorage {
+    pub fn snapshot(&self) -> StorageData {
+        self.0.borrow().clone()
+    }
+
+    pub fn restore(&self, data: StorageData) {
+        *self.0.borrow_mut() = data;
+    }
+}
+
+impl ReadonlyStorage for AppStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.borrow().get(key).cloned()
+    }
+}
+
+impl Storage for AppStorage {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.0.borrow_mut().insert(key.to_vec(), value.to_vec());
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.0.borrow_mut().remove(key);
+    }
+}
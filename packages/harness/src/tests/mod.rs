@@ -0,0 +1,4 @@
+pub mod prediction_snip20_test;
+pub mod prediction_test;
+pub mod simulation_test;
+pub mod test_utils;
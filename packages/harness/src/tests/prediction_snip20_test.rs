@@ -0,0 +1,96 @@
+use cosmwasm_std::{log, Uint128};
+
+use crate::tests::test_utils::{Suite, ALICE, BOB, CAROL, OWNER, TREASURY};
+use prediction::prediction::{HandleMsg, Position, State};
+use price_prediction::state::Bet;
+
+#[test]
+fn test_bet_with_snip20() {
+    let mut suite = Suite::snip20();
+    suite.start(3);
+
+    let alice_balance = suite.balance(ALICE);
+    let res = suite.bet(ALICE, 1000, Position::Up).unwrap();
+
+    // The token forwards the hook to the prediction contract
+    assert_eq!(res.logs.len(), 2);
+    assert_eq!(res.logs[1].0, suite.prediction);
+    assert_eq!(
+        res.logs[1].1,
+        vec![
+            log("action", "bet"),
+            log("amount", 1000),
+            log("position", Position::Up)
+        ]
+    );
+
+    assert_eq!(suite.state().epoch, Uint128(2));
+    assert_eq!(
+        suite.bet_of(2, ALICE).unwrap(),
+        Bet {
+            amount: Uint128(1000),
+            position: Some(Position::Up),
+            commitment: None,
+            claimed: false,
+        }
+    );
+    assert_eq!(suite.round(2).total_amount, Uint128(1000));
+    assert_eq!(
+        suite.balance(ALICE),
+        (alice_balance - Uint128(1000)).unwrap()
+    );
+}
+
+#[test]
+fn test_claim_with_snip20() {
+    let mut suite = Suite::snip20();
+    suite.start(3);
+
+    suite.bet(ALICE, 100, Position::Down).unwrap();
+    suite.bet(BOB, 1000, Position::Up).unwrap();
+    suite.bet(CAROL, 400, Position::Down).unwrap();
+
+    suite.finish_round(1, 5);
+    suite.finish_round(2, 3);
+
+    let alice_balance = suite.balance(ALICE);
+    let res = suite
+        .execute(ALICE, &HandleMsg::Claim { epoch: Uint128(2) })
+        .unwrap();
+    assert_eq!(
+        res.log(),
+        &[
+            log("action", "claim"),
+            log("epoch", 2),
+            log("amount", 100),
+            log("claim_amount", 291),
+        ]
+    );
+    assert_eq!(suite.balance(ALICE), alice_balance + Uint128(291));
+    assert!(suite.bet_of(2, ALICE).unwrap().claimed);
+}
+
+#[test]
+fn test_withdraw_with_snip20() {
+    let mut suite = Suite::snip20();
+    suite.start(3);
+
+    suite.bet(ALICE, 100, Position::Down).unwrap();
+    suite.bet(BOB, 1000, Position::Up).unwrap();
+    suite.bet(CAROL, 400, Position::Down).unwrap();
+
+    suite.finish_round(1, 5);
+    suite.finish_round(2, 3);
+
+    let res = suite.execute(OWNER, &HandleMsg::Withdraw {}).unwrap();
+    assert_eq!(res.log(), &[log("action", "withdraw"), log("amount", 45)]);
+    assert_eq!(suite.balance(TREASURY), Uint128(45));
+    assert_eq!(
+        suite.state(),
+        State {
+            epoch: Uint128(4),
+            total_fee: Uint128::zero(),
+            paused: false,
+        }
+    );
+}
@@ -0,0 +1,512 @@
+use cosmwasm_std::{log, Coin, Decimal, StdError, Uint128};
+
+use crate::tests::test_utils::{
+    Suite, ALICE, BOB, CAROL, DENOM, INTERVAL, OPERATOR, OWNER, TREASURY,
+};
+use prediction::asset::AssetInfo;
+use prediction::oracle::{AssetMetadata, HandleMsg as OracleHandleMsg};
+use prediction::prediction::{HandleMsg, Position, State};
+use price_prediction::state::Bet;
+
+#[test]
+fn test_bet_with_native_token() {
+    let mut suite = Suite::native();
+    suite.start(3);
+
+    let alice_balance = suite.balance(ALICE);
+    let res = suite.bet(ALICE, 1000, Position::Up).unwrap();
+    assert_eq!(
+        res.log(),
+        &[
+            log("action", "bet"),
+            log("amount", 1000),
+            log("position", Position::Up)
+        ]
+    );
+
+    assert_eq!(
+        suite.state(),
+        State {
+            epoch: Uint128(2),
+            total_fee: Uint128::zero(),
+            paused: false,
+        }
+    );
+    assert_eq!(
+        suite.bet_of(2, ALICE).unwrap(),
+        Bet {
+            amount: Uint128(1000),
+            position: Some(Position::Up),
+            commitment: None,
+            claimed: false,
+        }
+    );
+
+    let round = suite.round(2);
+    assert_eq!(round.total_amount, Uint128(1000));
+    assert_eq!(round.outcome_amounts, vec![Uint128(1000), Uint128::zero()]);
+
+    assert_eq!(
+        suite.balance(ALICE),
+        (alice_balance - Uint128(1000)).unwrap()
+    );
+    assert_eq!(suite.balance(suite.prediction.as_str()), Uint128(1000));
+}
+
+#[test]
+fn test_failed_bet_is_reverted() {
+    let mut suite = Suite::native();
+    suite.start(3);
+
+    suite.bet(ALICE, 1000, Position::Down).unwrap();
+    let alice_balance = suite.balance(ALICE);
+
+    let res = suite.bet(ALICE, 100, Position::Up).unwrap_err();
+    assert_eq!(StdError::generic_err("Already bet"), res);
+
+    suite.app.advance_time(INTERVAL);
+    let res = suite.bet(BOB, 1000, Position::Up).unwrap_err();
+    assert_eq!(StdError::generic_err("Cannot bet"), res);
+
+    // Sent funds are returned with the failed transaction
+    assert_eq!(suite.balance(ALICE), alice_balance);
+    assert_eq!(suite.balance(suite.prediction.as_str()), Uint128(1000));
+    assert_eq!(suite.round(2).total_amount, Uint128(1000));
+}
+
+#[test]
+fn test_execute_genesis_round() {
+    let mut suite = Suite::native();
+    suite.start(3);
+
+    suite.bet(ALICE, 100, Position::Down).unwrap();
+    suite.bet(BOB, 1000, Position::Up).unwrap();
+    suite.bet(CAROL, 500, Position::Down).unwrap();
+
+    let res = suite.finish_round(1, 5);
+    assert_eq!(
+        &res.log()[..4],
+        &[
+            log("action", "execute"),
+            log("epoch_finish", 1),
+            log("epoch_lock", 2),
+            log("close_price", Decimal::from_ratio(5u64, 1u64)),
+        ]
+    );
+
+    let price = Some(Decimal::from_ratio(5u64, 1u64));
+    assert_eq!(suite.round(1).close_price, price);
+    // The second oracle round, the first was fed at the start
+    assert_eq!(suite.round(1).close_round_id, Some(2));
+
+    let round = suite.round(2);
+    assert_eq!(round.open_price, price);
+    assert_eq!(round.open_round_id, Some(2));
+    assert_eq!(round.close_price, None);
+    assert_eq!(round.total_amount, Uint128(1600));
+    assert_eq!(round.outcome_amounts, vec![Uint128(1000), Uint128(600)]);
+
+    let round = suite.round(3);
+    assert_eq!(round.start_time, suite.app.block().time);
+    assert_eq!(round.lock_time, round.start_time + INTERVAL);
+    assert_eq!(round.end_time, round.lock_time + INTERVAL);
+    assert_eq!(round.open_price, None);
+    assert_eq!(round.total_amount, Uint128::zero());
+
+    assert_eq!(suite.state().epoch, Uint128(3));
+}
+
+#[test]
+fn test_auto_settle_on_price_hook() {
+    let mut suite = Suite::native();
+    suite.start(3);
+
+    let msg = OracleHandleMsg::Subscribe {
+        asset_info: suite.bet_asset.clone(),
+        contract_addr: suite.prediction.clone(),
+        code_hash: suite.app.code_hash(&suite.prediction).unwrap(),
+    };
+    suite.app.execute(OWNER, &suite.oracle, &msg, &[]).unwrap();
+
+    // The hook is skipped until auto settle is enabled
+    let end_time = suite.round(1).end_time;
+    suite.app.advance_to(end_time);
+    suite.feed_price(5);
+    assert_eq!(suite.state().epoch, Uint128(2));
+
+    suite
+        .execute(OWNER, &HandleMsg::UpdateAutoSettle { auto_settle: true })
+        .unwrap();
+    suite.app.advance_time(1);
+
+    // Hooks of another sender or asset are skipped without failing
+    let hook = |asset_info: AssetInfo| HandleMsg::PriceUpdated {
+        asset_info,
+        round_id: 1,
+        price: Decimal::one(),
+        updated_at: end_time + 1,
+    };
+    let skipped = [log("action", "price_updated"), log("settled", false)];
+    let res = suite
+        .execute(ALICE, &hook(suite.bet_asset.clone()))
+        .unwrap();
+    assert_eq!(res.log(), &skipped);
+
+    let other_asset = AssetInfo::NativeToken {
+        denom: "uatom".to_string(),
+    };
+    let oracle = suite.oracle.clone();
+    let res = suite.execute(oracle.as_str(), &hook(other_asset)).unwrap();
+    assert_eq!(res.log(), &skipped);
+    assert_eq!(suite.state().epoch, Uint128(2));
+
+    suite.feed_price(6);
+    assert_eq!(suite.state().epoch, Uint128(3));
+
+    let round = suite.round(1);
+    assert_eq!(round.close_price, Some(Decimal::from_ratio(6u64, 1u64)));
+    assert_eq!(round.close_round_id, Some(3));
+    assert_eq!(suite.round(2).open_round_id, Some(3));
+
+    // The next round is not executed before its end
+    let res = suite.execute_round().unwrap_err();
+    assert_eq!(StdError::generic_err("Cannot execute"), res);
+    suite.app.advance_time(1);
+    suite.feed_price(7);
+    assert_eq!(suite.state().epoch, Uint128(3));
+}
+
+#[test]
+fn test_execute_round_on_paused_feed() {
+    let mut suite = Suite::native();
+    suite.start(3);
+
+    let end_time = suite.round(1).end_time;
+    suite.app.advance_to(end_time);
+    suite.feed_price(5);
+
+    let msg = OracleHandleMsg::Pause {
+        asset_info: Some(suite.bet_asset.clone()),
+    };
+    suite.app.execute(OWNER, &suite.oracle, &msg, &[]).unwrap();
+
+    let res = suite.execute_round().unwrap_err();
+    assert_eq!(StdError::generic_err("Oracle feed paused"), res);
+    assert_eq!(suite.state().epoch, Uint128(2));
+
+    let msg = OracleHandleMsg::Unpause {
+        asset_info: Some(suite.bet_asset.clone()),
+    };
+    suite.app.execute(OWNER, &suite.oracle, &msg, &[]).unwrap();
+
+    suite.execute_round().unwrap();
+    assert_eq!(suite.state().epoch, Uint128(3));
+}
+
+#[test]
+fn test_execute_round_on_restricted_reads() {
+    let mut suite = Suite::native();
+    suite.start(3);
+
+    let end_time = suite.round(1).end_time;
+    suite.app.advance_to(end_time);
+    suite.feed_price(5);
+
+    let msg = OracleHandleMsg::UpdateConfig {
+        owner: None,
+        guardian: None,
+        remove_guardian: None,
+        history_retention: None,
+        quorum: None,
+        max_price_age: None,
+        restrict_reads: Some(true),
+    };
+    suite.app.execute(OWNER, &suite.oracle, &msg, &[]).unwrap();
+
+    let res = suite.execute_round().unwrap_err();
+    assert_eq!(StdError::generic_err("Authenticated query required"), res);
+
+    let msg = HandleMsg::SetOracleViewingKey {
+        key: "key".to_string(),
+    };
+    let res = suite.execute(ALICE, &msg).unwrap_err();
+    assert_eq!(StdError::unauthorized(), res);
+    suite.execute(OWNER, &msg).unwrap();
+
+    let res = suite.execute_round().unwrap_err();
+    assert_eq!(StdError::generic_err("Read access denied"), res);
+    assert_eq!(suite.state().epoch, Uint128(2));
+
+    let msg = OracleHandleMsg::AddConsumer {
+        consumer: suite.prediction.clone(),
+    };
+    suite.app.execute(OWNER, &suite.oracle, &msg, &[]).unwrap();
+
+    suite.execute_round().unwrap();
+    assert_eq!(suite.state().epoch, Uint128(3));
+}
+
+#[test]
+fn test_settle_strike_market_with_price_at_expiry() {
+    let mut suite = Suite::native();
+    suite.start(3);
+
+    let now = suite.app.block().time;
+    let expiry = now + 200;
+    let msg = HandleMsg::CreateStrikeMarket {
+        strike_price: Decimal::from_ratio(4u64, 1u64),
+        bet_deadline: now + 100,
+        expiry,
+        grace_interval: 600,
+        max_price_age: 60,
+    };
+    suite.execute(OPERATOR, &msg).unwrap();
+    for (user, position) in [(ALICE, Position::Up), (BOB, Position::Down)].iter() {
+        let msg = HandleMsg::BetStrikeMarket {
+            id: 1,
+            position: position.clone(),
+        };
+        suite
+            .app
+            .execute(user, &suite.prediction, &msg, &[Coin::new(100, DENOM)])
+            .unwrap();
+    }
+
+    suite.app.advance_to(expiry);
+    suite.feed_price(3);
+
+    let msg = HandleMsg::SettleStrikeMarket { id: 1 };
+    let res = suite.execute(OPERATOR, &msg).unwrap_err();
+    assert_eq!(StdError::generic_err("Price not updated"), res);
+
+    // A later price does not change the settlement
+    suite.app.advance_time(10);
+    suite.feed_price(5);
+    let res = suite.execute(OPERATOR, &msg).unwrap();
+    assert_eq!(
+        res.log(),
+        &[
+            log("action", "settle_strike_market"),
+            log("id", 1),
+            log("close_price", 3),
+        ]
+    );
+}
+
+#[test]
+fn test_settle_strike_market_failed_if_price_at_expiry_is_too_old() {
+    let mut suite = Suite::native();
+    suite.start(3);
+
+    let now = suite.app.block().time;
+    let expiry = now + 200;
+    let msg = HandleMsg::CreateStrikeMarket {
+        strike_price: Decimal::from_ratio(4u64, 1u64),
+        bet_deadline: now + 100,
+        expiry,
+        grace_interval: 600,
+        max_price_age: 60,
+    };
+    suite.execute(OPERATOR, &msg).unwrap();
+
+    // The latest price before the expiry was fed at the start
+    suite.app.advance_to(expiry + 10);
+    suite.feed_price(5);
+
+    let msg = HandleMsg::SettleStrikeMarket { id: 1 };
+    let res = suite.execute(OPERATOR, &msg).unwrap_err();
+    assert_eq!(StdError::generic_err("Price at expiry is too old"), res);
+}
+
+#[test]
+fn test_claim_by_winner() {
+    let mut suite = Suite::native();
+    suite.start(3);
+
+    suite.bet(ALICE, 100, Position::Down).unwrap();
+    suite.bet(BOB, 1000, Position::Up).unwrap();
+    suite.bet(CAROL, 400, Position::Down).unwrap();
+
+    suite.finish_round(1, 5);
+    suite.finish_round(2, 3);
+
+    let res = suite
+        .execute(BOB, &HandleMsg::Claim { epoch: Uint128(2) })
+        .unwrap_err();
+    assert_eq!(StdError::generic_err("Nothing to claim"), res);
+
+    let alice_balance = suite.balance(ALICE);
+    let res = suite
+        .execute(ALICE, &HandleMsg::Claim { epoch: Uint128(2) })
+        .unwrap();
+    assert_eq!(
+        res.log(),
+        &[
+            log("action", "claim"),
+            log("epoch", 2),
+            log("amount", 100),
+            log("claim_amount", 291),
+        ]
+    );
+    assert_eq!(suite.balance(ALICE), alice_balance + Uint128(291));
+    assert!(suite.bet_of(2, ALICE).unwrap().claimed);
+
+    let res = suite
+        .execute(ALICE, &HandleMsg::Claim { epoch: Uint128(2) })
+        .unwrap_err();
+    assert_eq!(StdError::generic_err("Already claimed"), res);
+}
+
+#[test]
+fn test_withdraw_to_treasury() {
+    let mut suite = Suite::native();
+    suite.start(3);
+
+    let res = suite.execute(OWNER, &HandleMsg::Withdraw {}).unwrap_err();
+    assert_eq!(StdError::generic_err("No stacked fee"), res);
+
+    suite.bet(ALICE, 100, Position::Down).unwrap();
+    suite.bet(BOB, 1000, Position::Up).unwrap();
+    suite.bet(CAROL, 400, Position::Down).unwrap();
+
+    suite.finish_round(1, 5);
+    suite.finish_round(2, 3);
+
+    let res = suite.execute(ALICE, &HandleMsg::Withdraw {});
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = suite.execute(OWNER, &HandleMsg::Withdraw {}).unwrap();
+    assert_eq!(res.log(), &[log("action", "withdraw"), log("amount", 45)]);
+    assert_eq!(suite.balance(TREASURY), Uint128(45));
+    assert_eq!(
+        suite.state(),
+        State {
+            epoch: Uint128(4),
+            total_fee: Uint128::zero(),
+            paused: false,
+        }
+    );
+
+    // The rewards stay in the contract for the winners
+    assert_eq!(suite.balance(suite.prediction.as_str()), Uint128(1455));
+}
+
+#[test]
+fn test_quote_currency_mismatch() {
+    let mut suite = Suite::native();
+
+    let update_msg = |quote_currency: &str| HandleMsg::UpdateConfig {
+        owner_addr: None,
+        operator_addr: None,
+        treasury_addr: None,
+        oracle_addr: None,
+        oracle_code_hash: None,
+        fee_rate: None,
+        interval: None,
+        grace_interval: None,
+        quote_currency: Some(quote_currency.to_string()),
+    };
+
+    let res = suite.execute(OWNER, &update_msg("EUR")).unwrap_err();
+    assert_eq!(StdError::generic_err("Oracle quote currency mismatch"), res);
+
+    suite.execute(OWNER, &update_msg("USD")).unwrap();
+}
+
+#[test]
+fn test_execute_round_on_price_unit_change() {
+    let mut suite = Suite::native();
+    suite.start(3);
+
+    let end_time = suite.round(1).end_time;
+    suite.app.advance_to(end_time);
+
+    // Updating the unit of the asset drops its prices, so it is fed again
+    let update_unit = |suite: &mut Suite, quote_currency: &str, decimals: u8| {
+        let msg = OracleHandleMsg::UpdateAsset {
+            asset_info: suite.bet_asset.clone(),
+            feeders: None,
+            heartbeat: None,
+            metadata: Some(AssetMetadata {
+                symbol: "SCRT".to_string(),
+                decimals,
+                quote_currency: quote_currency.to_string(),
+                description: None,
+            }),
+        };
+        suite.app.execute(OWNER, &suite.oracle, &msg, &[]).unwrap();
+        suite.feed_price(5);
+    };
+
+    update_unit(&mut suite, "EUR", 6);
+    let res = suite.execute_round().unwrap_err();
+    assert_eq!(StdError::generic_err("Oracle quote currency mismatch"), res);
+
+    update_unit(&mut suite, "USD", 8);
+    let res = suite.execute_round().unwrap_err();
+    assert_eq!(StdError::generic_err("Oracle decimals mismatch"), res);
+
+    update_unit(&mut suite, "USD", 6);
+    suite.execute_round().unwrap();
+    assert_eq!(suite.state().epoch, Uint128(3));
+}
+
+#[test]
+fn test_auto_settle_skips_price_unit_change() {
+    let mut suite = Suite::native();
+    suite.start(3);
+
+    let msg = OracleHandleMsg::Subscribe {
+        asset_info: suite.bet_asset.clone(),
+        contract_addr: suite.prediction.clone(),
+        code_hash: suite.app.code_hash(&suite.prediction).unwrap(),
+    };
+    suite.app.execute(OWNER, &suite.oracle, &msg, &[]).unwrap();
+    suite
+        .execute(OWNER, &HandleMsg::UpdateAutoSettle { auto_settle: true })
+        .unwrap();
+
+    let end_time = suite.round(1).end_time;
+    suite.app.advance_to(end_time);
+
+    let msg = OracleHandleMsg::UpdateAsset {
+        asset_info: suite.bet_asset.clone(),
+        feeders: None,
+        heartbeat: None,
+        metadata: Some(AssetMetadata {
+            symbol: "SCRT".to_string(),
+            decimals: 8,
+            quote_currency: "USD".to_string(),
+            description: None,
+        }),
+    };
+    suite.app.execute(OWNER, &suite.oracle, &msg, &[]).unwrap();
+
+    // The feed succeeds, but the round waits for a price in the expected unit
+    suite.feed_price(5);
+    assert_eq!(suite.state().epoch, Uint128(2));
+
+    let oracle = suite.oracle.clone();
+    let hook = HandleMsg::PriceUpdated {
+        asset_info: suite.bet_asset.clone(),
+        round_id: 1,
+        price: Decimal::from_ratio(5u64, 1u64),
+        updated_at: end_time,
+    };
+    let res = suite.execute(oracle.as_str(), &hook).unwrap();
+    assert_eq!(
+        res.log(),
+        &[
+            log("action", "price_updated"),
+            log("settled", false),
+            log("error", StdError::generic_err("Oracle decimals mismatch")),
+        ]
+    );
+    assert_eq!(suite.round(1).close_price, None);
+
+    let res = suite.execute_round().unwrap_err();
+    assert_eq!(StdError::generic_err("Oracle decimals mismatch"), res);
+}
@@ -0,0 +1,263 @@
+use cosmwasm_std::testing::mock_env;
+use cosmwasm_std::{Coin, Env, StdError, Uint128};
+use rand_chacha::ChaChaRng;
+use rand_core::{RngCore, SeedableRng};
+use std::collections::HashMap;
+
+use crate::tests::test_utils::{Suite, DENOM, GRACE_INTERVAL, INTERVAL, OWNER, TREASURY};
+use prediction::prediction::{HandleMsg, Position};
+use price_prediction::state::Round;
+
+const USERS: [&str; 5] = ["user0", "user1", "user2", "user3", "user4"];
+const SEEDS: u64 = 20;
+const STEPS: usize = 400;
+
+struct ModelBet {
+    amount: Uint128,
+    position: Position,
+    claimed: bool,
+}
+
+/// Bets placed through the simulation by epoch and user index, and rewards paid by epoch
+struct Simulation {
+    suite: Suite,
+    rng: ChaChaRng,
+    bets: HashMap<(u128, usize), ModelBet>,
+    paid_rewards: HashMap<u128, Uint128>,
+}
+
+impl Simulation {
+    fn new(seed: u64) -> Self {
+        let mut suite = Suite::native();
+        for user in USERS.iter() {
+            suite
+                .app
+                .init_balance(user, vec![Coin::new(1_000_000_000, DENOM)]);
+        }
+        suite.start(100);
+
+        Simulation {
+            suite,
+            rng: ChaChaRng::seed_from_u64(seed),
+            bets: HashMap::new(),
+            paid_rewards: HashMap::new(),
+        }
+    }
+
+    fn random(&mut self, n: u64) -> u64 {
+        self.rng.next_u64() % n
+    }
+
+    fn env(&self) -> Env {
+        let mut env = mock_env(OWNER, &[]);
+        env.block = self.suite.app.block().clone();
+        env
+    }
+
+    fn step(&mut self) {
+        match self.random(8) {
+            0 | 1 | 2 => self.bet(),
+            3 => self.execute_round(),
+            4 | 5 => self.claim(),
+            6 => {
+                let time = self.random(INTERVAL + GRACE_INTERVAL);
+                self.suite.app.advance_time(time);
+            }
+            _ => match self.random(4) {
+                0 => self.pause_and_restart(),
+                1 => self.withdraw(),
+                // Prices close to each other so rounds also end flat
+                _ => {
+                    let price = 98 + self.random(5);
+                    self.suite.feed_price(price);
+                }
+            },
+        }
+    }
+
+    fn bet(&mut self) {
+        let user = self.random(USERS.len() as u64) as usize;
+        let amount = Uint128(1 + self.random(10_000) as u128);
+        let position = if self.random(2) == 0 {
+            Position::Up
+        } else {
+            Position::Down
+        };
+        let epoch = self.suite.state().epoch.u128();
+
+        if self
+            .suite
+            .bet(USERS[user], amount.u128(), position.clone())
+            .is_ok()
+        {
+            assert!(
+                !self.bets.contains_key(&(epoch, user)),
+                "second bet on epoch {}",
+                epoch
+            );
+            self.bets.insert(
+                (epoch, user),
+                ModelBet {
+                    amount,
+                    position,
+                    claimed: false,
+                },
+            );
+        }
+    }
+
+    fn execute_round(&mut self) {
+        let state = self.suite.state();
+        if self.suite.execute_round().is_err() {
+            return;
+        }
+
+        let round = self.suite.round(state.epoch.u128() - 1);
+        let fee = (self.suite.state().total_fee - state.total_fee).unwrap();
+        assert!(fee <= round.total_amount * self.suite.config().fee_rate);
+        if round.claimable(self.env()) {
+            assert_eq!(fee + round.reward_amount, round.total_amount);
+        } else {
+            assert_eq!(fee, Uint128::zero());
+        }
+    }
+
+    fn claim(&mut self) {
+        let unclaimed: Vec<(u128, usize)> = self
+            .bets
+            .iter()
+            .filter(|(_, bet)| !bet.claimed)
+            .map(|(key, _)| *key)
+            .collect();
+        if unclaimed.is_empty() {
+            return;
+        }
+        let (epoch, user) = unclaimed[self.random(unclaimed.len() as u64) as usize];
+
+        let round = self.suite.round(epoch);
+        let expected = expected_payout(&round, &self.bets[&(epoch, user)], self.env());
+
+        let balance = self.suite.balance(USERS[user]);
+        let res = self.suite.execute(
+            USERS[user],
+            &HandleMsg::Claim {
+                epoch: Uint128(epoch),
+            },
+        );
+        let payout = (self.suite.balance(USERS[user]) - balance).unwrap();
+
+        match (res, expected) {
+            (Ok(_), Some((min, max))) => {
+                assert!(
+                    min <= payout && payout <= max,
+                    "payout {} of epoch {}",
+                    payout,
+                    epoch
+                );
+                self.bets.get_mut(&(epoch, user)).unwrap().claimed = true;
+
+                // Rounded shares never add up to more than the reward
+                if round.claimable(self.env()) {
+                    let paid = self.paid_rewards.entry(epoch).or_insert_with(Uint128::zero);
+                    *paid = *paid + payout;
+                    assert!(*paid <= round.reward_amount);
+                }
+
+                let res = self.suite.execute(
+                    USERS[user],
+                    &HandleMsg::Claim {
+                        epoch: Uint128(epoch),
+                    },
+                );
+                assert_eq!(res.unwrap_err(), StdError::generic_err("Already claimed"));
+            }
+            (Ok(_), None) => panic!("unexpected claim of epoch {}", epoch),
+            (Err(_), Some(_)) => panic!("failed claim of epoch {}", epoch),
+            (Err(_), None) => assert_eq!(payout, Uint128::zero()),
+        }
+    }
+
+    fn pause_and_restart(&mut self) {
+        if self.suite.execute(OWNER, &HandleMsg::Pause {}).is_ok() {
+            self.suite
+                .execute(OWNER, &HandleMsg::StartGenesisRound {})
+                .unwrap();
+        }
+    }
+
+    fn withdraw(&mut self) {
+        let total_fee = self.suite.state().total_fee;
+        let balance = self.suite.balance(TREASURY);
+
+        match self.suite.execute(OWNER, &HandleMsg::Withdraw {}) {
+            Ok(_) => {
+                assert_eq!(self.suite.balance(TREASURY), balance + total_fee);
+                assert_eq!(self.suite.state().total_fee, Uint128::zero());
+            }
+            Err(_) => assert!(total_fee.is_zero()),
+        }
+    }
+
+    /// The contract holds the fee and everything the bettors can still claim
+    fn assert_solvent(&self) {
+        let env = self.env();
+        let mut rounds: HashMap<u128, Round> = HashMap::new();
+        let mut liability = self.suite.state().total_fee;
+
+        for ((epoch, _), bet) in self.bets.iter() {
+            if bet.claimed {
+                continue;
+            }
+            let round = rounds
+                .entry(*epoch)
+                .or_insert_with(|| self.suite.round(*epoch));
+
+            liability = liability
+                + match expected_payout(round, bet, env.clone()) {
+                    Some((_, max)) => max,
+                    // Unsettled bets are owed at most their stake, until the round pays out
+                    None if round.close_price.is_none() => bet.amount,
+                    None => Uint128::zero(),
+                };
+        }
+
+        let balance = self.suite.balance(self.suite.prediction.as_str());
+        assert!(
+            balance >= liability,
+            "balance {} below liability {}",
+            balance,
+            liability
+        );
+    }
+}
+
+/// Range of the payout of an unclaimed bet, `None` if nothing is claimable yet
+fn expected_payout(round: &Round, bet: &ModelBet, env: Env) -> Option<(Uint128, Uint128)> {
+    if round.claimable(env.clone()) {
+        if round.winning_outcome != Some(bet.position.outcome()) {
+            return None;
+        }
+        // Share of the reward, rounded down by at most one through the decimal ratio
+        let exact = round
+            .reward_amount
+            .multiply_ratio(bet.amount, round.winning_amount());
+        Some(((exact - Uint128(1)).unwrap_or(exact), exact))
+    } else if round.refundable(env, GRACE_INTERVAL) {
+        Some((bet.amount, bet.amount))
+    } else {
+        None
+    }
+}
+
+#[test]
+fn test_random_rounds_stay_solvent() {
+    for seed in 0..SEEDS {
+        let mut simulation = Simulation::new(seed);
+        for _ in 0..STEPS {
+            simulation.step();
+            simulation.assert_solvent();
+        }
+
+        assert!(simulation.suite.state().epoch > Uint128(2));
+    }
+}
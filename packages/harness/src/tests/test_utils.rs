@@ -0,0 +1,300 @@
+use cosmwasm_std::{to_binary, Binary, Coin, Decimal, HumanAddr, StdResult, Uint128};
+use snip20_reference_impl::msg::{
+    HandleMsg as Snip20HandleMsg, InitMsg as Snip20InitMsg, InitialBalance,
+    QueryAnswer as Snip20QueryAnswer, QueryMsg as Snip20QueryMsg,
+};
+
+use crate::{oracle_contract, prediction_contract, snip20_contract, App, AppResponse};
+use prediction::{
+    asset::AssetInfo,
+    oracle::{AssetMetadata, HandleMsg as OracleHandleMsg, InitMsg as OracleInitMsg},
+    prediction::{ConfigResponse, Cw20HookMsg, HandleMsg, InitMsg, Position, QueryMsg, State},
+};
+use price_prediction::state::{Bet, Round};
+
+pub const OWNER: &str = "owner";
+pub const OPERATOR: &str = "operator";
+pub const TREASURY: &str = "treasury";
+pub const ALICE: &str = "alice";
+pub const BOB: &str = "bob";
+pub const CAROL: &str = "carol";
+
+pub const DENOM: &str = "uscrt";
+pub const INTERVAL: u64 = 30;
+pub const GRACE_INTERVAL: u64 = 15;
+pub const VIEWING_KEY: &str = "viewing_key";
+
+const INITIAL_BALANCE: u128 = 1_000_000_000_000;
+
+/// Oracle and prediction contracts, betting with native tokens or a snip20 token
+pub struct Suite {
+    pub app: App,
+    pub oracle: HumanAddr,
+    pub prediction: HumanAddr,
+    pub token: Option<HumanAddr>,
+    pub bet_asset: AssetInfo,
+}
+
+impl Suite {
+    pub fn native() -> Self {
+        let mut app = App::new();
+        for user in [ALICE, BOB, CAROL].iter() {
+            app.init_balance(user, vec![Coin::new(INITIAL_BALANCE, DENOM)]);
+        }
+
+        let bet_asset = AssetInfo::NativeToken {
+            denom: DENOM.to_string(),
+        };
+        Suite::init(app, None, bet_asset)
+    }
+
+    pub fn snip20() -> Self {
+        let mut app = App::new();
+        let snip20_code_id = app.store_code(snip20_contract());
+        let token = app
+            .instantiate(
+                snip20_code_id,
+                OWNER,
+                &Snip20InitMsg {
+                    name: "SEFI".to_string(),
+                    admin: Some(HumanAddr::from(OWNER)),
+                    symbol: "SEFI".to_string(),
+                    decimals: 6,
+                    initial_balances: Some(
+                        [ALICE, BOB, CAROL]
+                            .iter()
+                            .map(|user| InitialBalance {
+                                address: HumanAddr::from(*user),
+                                amount: Uint128(INITIAL_BALANCE),
+                            })
+                            .collect(),
+                    ),
+                    prng_seed: Binary::from("seed".as_bytes()),
+                    config: None,
+                },
+                &[],
+            )
+            .unwrap();
+
+        for user in [ALICE, TREASURY].iter() {
+            app.execute(
+                user,
+                &token,
+                &Snip20HandleMsg::SetViewingKey {
+                    key: VIEWING_KEY.to_string(),
+                    padding: None,
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let bet_asset = AssetInfo::Token {
+            contract_addr: token.clone(),
+            token_code_hash: app.code_hash(&token).unwrap(),
+            viewing_key: "empty".to_string(),
+        };
+        Suite::init(app, Some(token), bet_asset)
+    }
+
+    fn init(mut app: App, token: Option<HumanAddr>, bet_asset: AssetInfo) -> Self {
+        let oracle_code_id = app.store_code(oracle_contract());
+        let prediction_code_id = app.store_code(prediction_contract());
+
+        let oracle = app
+            .instantiate(
+                oracle_code_id,
+                OWNER,
+                &OracleInitMsg {
+                    owner: HumanAddr::from(OWNER),
+                    history_retention: None,
+                    quorum: None,
+                    max_price_age: None,
+                    guardian: None,
+                    restrict_reads: None,
+                    access_fee: None,
+                },
+                &[],
+            )
+            .unwrap();
+        app.execute(
+            OWNER,
+            &oracle,
+            &OracleHandleMsg::RegisterAsset {
+                asset_info: bet_asset.clone(),
+                feeder: HumanAddr::from(OWNER),
+                heartbeat: None,
+                metadata: AssetMetadata {
+                    symbol: "SCRT".to_string(),
+                    decimals: 6,
+                    quote_currency: "USD".to_string(),
+                    description: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+        let prediction = app
+            .instantiate(
+                prediction_code_id,
+                OWNER,
+                &InitMsg {
+                    operator_addr: HumanAddr::from(OPERATOR),
+                    treasury_addr: HumanAddr::from(TREASURY),
+                    bet_asset: bet_asset.clone(),
+                    oracle_code_hash: app.code_hash(&oracle).unwrap(),
+                    oracle_addr: oracle.clone(),
+                    fee_rate: Decimal::percent(3),
+                    interval: INTERVAL,
+                    grace_interval: GRACE_INTERVAL,
+                    prng_seed: Binary::from("seed".as_bytes()),
+                    bucket_bounds: None,
+                    commit_reveal: None,
+                    timelock: None,
+                    quote_currency: Some("USD".to_string()),
+                    auto_settle: None,
+                    import: None,
+                },
+                &[],
+            )
+            .unwrap();
+
+        Suite {
+            app,
+            oracle,
+            prediction,
+            token,
+            bet_asset,
+        }
+    }
+
+    /// Feed the price and start the genesis round, alice sets her viewing key
+    pub fn start(&mut self, price: u64) {
+        self.feed_price(price);
+        self.execute(OWNER, &HandleMsg::StartGenesisRound {})
+            .unwrap();
+        self.execute(
+            ALICE,
+            &HandleMsg::SetViewingKey {
+                key: VIEWING_KEY.to_string(),
+                padding: None,
+            },
+        )
+        .unwrap();
+    }
+
+    pub fn feed_price(&mut self, price: u64) {
+        self.app
+            .execute(
+                OWNER,
+                &self.oracle,
+                &OracleHandleMsg::FeedPrice {
+                    prices: vec![(self.bet_asset.clone(), Decimal::from_ratio(price, 1u64))],
+                    quote_currency: None,
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    pub fn execute(&mut self, sender: &str, msg: &HandleMsg) -> StdResult<AppResponse> {
+        self.app.execute(sender, &self.prediction, msg, &[])
+    }
+
+    pub fn execute_round(&mut self) -> StdResult<AppResponse> {
+        self.execute(OPERATOR, &HandleMsg::ExecuteRound {})
+    }
+
+    /// Advance to the end of the round, feed the close price and execute the round
+    pub fn finish_round(&mut self, epoch: u128, close_price: u64) -> AppResponse {
+        let end_time = self.round(epoch).end_time;
+        self.app.advance_to(end_time);
+        self.feed_price(close_price);
+        self.execute_round().unwrap()
+    }
+
+    pub fn bet(&mut self, user: &str, amount: u128, position: Position) -> StdResult<AppResponse> {
+        match self.token.clone() {
+            Some(token) => {
+                let code_hash = self.app.code_hash(&self.prediction)?;
+                self.app.execute(
+                    user,
+                    &token,
+                    &Snip20HandleMsg::Send {
+                        recipient: self.prediction.clone(),
+                        recipient_code_hash: Some(code_hash),
+                        amount: Uint128(amount),
+                        msg: Some(to_binary(&Cw20HookMsg::Bet { position })?),
+                        memo: None,
+                        padding: None,
+                    },
+                    &[],
+                )
+            }
+            None => self.app.execute(
+                user,
+                &self.prediction,
+                &HandleMsg::Bet { position },
+                &[Coin::new(amount, DENOM)],
+            ),
+        }
+    }
+
+    /// Balance of the bet asset
+    pub fn balance(&self, user: &str) -> Uint128 {
+        match &self.token {
+            Some(token) => {
+                let answer: Snip20QueryAnswer = self
+                    .app
+                    .query(
+                        token,
+                        &Snip20QueryMsg::Balance {
+                            address: HumanAddr::from(user),
+                            key: VIEWING_KEY.to_string(),
+                        },
+                    )
+                    .unwrap();
+                match answer {
+                    Snip20QueryAnswer::Balance { amount } => amount,
+                    _ => panic!("unexpected balance answer"),
+                }
+            }
+            None => self.app.balance(user, DENOM),
+        }
+    }
+
+    pub fn config(&self) -> ConfigResponse {
+        self.app
+            .query(&self.prediction, &QueryMsg::Config {})
+            .unwrap()
+    }
+
+    pub fn state(&self) -> State {
+        self.app
+            .query(&self.prediction, &QueryMsg::State {})
+            .unwrap()
+    }
+
+    pub fn round(&self, epoch: u128) -> Round {
+        self.app
+            .query(
+                &self.prediction,
+                &QueryMsg::Round {
+                    epoch: Uint128(epoch),
+                },
+            )
+            .unwrap()
+    }
+
+    pub fn bet_of(&self, epoch: u128, user: &str) -> StdResult<Bet> {
+        self.app.query(
+            &self.prediction,
+            &QueryMsg::Bet {
+                epoch: Uint128(epoch),
+                user: HumanAddr::from(user),
+                key: VIEWING_KEY.to_string(),
+            },
+        )
+    }
+}
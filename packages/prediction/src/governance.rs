@@ -0,0 +1,148 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::ContractInfo;
+use cosmwasm_std::{Binary, Decimal, HumanAddr, Uint128};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InitMsg {
+    /// SNIP-20 token staked for voting power
+    pub voting_token: ContractInfo,
+    /// Minimum share of the staked tokens which must vote
+    pub quorum: Decimal,
+    /// Share of yes votes required to pass
+    pub threshold: Decimal,
+    /// Voting period in seconds
+    pub voting_period: u64,
+    /// Delay between the end of voting and execution in seconds
+    pub timelock_period: u64,
+    /// Seconds after the timelock during which a passed proposal can be executed
+    pub expiration_period: u64,
+    /// Minimum stake to create a proposal
+    pub proposal_min_stake: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleMsg {
+    /// Stake voting tokens
+    Receive {
+        from: HumanAddr,
+        msg: Option<Binary>,
+        amount: Uint128,
+    },
+    /// Withdraw staked tokens, locked until the proposals voted on end
+    Unstake { amount: Option<Uint128> },
+    /// Propose messages to be executed by the governance contract
+    CreateProposal {
+        title: String,
+        description: String,
+        messages: Vec<ProposalMsg>,
+    },
+    /// Vote with the whole stake
+    CastVote { id: u64, vote: VoteOption },
+    /// Tally votes after the voting period
+    EndVoting { id: u64 },
+    /// Execute passed proposal after the timelock
+    ExecuteProposal { id: u64 },
+    /// Update configuration, only through a proposal
+    UpdateConfig {
+        quorum: Option<Decimal>,
+        threshold: Option<Decimal>,
+        voting_period: Option<u64>,
+        timelock_period: Option<u64>,
+        expiration_period: Option<u64>,
+        proposal_min_stake: Option<Uint128>,
+    },
+    /// Set viewing key
+    SetViewingKey {
+        key: String,
+        padding: Option<String>,
+    },
+}
+
+/// Message executed by the governance contract, such as `UpdateConfig` of prediction or oracle
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalMsg {
+    pub contract: ContractInfo,
+    pub msg: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteOption {
+    Yes,
+    No,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    Voting,
+    Passed,
+    Rejected,
+    Executed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    Stake {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Query current configuration
+    Config {},
+    /// Query current state
+    State {},
+    /// Query proposal by id
+    Proposal { id: u64 },
+    /// Query proposals in creation order
+    Proposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Query stake by user
+    Staker { address: HumanAddr, key: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub voting_token: ContractInfo,
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub voting_period: u64,
+    pub timelock_period: u64,
+    pub expiration_period: u64,
+    pub proposal_min_stake: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    pub proposal_count: u64,
+    pub total_staked: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalResponse {
+    pub id: u64,
+    pub proposer: HumanAddr,
+    pub title: String,
+    pub description: String,
+    pub messages: Vec<ProposalMsg>,
+    pub status: ProposalStatus,
+    pub yes_votes: Uint128,
+    pub no_votes: Uint128,
+    pub end_time: u64,
+    /// Earliest execution time once passed
+    pub execute_after: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakerResponse {
+    pub balance: Uint128,
+    /// Stake can not be withdrawn before this time
+    pub locked_until: u64,
+}
@@ -1,6 +1,9 @@
 pub mod asset;
+pub mod governance;
+pub mod math;
 pub mod oracle;
 pub mod prediction;
 pub mod rand;
 pub mod utils;
+pub mod vault;
 pub mod viewing_key;
@@ -0,0 +1,119 @@
+use cosmwasm_std::{Decimal, StdError, StdResult};
+use std::str::FromStr;
+
+/// Number of atomic units in `Decimal::one()`
+pub const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000;
+const DECIMAL_PLACES: usize = 18;
+
+/// Returns the fixed point representation of `value`.
+///
+/// `Decimal` of cosmwasm 0.10 does not expose its inner value, so it is read back
+/// from the string representation which is always exact.
+pub fn decimal_to_atomics(value: Decimal) -> u128 {
+    let repr = value.to_string();
+    let mut parts = repr.splitn(2, '.');
+    let whole = parts.next().unwrap_or("0").parse::<u128>().unwrap_or(0);
+    let fractional = match parts.next() {
+        Some(fractional) => format!("{:0<width$}", fractional, width = DECIMAL_PLACES)
+            .parse::<u128>()
+            .unwrap_or(0),
+        None => 0,
+    };
+
+    whole * DECIMAL_FRACTIONAL + fractional
+}
+
+pub fn decimal_from_atomics(atomics: u128) -> StdResult<Decimal> {
+    Decimal::from_str(&format!(
+        "{}.{:0width$}",
+        atomics / DECIMAL_FRACTIONAL,
+        atomics % DECIMAL_FRACTIONAL,
+        width = DECIMAL_PLACES
+    ))
+}
+
+pub fn decimal_sub(a: Decimal, b: Decimal) -> StdResult<Decimal> {
+    decimal_to_atomics(a)
+        .checked_sub(decimal_to_atomics(b))
+        .ok_or_else(|| StdError::generic_err("Decimal underflow"))
+        .and_then(decimal_from_atomics)
+}
+
+/// Multiplies two decimals, rounding down.
+pub fn decimal_mul(a: Decimal, b: Decimal) -> StdResult<Decimal> {
+    let a = decimal_to_atomics(a);
+    let b = decimal_to_atomics(b);
+    let (a_whole, a_fractional) = (a / DECIMAL_FRACTIONAL, a % DECIMAL_FRACTIONAL);
+    let (b_whole, b_fractional) = (b / DECIMAL_FRACTIONAL, b % DECIMAL_FRACTIONAL);
+
+    a.checked_mul(b_whole)
+        .and_then(|x| x.checked_add(a_whole.checked_mul(b_fractional)?))
+        .and_then(|x| x.checked_add(a_fractional * b_fractional / DECIMAL_FRACTIONAL))
+        .ok_or_else(|| StdError::generic_err("Decimal overflow"))
+        .and_then(decimal_from_atomics)
+}
+
+/// Divides `a` by `b`, rounding down.
+pub fn decimal_div(a: Decimal, b: Decimal) -> StdResult<Decimal> {
+    let a = decimal_to_atomics(a);
+    let b = decimal_to_atomics(b);
+    if b == 0 {
+        return Err(StdError::generic_err("Division by zero"));
+    }
+
+    let mut result = (a / b)
+        .checked_mul(DECIMAL_FRACTIONAL)
+        .ok_or_else(|| StdError::generic_err("Decimal overflow"))?;
+    let mut remainder = a % b;
+    let mut unit = DECIMAL_FRACTIONAL;
+    for _ in 0..DECIMAL_PLACES {
+        unit /= 10;
+        // remainder < b, so shifting by one digit at a time keeps it in range
+        // for any divisor below u128::MAX / 10
+        remainder = remainder
+            .checked_mul(10)
+            .ok_or_else(|| StdError::generic_err("Decimal overflow"))?;
+        result += (remainder / b) * unit;
+        remainder %= b;
+    }
+
+    decimal_from_atomics(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atomics_roundtrip() {
+        let value = Decimal::from_str("1234.000000000000000567").unwrap();
+        assert_eq!(decimal_to_atomics(value), 1234_000000000000000567);
+        assert_eq!(
+            decimal_from_atomics(1234_000000000000000567).unwrap(),
+            value
+        );
+        assert_eq!(decimal_to_atomics(Decimal::zero()), 0);
+        assert_eq!(
+            decimal_to_atomics(Decimal::percent(5)),
+            DECIMAL_FRACTIONAL / 20
+        );
+    }
+
+    #[test]
+    fn mul_and_div() {
+        let price = Decimal::from_str("60000.5").unwrap();
+        assert_eq!(
+            decimal_mul(price, Decimal::percent(102)).unwrap(),
+            Decimal::from_str("61200.51").unwrap()
+        );
+        assert_eq!(
+            decimal_div(Decimal::from_str("61200.51").unwrap(), price).unwrap(),
+            Decimal::percent(102)
+        );
+        assert_eq!(
+            decimal_div(Decimal::one(), Decimal::from_str("3").unwrap()).unwrap(),
+            Decimal::from_str("0.333333333333333333").unwrap()
+        );
+        assert!(decimal_div(Decimal::one(), Decimal::zero()).is_err());
+    }
+}
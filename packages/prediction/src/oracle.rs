@@ -2,11 +2,27 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::asset::AssetInfo;
-use cosmwasm_std::{Decimal, HumanAddr};
+use cosmwasm_std::{to_vec, Binary, Decimal, HumanAddr, StdResult, Uint128};
+use secret_toolkit::permit::Permit;
+
+/// Error of the price queries of a paused asset
+pub const FEED_PAUSED: &str = "Feed paused";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {
     pub owner: HumanAddr,
+    /// Feeds kept in the price history of each asset, 100 by default
+    pub history_retention: Option<u64>,
+    /// Fresh submissions required for a price, 1 by default
+    pub quorum: Option<u64>,
+    /// Seconds a submission stays fresh, unlimited by default
+    pub max_price_age: Option<u64>,
+    /// Address allowed to pause the feeds besides the owner
+    pub guardian: Option<HumanAddr>,
+    /// Price queries require an allowed or paying consumer, false by default
+    pub restrict_reads: Option<bool>,
+    /// Payment for the price queries of a consumer, credited to the feeders
+    pub access_fee: Option<AccessFee>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -14,32 +30,435 @@ pub struct InitMsg {
 pub enum HandleMsg {
     UpdateConfig {
         owner: Option<HumanAddr>,
+        guardian: Option<HumanAddr>,
+        /// Remove the guardian, cannot be combined with `guardian`
+        remove_guardian: Option<bool>,
+        history_retention: Option<u64>,
+        quorum: Option<u64>,
+        max_price_age: Option<u64>,
+        restrict_reads: Option<bool>,
     },
+    /// Register the asset with a single feeder, replacing its feeders
     RegisterAsset {
         asset_info: AssetInfo,
         feeder: HumanAddr,
+        /// Seconds after which the price is stale, never stale if not set
+        heartbeat: Option<u64>,
+        metadata: AssetMetadata,
+    },
+    /// Replace the given settings of a registered asset, the submissions of removed feeders are dropped
+    UpdateAsset {
+        asset_info: AssetInfo,
+        feeders: Option<Vec<HumanAddr>>,
+        heartbeat: Option<u64>,
+        metadata: Option<AssetMetadata>,
+    },
+    /// Remove the asset and its feeders, the price history is kept
+    DeregisterAsset {
+        asset_info: AssetInfo,
+    },
+    AddFeeder {
+        asset_info: AssetInfo,
+        feeder: HumanAddr,
     },
+    RemoveFeeder {
+        asset_info: AssetInfo,
+        feeder: HumanAddr,
+    },
+    /// Prices crossing the bounds of their asset are held as pending,
+    /// the assets must be quoted in `quote_currency` if given
     FeedPrice {
         prices: Vec<(AssetInfo, Decimal)>,
+        quote_currency: Option<String>,
+    },
+    /// Prices signed off-chain by feeders, relayed by anyone.
+    /// `signatures[i]` is the 64 byte secp256k1 signature of `reports[i]`
+    SubmitSignedPrices {
+        reports: Vec<SignedPriceReport>,
+        signatures: Vec<Binary>,
     },
+    /// Send `PriceHookMsg::PriceUpdated` to the contract on every new round of the asset.
+    /// A failing hook reverts the feed, so only the owner subscribes contracts
+    Subscribe {
+        asset_info: AssetInfo,
+        contract_addr: HumanAddr,
+        code_hash: String,
+    },
+    /// Stop the hooks of the contract, by the owner or the contract itself
+    Unsubscribe {
+        asset_info: AssetInfo,
+        contract_addr: HumanAddr,
+    },
+    /// Set the compressed secp256k1 public key of a feeder, removed if not given
+    SetFeederKey {
+        feeder: HumanAddr,
+        pubkey: Option<Binary>,
+    },
+    SetPriceBounds {
+        asset_info: AssetInfo,
+        bounds: PriceBounds,
+    },
+    /// Accept the pending price as the submission of its feeder
+    ConfirmPendingPrice {
+        asset_info: AssetInfo,
+    },
+    RejectPendingPrice {
+        asset_info: AssetInfo,
+    },
+    /// Reject the feeds of the asset, or of all assets if not given, by the owner or the guardian
+    Pause {
+        asset_info: Option<AssetInfo>,
+    },
+    /// Resume the feeds paused by `Pause`, by the owner only
+    Unpause {
+        asset_info: Option<AssetInfo>,
+    },
+    /// Let the consumer query prices without paying
+    AddConsumer {
+        consumer: HumanAddr,
+    },
+    /// Revoke the allowance of the consumer, its paid access is kept
+    RemoveConsumer {
+        consumer: HumanAddr,
+    },
+    /// Set the payment for the price queries, removed if not given.
+    /// The asset only changes once the feeder rewards are claimed
+    SetAccessFee {
+        access_fee: Option<AccessFee>,
+    },
+    /// Pay the access of the consumer, the sender by default, with the native fee asset sent
+    PayAccess {
+        consumer: Option<HumanAddr>,
+    },
+    /// SNIP-20 fee asset sent with `Cw20HookMsg::PayAccess`
+    Receive {
+        from: HumanAddr,
+        msg: Option<Binary>,
+        amount: Uint128,
+    },
+    /// Send the access fees credited to the sender as a feeder
+    ClaimFeederRewards {},
+    /// Viewing key of a consumer for `QueryMsg::WithViewingKey`
+    SetViewingKey {
+        key: String,
+        padding: Option<String>,
+    },
+    RevokePermit {
+        permit_name: String,
+        padding: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    PayAccess { consumer: Option<HumanAddr> },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
-    Feeder { asset_info: AssetInfo },
-    LatestPrice { asset_info: AssetInfo },
+    Feeders {
+        asset_info: AssetInfo,
+    },
+    Subscriptions {
+        asset_info: AssetInfo,
+    },
+    /// Public key of the feeder and its last used report nonce
+    FeederKey {
+        feeder: HumanAddr,
+    },
+    Asset {
+        asset_info: AssetInfo,
+    },
+    /// Registered assets ordered by asset key
+    Assets {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Lower median of the fresh submissions, updated at the latest submission,
+    /// fails with `FEED_PAUSED` while the asset is paused, and while it is halted.
+    /// Queries have no block time, so `is_stale` is evaluated at the later of `time` and the
+    /// last block time seen by the contract
+    LatestPrice {
+        asset_info: AssetInfo,
+        time: Option<u64>,
+    },
+    /// `LatestPrice` which fails if the price is stale at `time`
+    LatestPriceChecked {
+        asset_info: AssetInfo,
+        time: u64,
+    },
+    /// `LatestPrice` of each asset, failed assets return their error
+    LatestPrices {
+        asset_infos: Vec<AssetInfo>,
+        time: Option<u64>,
+    },
+    /// Price of `base` in `quote`, both assets must have the same quote currency
+    CrossRate {
+        base: AssetInfo,
+        quote: AssetInfo,
+        time: Option<u64>,
+    },
+    PriceStatus {
+        asset_info: AssetInfo,
+    },
+    /// Retained prices fed after `start_after`, oldest first
+    PriceHistory {
+        asset_info: AssetInfo,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Latest retained price fed at or before `timestamp`
+    PriceAt {
+        asset_info: AssetInfo,
+        timestamp: u64,
+    },
+    /// Latest retained round, fails while the asset is paused or halted
+    LatestRoundData {
+        asset_info: AssetInfo,
+    },
+    GetRoundData {
+        asset_info: AssetInfo,
+        round_id: u64,
+    },
+    Consumer {
+        consumer: HumanAddr,
+    },
+    /// Access fees credited to the feeder and not claimed yet
+    FeederRewards {
+        feeder: HumanAddr,
+    },
+    /// Price query of a consumer authenticated by its viewing key
+    WithViewingKey {
+        consumer: HumanAddr,
+        key: String,
+        query: Box<QueryMsg>,
+    },
+    /// Price query of a consumer authenticated by a SNIP-24 permit for the oracle
+    WithPermit {
+        permit: Permit,
+        query: Box<QueryMsg>,
+    },
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: HumanAddr,
+    pub history_retention: u64,
+    pub quorum: u64,
+    pub max_price_age: Option<u64>,
+    pub guardian: Option<HumanAddr>,
+    /// Feeds of all assets are paused
+    pub paused: bool,
+    pub restrict_reads: bool,
+    pub access_fee: Option<AccessFee>,
+}
+
+/// `amount` of `asset_info` buys `period` seconds of price queries
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccessFee {
+    pub asset_info: AssetInfo,
+    pub amount: Uint128,
+    pub period: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConsumerResponse {
+    pub allowed: bool,
+    /// Paid access ends once a price is fed at this time
+    pub paid_until: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeederRewardsResponse {
+    pub rewards: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeedersResponse {
+    pub feeders: Vec<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Subscription {
+    pub contract_addr: HumanAddr,
+    pub code_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubscriptionsResponse {
+    pub subscriptions: Vec<Subscription>,
+}
+
+/// Hook sent to the subscribers of an asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceHookMsg {
+    PriceUpdated {
+        asset_info: AssetInfo,
+        round_id: u64,
+        price: Decimal,
+        updated_at: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeederKeyResponse {
+    pub pubkey: Option<Binary>,
+    /// The next report of the feeder must have a greater nonce
+    pub nonce: u64,
+}
+
+/// Price observed by a feeder at `timestamp`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedPriceReport {
+    pub feeder: HumanAddr,
+    pub asset_info: AssetInfo,
+    pub price: Decimal,
+    pub timestamp: u64,
+    /// Increasing per feeder across all assets
+    pub nonce: u64,
+}
+
+/// Binds a report to one oracle on one chain
+#[derive(Serialize)]
+struct SigningPayload<'a> {
+    chain_id: &'a str,
+    oracle: &'a HumanAddr,
+    report: &'a SignedPriceReport,
+}
+
+impl SignedPriceReport {
+    /// Message signed by the feeder, the JSON encoding of `{chain_id, oracle, report}`
+    /// hashed with SHA-256
+    pub fn signing_bytes(&self, oracle: &HumanAddr, chain_id: &str) -> StdResult<Vec<u8>> {
+        to_vec(&SigningPayload {
+            chain_id,
+            oracle,
+            report: self,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetMetadata {
+    pub symbol: String,
+    /// Maximum fractional digits of a price, at most 18
+    pub decimals: u8,
+    /// Currency the price is quoted in, e.g. USD
+    pub quote_currency: String,
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetResponse {
+    /// Key of the asset in logs and pagination
+    pub asset_key: String,
+    pub asset_info: AssetInfo,
+    pub metadata: AssetMetadata,
+    pub feeders: Vec<HumanAddr>,
+    pub heartbeat: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetsResponse {
+    pub assets: Vec<AssetResponse>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PriceInfo {
     pub price: Decimal,
     pub last_updated_time: u64,
+    pub quote_currency: String,
+    pub decimals: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceHistoryResponse {
+    pub prices: Vec<PriceInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LatestPriceResponse {
+    pub price: Decimal,
+    pub last_updated_time: u64,
+    pub quote_currency: String,
+    pub decimals: u8,
+    /// Feeder of the median submission
+    pub feeder: HumanAddr,
+    pub heartbeat: Option<u64>,
+    pub is_stale: bool,
+    /// Round which recorded the price, none if the price changed without a new round
+    pub round_id: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LatestPriceResult {
+    pub asset_info: AssetInfo,
+    pub price: Option<LatestPriceResponse>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LatestPricesResponse {
+    pub prices: Vec<LatestPriceResult>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CrossRateResponse {
+    pub rate: Decimal,
+    /// Update time of the older price
+    pub last_updated_time: u64,
+    /// Whether either price is stale
+    pub is_stale: bool,
+}
+
+/// Aggregated price of a block, round ids start at 1 and increase per asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoundData {
+    pub round_id: u64,
+    pub price: Decimal,
+    /// Time of the oldest aggregated submission
+    pub started_at: u64,
+    /// Time of the latest aggregated submission
+    pub updated_at: u64,
+    /// Unit of the price when the round was recorded
+    pub quote_currency: String,
+    pub decimals: u8,
+}
+
+/// Circuit breaker of an asset, unset bounds are not checked
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PriceBounds {
+    /// Relative change from the latest price
+    pub max_deviation: Option<Decimal>,
+    pub min_price: Option<Decimal>,
+    pub max_price: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceStatus {
+    Active,
+    /// A pending price waits for the owner
+    Halted,
+    /// Feeds are paused globally or for the asset
+    Paused,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingPrice {
+    pub feeder: HumanAddr,
+    pub price: Decimal,
+    pub last_updated_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceStatusResponse {
+    pub status: PriceStatus,
+    pub bounds: PriceBounds,
+    pub pending_price: Option<PendingPrice>,
 }
@@ -2,6 +2,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::asset::AssetInfo;
+use crate::rand::sha_256;
 use cosmwasm_std::{Binary, Decimal, HumanAddr, Uint128};
 use secret_toolkit::permit::Permit;
 
@@ -25,6 +26,34 @@ pub struct InitMsg {
     pub grace_interval: u64,
     /// PRNG seed
     pub prng_seed: Binary,
+    /// Bucket bounds relative to the open price, empty for up/down rounds
+    pub bucket_bounds: Option<Vec<Decimal>>,
+    /// Hide bet positions until the round is locked
+    pub commit_reveal: Option<CommitRevealConfig>,
+    /// Delay admin actions, applied immediately if not given
+    pub timelock: Option<TimelockConfig>,
+    /// Quote currency the oracle must price the bet asset in, unchecked if not given
+    pub quote_currency: Option<String>,
+    /// Execute rounds on the price hook of the oracle, false by default
+    pub auto_settle: Option<bool>,
+    /// Load the state of an old instance, other handlers fail until `SealImport`
+    pub import: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CommitRevealConfig {
+    /// Seconds after lock time to reveal committed bets
+    pub reveal_interval: u64,
+    /// Share of an unrevealed bet moved to the fee, 1 forfeits the whole bet
+    pub unrevealed_penalty_rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TimelockConfig {
+    /// Seconds between queueing and executing an admin action
+    pub min_delay: u64,
+    /// Fee withdrawals above this amount are queued
+    pub withdraw_threshold: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -45,11 +74,28 @@ pub enum HandleMsg {
         fee_rate: Option<Decimal>,
         interval: Option<u64>,
         grace_interval: Option<u64>,
+        quote_currency: Option<String>,
     },
     /// Bet
     Bet { position: Position },
+    /// Bet with a hidden position, commitment is `bet_commitment(epoch, sender, position, salt)`
+    CommitBet { commitment: Binary },
+    /// Reveal the position of a committed bet after lock
+    RevealBet {
+        epoch: Uint128,
+        position: Position,
+        salt: String,
+    },
     /// Claim winner reward
     Claim { epoch: Uint128 },
+    /// Claim winner reward into the internal balance
+    ClaimToBalance { epoch: Uint128 },
+    /// Bet with the internal balance
+    BetFromBalance { position: Position, amount: Uint128 },
+    /// Deposit sent bet asset into the internal balance
+    Deposit {},
+    /// Withdraw from the internal balance, the whole balance if amount is not given
+    WithdrawBalance { amount: Option<Uint128> },
     /// Finish ongoing round, lock betting round and start new round
     ExecuteRound {},
     /// Withdraw performance fee to treasury
@@ -58,6 +104,66 @@ pub enum HandleMsg {
     Pause {},
     /// Start genesis round
     StartGenesisRound {},
+    /// Set price buckets of upcoming rounds
+    SetBuckets { bounds: Vec<Decimal> },
+    /// Enable or disable commit-reveal betting of upcoming rounds
+    UpdateCommitReveal {
+        commit_reveal: Option<CommitRevealConfig>,
+    },
+    /// Enable or disable executing rounds on the price hook of the oracle
+    UpdateAutoSettle { auto_settle: bool },
+    /// Set the viewing key of this contract at the oracle, used by its price queries.
+    /// Set again after changing the oracle
+    SetOracleViewingKey { key: String },
+    /// Price hook of the oracle, executes the ended round if auto settle is enabled
+    PriceUpdated {
+        asset_info: AssetInfo,
+        round_id: u64,
+        price: Decimal,
+        updated_at: u64,
+    },
+    /// Change the timelock of admin actions, queued by the current timelock
+    UpdateTimelock { timelock: Option<TimelockConfig> },
+    /// Apply queued admin action after its ETA
+    ExecuteQueued { id: u64 },
+    /// Drop queued admin action
+    CancelQueued { id: u64 },
+    /// Load exported records into a new instance, accepted until sealed
+    Import { data: Binary },
+    /// Stop accepting imports, the checksum must match the imported pages and the funds be transferred
+    SealImport { checksum: Binary },
+    /// Transfer the bet asset of a paused instance to the instance importing it
+    TransferFunds {
+        recipient: HumanAddr,
+        amount: Uint128,
+    },
+    /// Create a market on the price being above or below the strike price at expiry
+    CreateStrikeMarket {
+        strike_price: Decimal,
+        bet_deadline: u64,
+        expiry: u64,
+        /// Seconds after expiry to settle the market before its bets are refunded
+        grace_interval: u64,
+        /// Maximum seconds between the price at expiry and the expiry
+        max_price_age: u64,
+    },
+    /// Bet on a strike market, up is above and down is below the strike price
+    BetStrikeMarket { id: u64, position: Position },
+    /// Settle strike market with the oracle price at expiry, once a later price is fed
+    SettleStrikeMarket { id: u64 },
+    /// Claim strike market reward
+    ClaimStrikeMarket { id: u64 },
+    /// Bet every round from the sent budget, either on a fixed position or by strategy
+    SetStandingOrder {
+        position: Option<Position>,
+        strategy: Option<Strategy>,
+        amount_per_round: Uint128,
+        max_rounds: u64,
+    },
+    /// Cancel standing order and refund the remaining budget
+    CancelStandingOrder {},
+    /// Continue placing standing order bets of the betting round
+    ProcessStandingOrders { limit: Option<u32> },
     /// Create viewing key
     CreateViewingKey {
         entropy: String,
@@ -78,7 +184,23 @@ pub enum HandleMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
-    Bet { position: Position },
+    Bet {
+        position: Position,
+    },
+    Deposit {},
+    CommitBet {
+        commitment: Binary,
+    },
+    BetStrikeMarket {
+        id: u64,
+        position: Position,
+    },
+    SetStandingOrder {
+        position: Option<Position>,
+        strategy: Option<Strategy>,
+        amount_per_round: Uint128,
+        max_rounds: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -96,6 +218,34 @@ pub enum QueryMsg {
         user: HumanAddr,
         key: String,
     },
+    /// Query strike market by id
+    StrikeMarket { id: u64 },
+    /// Query strike markets in creation order
+    StrikeMarkets {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Query strike market bet by user
+    StrikeBet {
+        id: u64,
+        user: HumanAddr,
+        key: String,
+    },
+    /// Query internal balance by user
+    Balance { user: HumanAddr, key: String },
+    /// Query standing order by user
+    StandingOrder { user: HumanAddr, key: String },
+    /// Query standing order progress of the betting round
+    StandingOrderProgress {},
+    /// Query queued admin actions in queueing order
+    PendingActions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Export records for a new instance, authenticated by the owner viewing key
+    Export { key: String, page: ExportPage },
+    /// Query import progress of this instance
+    ImportStatus {},
     /// Query with permit
     WithPermit {
         permit: Permit,
@@ -103,10 +253,48 @@ pub enum QueryMsg {
     },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportPage {
+    Config {},
+    State {},
+    /// Rounds by epoch
+    Rounds {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Round and strike market bets in placing order
+    Bets {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Viewing key hash, internal balance and standing order of each account
+    Accounts {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    StrikeMarkets {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    QueuedActions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Permits revoked by their signers
+    RevokedPermits {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryWithPermit {
     Bet { epoch: Uint128 },
+    StrikeBet { id: u64 },
+    StandingOrder {},
+    Balance {},
 }
 
 // We define a custom struct for each query response
@@ -122,6 +310,95 @@ pub struct ConfigResponse {
     pub fee_rate: Decimal,
     pub interval: u64,
     pub grace_interval: u64,
+    pub bucket_bounds: Vec<Decimal>,
+    pub commit_reveal: Option<CommitRevealConfig>,
+    pub timelock: Option<TimelockConfig>,
+    pub quote_currency: Option<String>,
+    pub auto_settle: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BalanceResponse {
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportResponse {
+    /// Records to pass to `Import` as they are
+    pub data: Binary,
+    /// sha256 of the data
+    pub checksum: Binary,
+    /// Index of the last record, the next page starts after it
+    pub last: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ImportStatusResponse {
+    pub sealed: bool,
+    pub pages: u64,
+    /// sha256 chain of the page checksums, `sha256(checksum || page_checksum)`
+    pub checksum: Binary,
+    /// Bet asset balance required before sealing
+    pub funds: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StandingOrderProgressResponse {
+    pub epoch: Uint128,
+    /// Standing orders already processed for the betting round
+    pub processed: u64,
+    pub total: u64,
+}
+
+/// Admin action waiting for its timelock
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QueuedAction {
+    pub id: u64,
+    pub action: AdminAction,
+    /// Earliest execution time
+    pub eta: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    UpdateConfig {
+        owner_addr: Option<HumanAddr>,
+        operator_addr: Option<HumanAddr>,
+        treasury_addr: Option<HumanAddr>,
+        oracle_addr: Option<HumanAddr>,
+        oracle_code_hash: Option<String>,
+        fee_rate: Option<Decimal>,
+        interval: Option<u64>,
+        grace_interval: Option<u64>,
+        quote_currency: Option<String>,
+    },
+    UpdateTimelock {
+        timelock: Option<TimelockConfig>,
+    },
+    /// Withdraw up to the amount of the performance fee
+    Withdraw {
+        amount: Uint128,
+    },
+    /// Queued by the operator, which also executes or cancels it
+    SetBuckets {
+        bounds: Vec<Decimal>,
+    },
+    UpdateCommitReveal {
+        commit_reveal: Option<CommitRevealConfig>,
+    },
+    UpdateAutoSettle {
+        auto_settle: bool,
+    },
+    /// The key is hidden in the pending actions
+    SetOracleViewingKey {
+        key: String,
+    },
+    /// Executed only while the instance is paused
+    TransferFunds {
+        recipient: HumanAddr,
+        amount: Uint128,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -129,6 +406,29 @@ pub struct ConfigResponse {
 pub enum Position {
     Up,
     Down,
+    /// Price bucket index, counted from the lowest range
+    Bucket(u8),
+}
+
+impl Position {
+    /// Outcome index of the position, up and down are outcomes 0 and 1
+    pub fn outcome(&self) -> u8 {
+        match self {
+            Position::Up => 0,
+            Position::Down => 1,
+            Position::Bucket(index) => *index,
+        }
+    }
+}
+
+/// Standing order strategy based on the last settled round
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Bet on the winning outcome
+    FollowWinner,
+    /// Bet against the winning outcome, up/down rounds only
+    AgainstWinner,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -138,12 +438,31 @@ pub struct State {
     pub paused: bool,
 }
 
+/// Commitment of a hidden bet, sha256 of the epoch, the length-prefixed bettor address,
+/// the outcome index and the salt
+pub fn bet_commitment(epoch: Uint128, user: &HumanAddr, position: &Position, salt: &str) -> Binary {
+    let user = user.as_str().as_bytes();
+    Binary(
+        sha_256(
+            &[
+                &epoch.u128().to_be_bytes()[..],
+                &(user.len() as u32).to_be_bytes(),
+                user,
+                &[position.outcome()],
+                salt.as_bytes(),
+            ]
+            .concat(),
+        )
+        .to_vec(),
+    )
+}
+
 impl ToString for Position {
     fn to_string(&self) -> String {
-        if self == &Position::Up {
-            String::from("up")
-        } else {
-            String::from("down")
+        match self {
+            Position::Up => String::from("up"),
+            Position::Down => String::from("down"),
+            Position::Bucket(index) => format!("bucket_{}", index),
         }
     }
 }
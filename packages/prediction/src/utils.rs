@@ -1,4 +1,7 @@
 use crate::viewing_key::VIEWING_KEY_SIZE;
+use cosmwasm_std::HumanAddr;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::convert::TryInto;
 use subtle::ConstantTimeEq;
@@ -13,3 +16,9 @@ pub fn create_hashed_password(s1: &str) -> [u8; VIEWING_KEY_SIZE] {
         .try_into()
         .expect("Wrong password length")
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractInfo {
+    pub address: HumanAddr,
+    pub code_hash: String,
+}
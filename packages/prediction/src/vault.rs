@@ -0,0 +1,107 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::AssetInfo;
+use crate::prediction::Position;
+use crate::utils::ContractInfo;
+use cosmwasm_std::{Binary, Decimal, HumanAddr, Uint128};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InitMsg {
+    /// Operator address which executes the strategy
+    pub operator_addr: HumanAddr,
+    /// Prediction contract to bet on
+    pub prediction: ContractInfo,
+    /// Bet asset of the prediction contract
+    pub bet_asset: AssetInfo,
+    /// SNIP-20 vault share token, the vault must be a minter
+    pub share_token: ContractInfo,
+    pub strategy: Strategy,
+    /// Share of the net asset value to bet each round
+    pub bet_rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Bet on the side with the smaller pool of the betting round
+    Minority,
+    /// Bet on the winning side of the last settled round
+    Momentum,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleMsg {
+    /// Deposit bet asset from SNIP-20 or withdraw with share tokens
+    Receive {
+        from: HumanAddr,
+        msg: Option<Binary>,
+        amount: Uint128,
+    },
+    /// Deposit native bet asset
+    Deposit {},
+    /// Claim finished rounds and bet on the betting round
+    ExecuteStrategy {},
+    /// Update configuration
+    UpdateConfig {
+        owner_addr: Option<HumanAddr>,
+        operator_addr: Option<HumanAddr>,
+        strategy: Option<Strategy>,
+        bet_rate: Option<Decimal>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Sent with the bet asset
+    Deposit {},
+    /// Sent with share tokens
+    Withdraw {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Query current configuration
+    Config {},
+    /// Query share supply and open bets
+    State {},
+    /// Query net asset value
+    Nav {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner_addr: HumanAddr,
+    pub operator_addr: HumanAddr,
+    pub prediction: ContractInfo,
+    pub bet_asset: AssetInfo,
+    pub share_token: ContractInfo,
+    pub strategy: Strategy,
+    pub bet_rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OpenBet {
+    pub epoch: Uint128,
+    pub amount: Uint128,
+    pub position: Position,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    pub total_shares: Uint128,
+    /// Bets which are not claimed yet
+    pub open_bets: Vec<OpenBet>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NavResponse {
+    /// Idle balance plus open bets, valued by their settled rounds or at cost
+    pub nav: Uint128,
+    /// Balance held by the vault
+    pub idle: Uint128,
+    pub total_shares: Uint128,
+}
@@ -0,0 +1,196 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::Command;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClientError {
+    /// The contract rejected the message
+    Contract(String),
+    /// The node could not be reached or answered unexpectedly
+    Transport(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientError::Contract(msg) => write!(f, "contract error: {}", msg),
+            ClientError::Transport(msg) => write!(f, "transport error: {}", msg),
+        }
+    }
+}
+
+/// Client running `secretcli`, which holds the signing key
+#[derive(Clone, Debug)]
+pub struct SecretCli {
+    pub binary: String,
+    pub contract: String,
+    /// Key name or address signing the transactions
+    pub from: String,
+    pub node: Option<String>,
+    pub chain_id: Option<String>,
+    pub gas: u64,
+}
+
+#[derive(Deserialize)]
+struct TxResponse {
+    code: Option<u32>,
+    raw_log: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StatusResponse {
+    #[serde(alias = "SyncInfo")]
+    sync_info: SyncInfo,
+}
+
+#[derive(Deserialize)]
+struct SyncInfo {
+    latest_block_time: String,
+}
+
+impl SecretCli {
+    fn run(&self, args: Vec<String>) -> Result<Vec<u8>, ClientError> {
+        let mut command = Command::new(&self.binary);
+        command.args(args);
+        if let Some(node) = &self.node {
+            command.arg("--node").arg(node);
+        }
+
+        let output = command
+            .output()
+            .map_err(|err| ClientError::Transport(err.to_string()))?;
+        if output.status.success() {
+            return Ok(output.stdout);
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        Err(classify(stderr))
+    }
+
+    pub fn latest_block_time(&self) -> Result<u64, ClientError> {
+        let stdout = self.run(vec!["status".to_string()])?;
+        let status: StatusResponse = serde_json::from_slice(&stdout)
+            .map_err(|err| ClientError::Transport(err.to_string()))?;
+
+        parse_rfc3339(&status.sync_info.latest_block_time).ok_or_else(|| {
+            ClientError::Transport(format!(
+                "invalid block time {}",
+                status.sync_info.latest_block_time
+            ))
+        })
+    }
+
+    pub fn query_contract<M: Serialize, T: DeserializeOwned>(
+        &self,
+        msg: &M,
+    ) -> Result<T, ClientError> {
+        let msg =
+            serde_json::to_string(msg).map_err(|err| ClientError::Transport(err.to_string()))?;
+        let stdout = self.run(self.query_args(msg))?;
+
+        serde_json::from_slice(&stdout).map_err(|err| ClientError::Transport(err.to_string()))
+    }
+
+    pub fn execute_contract<M: Serialize>(&self, msg: &M) -> Result<(), ClientError> {
+        let msg =
+            serde_json::to_string(msg).map_err(|err| ClientError::Transport(err.to_string()))?;
+        let stdout = self.run(self.execute_args(msg))?;
+
+        parse_tx_response(&stdout)
+    }
+
+    pub(crate) fn query_args(&self, msg: String) -> Vec<String> {
+        vec![
+            "query".to_string(),
+            "compute".to_string(),
+            "query".to_string(),
+            self.contract.clone(),
+            msg,
+        ]
+    }
+
+    pub(crate) fn execute_args(&self, msg: String) -> Vec<String> {
+        let mut args = vec![
+            "tx".to_string(),
+            "compute".to_string(),
+            "execute".to_string(),
+            self.contract.clone(),
+            msg,
+            "--from".to_string(),
+            self.from.clone(),
+            "--gas".to_string(),
+            self.gas.to_string(),
+            "--broadcast-mode".to_string(),
+            "block".to_string(),
+            "--output".to_string(),
+            "json".to_string(),
+            "-y".to_string(),
+        ];
+        if let Some(chain_id) = &self.chain_id {
+            args.push("--chain-id".to_string());
+            args.push(chain_id.clone());
+        }
+
+        args
+    }
+}
+
+/// A transaction included with a non zero code failed in the contract or the node
+pub(crate) fn parse_tx_response(stdout: &[u8]) -> Result<(), ClientError> {
+    let res: TxResponse =
+        serde_json::from_slice(stdout).map_err(|err| ClientError::Transport(err.to_string()))?;
+    match res.code {
+        None | Some(0) => Ok(()),
+        Some(_) => Err(classify(res.raw_log.unwrap_or_default())),
+    }
+}
+
+/// Errors returned by the contract are reported with its message, the rest is a node failure
+pub(crate) fn classify(log: String) -> ClientError {
+    if log.contains("generic_err")
+        || log.contains("unauthorized")
+        || log.contains("failed to execute message")
+    {
+        ClientError::Contract(log)
+    } else {
+        ClientError::Transport(log)
+    }
+}
+
+/// Seconds since the unix epoch of a UTC time such as `2022-03-01T12:00:05.123Z`
+pub fn parse_rfc3339(time: &str) -> Option<u64> {
+    let time = time.trim_end_matches('Z');
+    let (date, clock) = time.split_at(time.find('T')?);
+    let clock = clock[1..].split('.').next()?;
+
+    let date: Vec<i64> = date
+        .split('-')
+        .map(|x| x.parse().ok())
+        .collect::<Option<_>>()?;
+    let clock: Vec<u64> = clock
+        .split(':')
+        .map(|x| x.parse().ok())
+        .collect::<Option<_>>()?;
+    if date.len() != 3 || clock.len() != 3 {
+        return None;
+    }
+
+    // Days from the civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let (year, month, day) = (date[0], date[1], date[2]);
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    if days < 0 {
+        return None;
+    }
+
+    Some(days as u64 * 86_400 + clock[0] * 3_600 + clock[1] * 60 + clock[2])
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1 @@
+pub mod secretcli_test;
@@ -0,0 +1,112 @@
+use crate::{classify, parse_rfc3339, parse_tx_response, ClientError, SecretCli};
+
+fn cli(chain_id: Option<&str>) -> SecretCli {
+    SecretCli {
+        binary: "secretcli".to_string(),
+        contract: "secret1contract".to_string(),
+        from: "keeper".to_string(),
+        node: None,
+        chain_id: chain_id.map(|chain_id| chain_id.to_string()),
+        gas: 200_000,
+    }
+}
+
+#[test]
+fn test_parse_block_time() {
+    assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), Some(0));
+    assert_eq!(
+        parse_rfc3339("2022-03-01T12:00:05.123456Z"),
+        Some(1_646_136_005)
+    );
+    assert_eq!(parse_rfc3339("2022-03-01"), None);
+}
+
+#[test]
+fn test_query_args() {
+    assert_eq!(
+        cli(None).query_args("{\"state\":{}}".to_string()),
+        vec![
+            "query",
+            "compute",
+            "query",
+            "secret1contract",
+            "{\"state\":{}}"
+        ]
+    );
+}
+
+#[test]
+fn test_execute_args() {
+    let msg = "{\"execute_round\":{}}".to_string();
+    let args = vec![
+        "tx",
+        "compute",
+        "execute",
+        "secret1contract",
+        "{\"execute_round\":{}}",
+        "--from",
+        "keeper",
+        "--gas",
+        "200000",
+        "--broadcast-mode",
+        "block",
+        "--output",
+        "json",
+        "-y",
+    ];
+    assert_eq!(cli(None).execute_args(msg.clone()), args);
+
+    let mut with_chain_id = args;
+    with_chain_id.extend(&["--chain-id", "secret-4"]);
+    assert_eq!(cli(Some("secret-4")).execute_args(msg), with_chain_id);
+}
+
+#[test]
+fn test_classify_errors() {
+    let contract_errors = [
+        "failed to execute message; message index: 0: encrypted: {\"generic_err\":{\"msg\":\"Price not updated\"}}",
+        "{\"unauthorized\":{}}",
+        "failed to execute message; message index: 0: Unauthorized: execute wasm contract failed",
+    ];
+    for log in contract_errors.iter() {
+        assert_eq!(
+            classify(log.to_string()),
+            ClientError::Contract(log.to_string())
+        );
+    }
+
+    let log = "Error: post failed: connection refused";
+    assert_eq!(
+        classify(log.to_string()),
+        ClientError::Transport(log.to_string())
+    );
+}
+
+#[test]
+fn test_parse_tx_response() {
+    assert_eq!(
+        parse_tx_response(b"{\"code\":0,\"raw_log\":\"[]\"}"),
+        Ok(())
+    );
+    assert_eq!(parse_tx_response(b"{\"txhash\":\"ABC\"}"), Ok(()));
+
+    let res = parse_tx_response(b"{\"code\":3,\"raw_log\":\"{\\\"generic_err\\\":{}}\"}");
+    assert_eq!(
+        res,
+        Err(ClientError::Contract("{\"generic_err\":{}}".to_string()))
+    );
+
+    let res =
+        parse_tx_response(b"{\"code\":11,\"raw_log\":\"out of gas in location: WritePerByte\"}");
+    assert_eq!(
+        res,
+        Err(ClientError::Transport(
+            "out of gas in location: WritePerByte".to_string()
+        ))
+    );
+
+    assert!(matches!(
+        parse_tx_response(b"not json"),
+        Err(ClientError::Transport(_))
+    ));
+}
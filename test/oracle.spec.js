@@ -26,6 +26,13 @@ describe('oracle', () => {
 
       await expect(oracleContract.query.config()).to.respondWith({
         owner: owner.account.address,
+        history_retention: 100,
+        quorum: 1,
+        max_price_age: null,
+        guardian: null,
+        paused: false,
+        restrict_reads: false,
+        access_fee: null,
       });
     });
   });
@@ -48,6 +55,13 @@ describe('oracle', () => {
 
       await expect(oracleContract.query.config()).to.respondWith({
         owner: alice.account.address,
+        history_retention: 100,
+        quorum: 1,
+        max_price_age: null,
+        guardian: null,
+        paused: false,
+        restrict_reads: false,
+        access_fee: null,
       });
 
       checkLogs(ex_response, {
@@ -75,6 +89,8 @@ describe('oracle', () => {
             },
           },
           alice.account.address,
+          null,
+          { symbol: 'SCRT', decimals: 6, quote_currency: 'USD' },
         ),
       ).to.be.revertedWith('unauthorized');
     });
@@ -88,15 +104,17 @@ describe('oracle', () => {
           },
         },
         alice.account.address,
+        null,
+        { symbol: 'SCRT', decimals: 6, quote_currency: 'USD' },
       );
 
       await expect(
-        oracleContract.query.feeder({
+        oracleContract.query.feeders({
           native_token: {
             denom: 'uscrt',
           },
         }),
-      ).to.respondWith(alice.account.address);
+      ).to.respondWith({ feeders: [alice.account.address] });
 
       checkLogs(ex_response, {
         action: 'register_asset',
@@ -116,17 +134,19 @@ describe('oracle', () => {
           },
         },
         alice.account.address,
+        null,
+        { symbol: 'TEST', decimals: 6, quote_currency: 'USD' },
       );
 
       await expect(
-        oracleContract.query.feeder({
+        oracleContract.query.feeders({
           token: {
             contract_addr: 'secret10xy2dz4df5rrqsjf8wjreh6ejrqwt6y7a4gunn',
             token_code_hash: 'test_token_code_hash',
             viewing_key: 'test_viewing_key',
           },
         }),
-      ).to.respondWith(alice.account.address);
+      ).to.respondWith({ feeders: [alice.account.address] });
 
       checkLogs(ex_response, {
         action: 'register_asset',
@@ -134,6 +154,16 @@ describe('oracle', () => {
         feeder: alice.account.address,
       });
     });
+
+    it('list registered assets', async () => {
+      const { assets } = await oracleContract.query.assets();
+      expect(assets.map((asset) => asset.asset_key)).to.deep.equal([
+        'native_token_uscrt',
+        'snip20_token_secret10xy2dz4df5rrqsjf8wjreh6ejrqwt6y7a4gunn',
+      ]);
+      expect(assets[0].metadata.symbol).to.be.equal('SCRT');
+      expect(assets[0].feeders).to.deep.equal([alice.account.address]);
+    });
   });
 
   describe('feed_price', () => {
@@ -189,14 +219,37 @@ describe('oracle', () => {
         },
       });
       expect(latestPrice.price).to.be.equal('10.3');
+      expect(latestPrice.quote_currency).to.be.equal('USD');
+      expect(latestPrice.decimals).to.be.equal(6);
       expect(latestPrice.last_updated_time)
         .to.be.greaterThanOrEqual(currentTime - 2)
         .to.be.lessThanOrEqual(currentTime + 2);
+      expect(latestPrice.feeder).to.be.equal(alice.account.address);
+      expect(latestPrice.is_stale).to.be.equal(false);
+      expect(latestPrice.round_id).to.be.equal(1);
+
+      const roundData = await oracleContract.query.latest_round_data({
+        native_token: {
+          denom: 'uscrt',
+        },
+      });
+      expect(roundData.round_id).to.be.equal(1);
+      expect(roundData.price).to.be.equal('10.3');
+      expect(roundData.updated_at).to.be.equal(latestPrice.last_updated_time);
+
+      const { prices } = await oracleContract.query.latest_prices([
+        { native_token: { denom: 'uscrt' } },
+        { native_token: { denom: 'uatom' } },
+      ]);
+      expect(prices[0].price.price).to.be.equal('10.3');
+      expect(prices[1].price).to.be.equal(null);
+      expect(prices[1].error).to.be.equal('Asset not registered');
 
       checkLogs(ex_response, {
         action: 'feed_price',
         asset_key: 'native_token_uscrt',
         price: '10.3',
+        round_id: '1',
       });
     });
   });
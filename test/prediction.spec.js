@@ -79,6 +79,8 @@ describe('prediction', () => {
       { account: owner },
       assetInfo,
       owner.account.address,
+      null,
+      { symbol: 'SCRT', decimals: 6, quote_currency: 'USD' },
     );
 
     predictionContract = new Contract('price_prediction');
@@ -177,6 +179,11 @@ describe('prediction', () => {
         fee_rate: feeRate.toString(),
         interval,
         grace_interval: graceInterval,
+        bucket_bounds: [],
+        commit_reveal: null,
+        timelock: null,
+        quote_currency: null,
+        auto_settle: false,
       });
 
       await expect(predictionContract.query.state()).to.respondWith({
@@ -200,6 +207,7 @@ describe('prediction', () => {
           null,
           null,
           null,
+          null,
         ),
       ).to.be.revertedWith('unauthorized');
     });
@@ -216,6 +224,7 @@ describe('prediction', () => {
           null,
           null,
           null,
+          null,
         ),
       ).to.be.revertedWith('Invalid fee rate');
     });
@@ -232,6 +241,7 @@ describe('prediction', () => {
           null,
           null,
           null,
+          null,
         ),
       ).to.be.revertedWith('Invalid grace interval');
 
@@ -246,10 +256,28 @@ describe('prediction', () => {
           null,
           null,
           null,
+          null,
         ),
       ).to.be.revertedWith('Invalid grace interval');
     });
 
+    it('fail if the oracle quote currency mismatches', async () => {
+      await expect(
+        predictionContract.tx.update_config(
+          { account: owner },
+          null,
+          null,
+          null,
+          null,
+          null,
+          null,
+          null,
+          'EUR',
+          null,
+        ),
+      ).to.be.revertedWith('Oracle quote currency mismatch');
+    });
+
     it('update config by owner', async () => {
       const ex_response = await predictionContract.tx.update_config(
         { account: owner },
@@ -260,6 +288,7 @@ describe('prediction', () => {
         alice.account.address, // new oracle addr
         'oracle_new_code_hash',
         bob.account.address, // new owner addr
+        null,
         owner.account.address, // new treasury addr
       );
 
@@ -274,6 +303,11 @@ describe('prediction', () => {
         fee_rate: '0.5',
         interval: 25,
         grace_interval: 18,
+        bucket_bounds: [],
+        commit_reveal: null,
+        timelock: null,
+        quote_currency: null,
+        auto_settle: false,
       });
 
       checkLogs(ex_response, {
@@ -326,8 +360,8 @@ describe('prediction', () => {
       expect(genesisRound.close_price).to.be.equal(null);
       expect(genesisRound.total_amount).to.be.equal('0');
       expect(genesisRound.reward_amount).to.be.equal('0');
-      expect(genesisRound.up_amount).to.be.equal('0');
-      expect(genesisRound.down_amount).to.be.equal('0');
+      expect(genesisRound.outcome_amounts[0]).to.be.equal('0');
+      expect(genesisRound.outcome_amounts[1]).to.be.equal('0');
       expect(genesisRound.is_genesis).to.be.equal(true);
 
       expect(currentRound.start_time).to.be.equal(genesisRound.lock_time);
@@ -341,8 +375,8 @@ describe('prediction', () => {
       expect(currentRound.close_price).to.be.equal(null);
       expect(currentRound.total_amount).to.be.equal('0');
       expect(currentRound.reward_amount).to.be.equal('0');
-      expect(currentRound.up_amount).to.be.equal('0');
-      expect(currentRound.down_amount).to.be.equal('0');
+      expect(currentRound.outcome_amounts[0]).to.be.equal('0');
+      expect(currentRound.outcome_amounts[1]).to.be.equal('0');
       expect(currentRound.is_genesis).to.be.equal(false);
 
       checkLogs(ex_response, {
@@ -473,8 +507,8 @@ describe('prediction', () => {
 
       expect(currentRound.total_amount).to.be.equal(amount);
       expect(currentRound.reward_amount).to.be.equal('0');
-      expect(currentRound.up_amount).to.be.equal(amount);
-      expect(currentRound.down_amount).to.be.equal('0');
+      expect(currentRound.outcome_amounts[0]).to.be.equal(amount);
+      expect(currentRound.outcome_amounts[1]).to.be.equal('0');
 
       checkLogs(ex_response, {
         action: 'bet',
@@ -514,8 +548,8 @@ describe('prediction', () => {
 
       expect(currentRound.total_amount).to.be.equal(amount);
       expect(currentRound.reward_amount).to.be.equal('0');
-      expect(currentRound.up_amount).to.be.equal('0');
-      expect(currentRound.down_amount).to.be.equal(amount);
+      expect(currentRound.outcome_amounts[0]).to.be.equal('0');
+      expect(currentRound.outcome_amounts[1]).to.be.equal(amount);
 
       checkLogs(ex_response, {
         action: 'bet',
@@ -567,8 +601,8 @@ describe('prediction', () => {
 
       expect(currentRound.total_amount).to.be.equal('1600');
       expect(currentRound.reward_amount).to.be.equal('0');
-      expect(currentRound.up_amount).to.be.equal('1000');
-      expect(currentRound.down_amount).to.be.equal('600');
+      expect(currentRound.outcome_amounts[0]).to.be.equal('1000');
+      expect(currentRound.outcome_amounts[1]).to.be.equal('600');
     });
 
     it('fail after lock time', async () => {
@@ -686,8 +720,8 @@ describe('prediction', () => {
       expect(currentRound.close_price).to.be.equal(null);
       expect(currentRound.total_amount).to.be.equal('1600');
       expect(currentRound.reward_amount).to.be.equal('0');
-      expect(currentRound.up_amount).to.be.equal('1000');
-      expect(currentRound.down_amount).to.be.equal('600');
+      expect(currentRound.outcome_amounts[0]).to.be.equal('1000');
+      expect(currentRound.outcome_amounts[1]).to.be.equal('600');
       expect(currentRound.is_genesis).to.be.equal(false);
 
       const newRound = await predictionContract.query.round('3');
@@ -700,8 +734,8 @@ describe('prediction', () => {
       expect(newRound.close_price).to.be.equal(null);
       expect(newRound.total_amount).to.be.equal('0');
       expect(newRound.reward_amount).to.be.equal('0');
-      expect(newRound.up_amount).to.be.equal('0');
-      expect(newRound.down_amount).to.be.equal('0');
+      expect(newRound.outcome_amounts[0]).to.be.equal('0');
+      expect(newRound.outcome_amounts[1]).to.be.equal('0');
       expect(newRound.is_genesis).to.be.equal(false);
 
       await expect(predictionContract.query.state()).to.respondWith({
@@ -755,8 +789,8 @@ describe('prediction', () => {
       expect(finishedRound.total_amount).to.be.equal('1600');
       const fee = '48';
       expect(finishedRound.reward_amount).to.be.equal('1552');
-      expect(finishedRound.up_amount).to.be.equal('1000');
-      expect(finishedRound.down_amount).to.be.equal('600');
+      expect(finishedRound.outcome_amounts[0]).to.be.equal('1000');
+      expect(finishedRound.outcome_amounts[1]).to.be.equal('600');
       expect(finishedRound.is_genesis).to.be.equal(false);
 
       await expect(predictionContract.query.state()).to.respondWith({
@@ -810,8 +844,8 @@ describe('prediction', () => {
       expect(finishedRound.total_amount).to.be.equal('1600');
       const fee = '48';
       expect(finishedRound.reward_amount).to.be.equal('1552');
-      expect(finishedRound.up_amount).to.be.equal('1000');
-      expect(finishedRound.down_amount).to.be.equal('600');
+      expect(finishedRound.outcome_amounts[0]).to.be.equal('1000');
+      expect(finishedRound.outcome_amounts[1]).to.be.equal('600');
       expect(finishedRound.is_genesis).to.be.equal(false);
 
       await expect(predictionContract.query.state()).to.respondWith({
@@ -864,8 +898,8 @@ describe('prediction', () => {
       expect(finishedRound.total_amount).to.be.equal('1020');
       const fee = '20';
       expect(finishedRound.reward_amount).to.be.equal('1000');
-      expect(finishedRound.up_amount).to.be.equal('1000');
-      expect(finishedRound.down_amount).to.be.equal('20');
+      expect(finishedRound.outcome_amounts[0]).to.be.equal('1000');
+      expect(finishedRound.outcome_amounts[1]).to.be.equal('20');
       expect(finishedRound.is_genesis).to.be.equal(false);
 
       await expect(predictionContract.query.state()).to.respondWith({
@@ -918,8 +952,8 @@ describe('prediction', () => {
       expect(finishedRound.total_amount).to.be.equal('1020');
       const fee = '20';
       expect(finishedRound.reward_amount).to.be.equal('1000');
-      expect(finishedRound.up_amount).to.be.equal('20');
-      expect(finishedRound.down_amount).to.be.equal('1000');
+      expect(finishedRound.outcome_amounts[0]).to.be.equal('20');
+      expect(finishedRound.outcome_amounts[1]).to.be.equal('1000');
       expect(finishedRound.is_genesis).to.be.equal(false);
 
       await expect(predictionContract.query.state()).to.respondWith({
@@ -970,8 +1004,8 @@ describe('prediction', () => {
       expect(finishedRound.close_price).to.be.equal(closePrice);
       expect(finishedRound.total_amount).to.be.equal('1020');
       expect(finishedRound.reward_amount).to.be.equal('0');
-      expect(finishedRound.up_amount).to.be.equal('1000');
-      expect(finishedRound.down_amount).to.be.equal('20');
+      expect(finishedRound.outcome_amounts[0]).to.be.equal('1000');
+      expect(finishedRound.outcome_amounts[1]).to.be.equal('20');
       expect(finishedRound.is_genesis).to.be.equal(false);
 
       await expect(predictionContract.query.state()).to.respondWith({
@@ -1021,8 +1055,8 @@ describe('prediction', () => {
       expect(finishedRound.close_price).to.be.equal(closePrice);
       expect(finishedRound.total_amount).to.be.equal('1000');
       expect(finishedRound.reward_amount).to.be.equal('0');
-      expect(finishedRound.up_amount).to.be.equal('1000');
-      expect(finishedRound.down_amount).to.be.equal('0');
+      expect(finishedRound.outcome_amounts[0]).to.be.equal('1000');
+      expect(finishedRound.outcome_amounts[1]).to.be.equal('0');
       expect(finishedRound.is_genesis).to.be.equal(false);
 
       await expect(predictionContract.query.state()).to.respondWith({
@@ -1072,8 +1106,8 @@ describe('prediction', () => {
       expect(finishedRound.close_price).to.be.equal(closePrice);
       expect(finishedRound.total_amount).to.be.equal('1000');
       expect(finishedRound.reward_amount).to.be.equal('0');
-      expect(finishedRound.up_amount).to.be.equal('0');
-      expect(finishedRound.down_amount).to.be.equal('1000');
+      expect(finishedRound.outcome_amounts[0]).to.be.equal('0');
+      expect(finishedRound.outcome_amounts[1]).to.be.equal('1000');
       expect(finishedRound.is_genesis).to.be.equal(false);
 
       await expect(predictionContract.query.state()).to.respondWith({
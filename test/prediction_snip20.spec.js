@@ -121,6 +121,8 @@ describe.only('prediction with snip20', () => {
       { account: owner },
       assetInfo,
       owner.account.address,
+      null,
+      { symbol: 'SEFI', decimals: 6, quote_currency: 'USD' },
     );
 
     predictionContract = new Contract('price_prediction');
@@ -209,8 +211,8 @@ describe.only('prediction with snip20', () => {
 
       expect(currentRound.total_amount).to.be.equal(amount);
       expect(currentRound.reward_amount).to.be.equal('0');
-      expect(currentRound.up_amount).to.be.equal(amount);
-      expect(currentRound.down_amount).to.be.equal('0');
+      expect(currentRound.outcome_amounts[0]).to.be.equal(amount);
+      expect(currentRound.outcome_amounts[1]).to.be.equal('0');
     });
   });
 
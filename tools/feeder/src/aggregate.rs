@@ -0,0 +1,61 @@
+use cosmwasm_std::Decimal;
+
+use prediction::math::{decimal_div, decimal_from_atomics, decimal_sub, decimal_to_atomics};
+
+/// Middle price, the mean of both middle prices for an even count
+pub fn median(prices: &[Decimal]) -> Option<Decimal> {
+    let mut sorted = prices.to_vec();
+    sorted.sort();
+
+    let mid = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(sorted[mid]),
+        _ => decimal_div(
+            sorted[mid - 1] + sorted[mid],
+            Decimal::from_ratio(2u64, 1u64),
+        )
+        .ok(),
+    }
+}
+
+/// `|price - reference| / reference`, `None` for a zero reference
+pub fn relative_deviation(price: Decimal, reference: Decimal) -> Option<Decimal> {
+    let diff = if price > reference {
+        decimal_sub(price, reference)
+    } else {
+        decimal_sub(reference, price)
+    };
+
+    decimal_div(diff.ok()?, reference).ok()
+}
+
+/// Median of the prices within `max_deviation` of the median of all prices,
+/// `None` if less than `min_sources` prices are left
+pub fn aggregate(
+    prices: &[Decimal],
+    max_deviation: Decimal,
+    min_sources: usize,
+) -> Option<Decimal> {
+    let reference = median(prices)?;
+    let accepted: Vec<Decimal> = prices
+        .iter()
+        .copied()
+        .filter(|price| {
+            relative_deviation(*price, reference)
+                .map_or(false, |deviation| deviation <= max_deviation)
+        })
+        .collect();
+
+    if accepted.len() < min_sources.max(1) {
+        return None;
+    }
+
+    median(&accepted)
+}
+
+/// Price rounded down to `decimals` fractional digits
+pub fn truncate(price: Decimal, decimals: u8) -> Decimal {
+    let unit = 10u128.pow(18u32.saturating_sub(decimals as u32));
+    decimal_from_atomics(decimal_to_atomics(price) / unit * unit).unwrap_or(price)
+}
@@ -0,0 +1,26 @@
+use serde::de::DeserializeOwned;
+
+use prediction::oracle::{HandleMsg, QueryMsg};
+use secretcli::{ClientError, SecretCli};
+
+/// Access to the oracle contract on a chain
+pub trait OracleClient {
+    /// Time of the latest block in seconds
+    fn block_time(&self) -> Result<u64, ClientError>;
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> Result<T, ClientError>;
+    fn execute(&mut self, msg: &HandleMsg) -> Result<(), ClientError>;
+}
+
+impl OracleClient for SecretCli {
+    fn block_time(&self) -> Result<u64, ClientError> {
+        self.latest_block_time()
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> Result<T, ClientError> {
+        self.query_contract(msg)
+    }
+
+    fn execute(&mut self, msg: &HandleMsg) -> Result<(), ClientError> {
+        self.execute_contract(msg)
+    }
+}
@@ -0,0 +1,244 @@
+use cosmwasm_std::{Decimal, HumanAddr};
+use serde::{Deserialize, Serialize};
+
+use crate::aggregate::{aggregate, relative_deviation, truncate};
+use crate::client::OracleClient;
+use crate::source::PriceSource;
+use prediction::asset::AssetInfo;
+use prediction::oracle::{
+    AssetResponse, FeedersResponse, HandleMsg, LatestPriceResponse, QueryMsg,
+};
+use secretcli::ClientError;
+
+/// Asset fed to the oracle and its symbol at the price sources
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FeedAsset {
+    pub asset_info: AssetInfo,
+    pub symbol: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeederConfig {
+    /// Seconds after which a price is fed even if unchanged
+    pub heartbeat: u64,
+    /// Relative change from the last fed price which is fed immediately
+    pub deviation_threshold: Decimal,
+    /// Relative distance from the median above which a source price is rejected
+    pub max_deviation: Decimal,
+    /// Prices required after rejecting outliers
+    pub min_sources: usize,
+    /// Quote currency of the source prices, checked by the oracle if set
+    pub quote_currency: Option<String>,
+}
+
+impl Default for FeederConfig {
+    fn default() -> Self {
+        FeederConfig {
+            heartbeat: 60,
+            deviation_threshold: Decimal::percent(1),
+            max_deviation: Decimal::percent(5),
+            min_sources: 1,
+            quote_currency: None,
+        }
+    }
+}
+
+/// Aggregates source prices and feeds them in one batch to the oracle
+pub struct Feeder<C: OracleClient> {
+    client: C,
+    config: FeederConfig,
+    assets: Vec<FeedAsset>,
+    sources: Vec<Box<dyn PriceSource>>,
+    /// Last fed price and time per asset
+    last_fed: Vec<Option<(Decimal, u64)>>,
+    /// Price decimals of each asset at the oracle
+    decimals: Vec<Option<u8>>,
+}
+
+impl<C: OracleClient> Feeder<C> {
+    pub fn new(
+        client: C,
+        config: FeederConfig,
+        assets: Vec<FeedAsset>,
+        sources: Vec<Box<dyn PriceSource>>,
+    ) -> Self {
+        let last_fed = vec![None; assets.len()];
+        let decimals = vec![None; assets.len()];
+        Feeder {
+            client,
+            config,
+            assets,
+            sources,
+            last_fed,
+            decimals,
+        }
+    }
+
+    pub fn client(&mut self) -> &mut C {
+        &mut self.client
+    }
+
+    /// Fail unless `feeder` is registered as a feeder of every asset
+    pub fn check_registration(&self, feeder: &HumanAddr) -> Result<(), ClientError> {
+        for asset in &self.assets {
+            let registered: FeedersResponse = self.client.query(&QueryMsg::Feeders {
+                asset_info: asset.asset_info.clone(),
+            })?;
+            if !registered.feeders.contains(feeder) {
+                return Err(ClientError::Contract(format!(
+                    "{} is not a feeder of {}",
+                    feeder, asset.symbol
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Feed the assets whose heartbeat elapsed or whose price deviated, returns the fed prices
+    pub fn tick(&mut self) -> Result<Vec<(AssetInfo, Decimal)>, ClientError> {
+        let now = self.client.block_time()?;
+
+        let mut fed = vec![];
+        for index in 0..self.assets.len() {
+            let price = match self.aggregate_price(index, now) {
+                Some(price) => price,
+                None => continue,
+            };
+            let price = match self.asset_decimals(index) {
+                Ok(decimals) => truncate(price, decimals),
+                Err(err) => {
+                    eprintln!("feeder: {}: {}", self.assets[index].symbol, err);
+                    continue;
+                }
+            };
+
+            // Continue from the price on chain after a restart
+            if self.last_fed[index].is_none() {
+                self.last_fed[index] = self
+                    .client
+                    .query::<LatestPriceResponse>(&QueryMsg::LatestPrice {
+                        asset_info: self.assets[index].asset_info.clone(),
+                        time: None,
+                    })
+                    .ok()
+                    .map(|info| (info.price, info.last_updated_time));
+            }
+
+            if self.should_feed(self.last_fed[index], price, now) {
+                fed.push((index, price));
+            }
+        }
+
+        if fed.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let fed = match self.feed(&fed) {
+            Ok(()) => fed,
+            // One rejected price reverts the batch, so the assets are fed one by one
+            Err(ClientError::Contract(msg)) => {
+                eprintln!("feeder: batch rejected: {}", msg);
+                self.feed_each(fed)?
+            }
+            Err(err) => return Err(err),
+        };
+
+        for (index, price) in fed.iter() {
+            self.last_fed[*index] = Some((*price, now));
+        }
+
+        Ok(fed
+            .into_iter()
+            .map(|(index, price)| (self.assets[index].asset_info.clone(), price))
+            .collect())
+    }
+
+    fn feed(&mut self, prices: &[(usize, Decimal)]) -> Result<(), ClientError> {
+        let prices = prices
+            .iter()
+            .map(|(index, price)| (self.assets[*index].asset_info.clone(), *price))
+            .collect();
+        self.client.execute(&HandleMsg::FeedPrice {
+            prices,
+            quote_currency: self.config.quote_currency.clone(),
+        })
+    }
+
+    /// Feed the prices of a rejected batch separately, returns the accepted ones
+    fn feed_each(
+        &mut self,
+        prices: Vec<(usize, Decimal)>,
+    ) -> Result<Vec<(usize, Decimal)>, ClientError> {
+        let mut fed = vec![];
+        for (index, price) in prices {
+            // The decimals of the asset may have changed at the oracle
+            self.decimals[index] = None;
+            let price = match self.asset_decimals(index) {
+                Ok(decimals) => truncate(price, decimals),
+                Err(err) => {
+                    eprintln!("feeder: {}: {}", self.assets[index].symbol, err);
+                    continue;
+                }
+            };
+
+            match self.feed(&[(index, price)]) {
+                Ok(()) => fed.push((index, price)),
+                Err(ClientError::Contract(msg)) => {
+                    eprintln!("feeder: {}: {}", self.assets[index].symbol, msg)
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(fed)
+    }
+
+    /// The oracle rejects prices with more decimals than the asset
+    fn asset_decimals(&mut self, index: usize) -> Result<u8, ClientError> {
+        if let Some(decimals) = self.decimals[index] {
+            return Ok(decimals);
+        }
+
+        let asset: AssetResponse = self.client.query(&QueryMsg::Asset {
+            asset_info: self.assets[index].asset_info.clone(),
+        })?;
+        self.decimals[index] = Some(asset.metadata.decimals);
+        Ok(asset.metadata.decimals)
+    }
+
+    fn aggregate_price(&mut self, index: usize, now: u64) -> Option<Decimal> {
+        let symbol = &self.assets[index].symbol;
+
+        let mut prices = vec![];
+        for source in self.sources.iter_mut() {
+            match source.fetch(symbol, now) {
+                Ok(price) => prices.push(price),
+                Err(err) => eprintln!("feeder: {} {}: {}", source.name(), symbol, err),
+            }
+        }
+
+        let price = aggregate(&prices, self.config.max_deviation, self.config.min_sources);
+        if price.is_none() {
+            eprintln!(
+                "feeder: not enough agreeing prices for {} ({} fetched)",
+                symbol,
+                prices.len()
+            );
+        }
+
+        price
+    }
+
+    fn should_feed(&self, last_fed: Option<(Decimal, u64)>, price: Decimal, now: u64) -> bool {
+        match last_fed {
+            None => true,
+            Some((last_price, last_time)) => {
+                now >= last_time + self.config.heartbeat
+                    || relative_deviation(price, last_price).map_or(true, |deviation| {
+                        deviation >= self.config.deviation_threshold
+                    })
+            }
+        }
+    }
+}
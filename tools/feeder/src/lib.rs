@@ -0,0 +1,7 @@
+pub mod aggregate;
+pub mod client;
+pub mod feeder;
+pub mod source;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,94 @@
+use cosmwasm_std::HumanAddr;
+use std::env;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use feeder::feeder::{FeedAsset, Feeder, FeederConfig};
+use feeder::source::{CommandSource, PriceSource, ReplaySource};
+use secretcli::SecretCli;
+
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn required(name: &str) -> String {
+    var(name).unwrap_or_else(|| panic!("{} is not set", name))
+}
+
+fn parsed<T: FromStr>(name: &str, default: T) -> T {
+    var(name).map_or(default, |value| {
+        value.parse().unwrap_or_else(|_| panic!("invalid {}", name))
+    })
+}
+
+fn sources() -> Vec<Box<dyn PriceSource>> {
+    let mut sources: Vec<Box<dyn PriceSource>> = vec![];
+    for path in var("FEEDER_REPLAY_FILES")
+        .iter()
+        .flat_map(|paths| paths.split(','))
+    {
+        let source = ReplaySource::from_file(path, path).unwrap_or_else(|err| panic!("{}", err));
+        sources.push(Box::new(source));
+    }
+    for command in var("FEEDER_COMMANDS")
+        .iter()
+        .flat_map(|commands| commands.lines())
+    {
+        sources.push(Box::new(CommandSource::new(command, command)));
+    }
+
+    if sources.is_empty() {
+        panic!("neither FEEDER_REPLAY_FILES nor FEEDER_COMMANDS is set");
+    }
+    sources
+}
+
+fn main() {
+    let client = SecretCli {
+        binary: var("FEEDER_SECRETCLI").unwrap_or_else(|| "secretcli".to_string()),
+        contract: required("FEEDER_ORACLE"),
+        from: required("FEEDER_FROM"),
+        node: var("FEEDER_NODE"),
+        chain_id: var("FEEDER_CHAIN_ID"),
+        gas: parsed("FEEDER_GAS", 200_000),
+    };
+
+    let assets: Vec<FeedAsset> =
+        serde_json::from_str(&required("FEEDER_ASSETS")).expect("invalid FEEDER_ASSETS");
+
+    let default = FeederConfig::default();
+    let config = FeederConfig {
+        heartbeat: parsed("FEEDER_HEARTBEAT", default.heartbeat),
+        deviation_threshold: parsed("FEEDER_DEVIATION_THRESHOLD", default.deviation_threshold),
+        max_deviation: parsed("FEEDER_MAX_DEVIATION", default.max_deviation),
+        min_sources: parsed("FEEDER_MIN_SOURCES", default.min_sources),
+        quote_currency: var("FEEDER_QUOTE_CURRENCY"),
+    };
+    let poll_interval = parsed("FEEDER_POLL_INTERVAL", 10);
+
+    let mut feeder = Feeder::new(client, config, assets.clone(), sources());
+    if let Some(address) = var("FEEDER_ADDRESS") {
+        feeder
+            .check_registration(&HumanAddr::from(address))
+            .expect("feeder is not registered");
+    }
+
+    loop {
+        match feeder.tick() {
+            Ok(prices) if !prices.is_empty() => {
+                let prices: Vec<String> = prices
+                    .iter()
+                    .map(|(asset_info, price)| {
+                        let asset = assets.iter().find(|asset| &asset.asset_info == asset_info);
+                        format!("{}={}", asset.map_or("?", |asset| &asset.symbol), price)
+                    })
+                    .collect();
+                println!("feeder: fed {}", prices.join(" "));
+            }
+            Ok(_) => {}
+            Err(err) => eprintln!("feeder: {}", err),
+        }
+        thread::sleep(Duration::from_secs(poll_interval));
+    }
+}
@@ -0,0 +1,110 @@
+use cosmwasm_std::Decimal;
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+use std::str::FromStr;
+
+/// Provider of prices by symbol, such as `SCRT`
+pub trait PriceSource {
+    fn name(&self) -> &str;
+    /// Latest price of the symbol at `time`
+    fn fetch(&mut self, symbol: &str, time: u64) -> Result<Decimal, String>;
+}
+
+/// Prices recorded as `time,symbol,price` lines, replayed by time
+pub struct ReplaySource {
+    name: String,
+    prices: HashMap<String, Vec<(u64, Decimal)>>,
+}
+
+impl ReplaySource {
+    pub fn from_file(name: &str, path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+        ReplaySource::parse(name, &content)
+    }
+
+    /// Empty lines and lines starting with `#` are skipped
+    pub fn parse(name: &str, content: &str) -> Result<Self, String> {
+        let mut prices: HashMap<String, Vec<(u64, Decimal)>> = HashMap::new();
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+            let entry = match fields.as_slice() {
+                [time, symbol, price] => time
+                    .parse::<u64>()
+                    .ok()
+                    .zip(Decimal::from_str(price).ok())
+                    .map(|entry| (symbol.to_string(), entry)),
+                _ => None,
+            };
+
+            match entry {
+                Some((symbol, entry)) => prices.entry(symbol).or_default().push(entry),
+                None => return Err(format!("invalid line {}: {}", index + 1, line)),
+            }
+        }
+
+        for entries in prices.values_mut() {
+            entries.sort_by_key(|(time, _)| *time);
+        }
+
+        Ok(ReplaySource {
+            name: name.to_string(),
+            prices,
+        })
+    }
+}
+
+impl PriceSource for ReplaySource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fetch(&mut self, symbol: &str, time: u64) -> Result<Decimal, String> {
+        self.prices
+            .get(symbol)
+            .and_then(|entries| entries.iter().rev().find(|(at, _)| *at <= time))
+            .map(|(_, price)| *price)
+            .ok_or_else(|| format!("no {} price at {}", symbol, time))
+    }
+}
+
+/// Runs a shell command printing the price, `{symbol}` in the command is replaced
+pub struct CommandSource {
+    name: String,
+    command: String,
+}
+
+impl CommandSource {
+    pub fn new(name: &str, command: &str) -> Self {
+        CommandSource {
+            name: name.to_string(),
+            command: command.to_string(),
+        }
+    }
+}
+
+impl PriceSource for CommandSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fetch(&mut self, symbol: &str, _time: u64) -> Result<Decimal, String> {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(self.command.replace("{symbol}", symbol))
+            .output()
+            .map_err(|err| err.to_string())?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Decimal::from_str(stdout.trim()).map_err(|err| err.to_string())
+    }
+}
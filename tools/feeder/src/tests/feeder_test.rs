@@ -0,0 +1,216 @@
+use cosmwasm_std::{Decimal, HumanAddr};
+use std::str::FromStr;
+
+use crate::aggregate::{aggregate, median, truncate};
+use crate::feeder::{FeedAsset, Feeder, FeederConfig};
+use crate::source::{PriceSource, ReplaySource};
+use crate::tests::mock_chain::{native, MockChain, FEEDER};
+use prediction::oracle::{AssetMetadata, HandleMsg};
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn assets() -> Vec<FeedAsset> {
+    vec![
+        FeedAsset {
+            asset_info: native("uscrt"),
+            symbol: "SCRT".to_string(),
+        },
+        FeedAsset {
+            asset_info: native("uatom"),
+            symbol: "ATOM".to_string(),
+        },
+    ]
+}
+
+/// Replay source with `(offset, symbol, price)` entries relative to `start`
+fn replay(name: &str, start: u64, entries: &[(u64, &str, &str)]) -> Box<dyn PriceSource> {
+    let content: Vec<String> = entries
+        .iter()
+        .map(|(offset, symbol, price)| format!("{},{},{}", start + offset, symbol, price))
+        .collect();
+    Box::new(ReplaySource::parse(name, &content.join("\n")).unwrap())
+}
+
+#[test]
+fn test_aggregate_rejects_outliers() {
+    let prices = [dec("1.00"), dec("1.02"), dec("0.98"), dec("5")];
+    assert_eq!(median(&prices), Some(dec("1.01")));
+    assert_eq!(aggregate(&prices, Decimal::percent(5), 3), Some(dec("1")));
+    assert_eq!(aggregate(&prices, Decimal::percent(5), 4), None);
+    assert_eq!(aggregate(&[], Decimal::percent(5), 0), None);
+
+    assert_eq!(truncate(dec("1.23456789"), 6), dec("1.234567"));
+    assert_eq!(truncate(dec("1.5"), 0), dec("1"));
+}
+
+#[test]
+fn test_replay_source() {
+    let mut source = ReplaySource::parse(
+        "replay",
+        "# time,symbol,price\n20,SCRT,1.5\n10,SCRT,1.2\n\n10,ATOM,30",
+    )
+    .unwrap();
+    assert_eq!(source.name(), "replay");
+    assert!(source.fetch("SCRT", 9).is_err());
+    assert_eq!(source.fetch("SCRT", 15), Ok(dec("1.2")));
+    assert_eq!(source.fetch("SCRT", 25), Ok(dec("1.5")));
+    assert_eq!(source.fetch("ATOM", 25), Ok(dec("30")));
+    assert!(source.fetch("OSMO", 25).is_err());
+
+    let err = ReplaySource::parse("replay", "10,SCRT").err().unwrap();
+    assert_eq!(err, "invalid line 1: 10,SCRT");
+}
+
+#[test]
+fn test_feed_on_deviation_and_heartbeat() {
+    let chain = MockChain::new();
+    let start = chain.app.block().time;
+    let source = replay(
+        "replay",
+        start,
+        &[
+            (0, "SCRT", "1.00"),
+            (0, "ATOM", "30"),
+            (10, "SCRT", "1.005"),
+            (20, "SCRT", "1.02"),
+        ],
+    );
+    let mut feeder = Feeder::new(chain, FeederConfig::default(), assets(), vec![source]);
+
+    // Nothing fed yet
+    let fed = feeder.tick().unwrap();
+    assert_eq!(
+        fed,
+        vec![(native("uscrt"), dec("1")), (native("uatom"), dec("30"))]
+    );
+    assert_eq!(feeder.client().latest_price("uatom").price, dec("30"));
+
+    // Below the deviation threshold
+    feeder.client().app.advance_time(10);
+    assert_eq!(feeder.tick().unwrap(), vec![]);
+
+    feeder.client().app.advance_time(10);
+    assert_eq!(feeder.tick().unwrap(), vec![(native("uscrt"), dec("1.02"))]);
+    assert_eq!(feeder.client().latest_price("uscrt").price, dec("1.02"));
+
+    // The heartbeat of ATOM elapsed
+    feeder.client().app.advance_time(40);
+    assert_eq!(feeder.tick().unwrap(), vec![(native("uatom"), dec("30"))]);
+
+    let now = feeder.client().app.block().time;
+    assert_eq!(feeder.client().latest_price("uatom").last_updated_time, now);
+}
+
+#[test]
+fn test_continue_from_price_on_chain() {
+    let mut chain = MockChain::new();
+    chain.feed_price("uscrt", dec("1"));
+    chain.app.advance_time(30);
+    let start = chain.app.block().time;
+
+    let source = replay("replay", start, &[(0, "SCRT", "1.001")]);
+    let assets = vec![assets().remove(0)];
+    let mut feeder = Feeder::new(chain, FeederConfig::default(), assets, vec![source]);
+    assert_eq!(feeder.tick().unwrap(), vec![]);
+
+    // The heartbeat counts from the last feed on chain
+    feeder.client().app.advance_time(30);
+    assert_eq!(
+        feeder.tick().unwrap(),
+        vec![(native("uscrt"), dec("1.001"))]
+    );
+}
+
+#[test]
+fn test_outlier_source_is_ignored() {
+    let chain = MockChain::new();
+    let start = chain.app.block().time;
+    let sources = vec![
+        replay("first", start, &[(0, "SCRT", "1.00"), (0, "ATOM", "30")]),
+        replay("second", start, &[(0, "SCRT", "1.02"), (0, "ATOM", "30.3")]),
+        replay("broken", start, &[(0, "SCRT", "10"), (0, "ATOM", "300")]),
+    ];
+    let config = FeederConfig {
+        min_sources: 2,
+        ..FeederConfig::default()
+    };
+    let mut feeder = Feeder::new(chain, config, assets(), sources);
+
+    assert_eq!(
+        feeder.tick().unwrap(),
+        vec![
+            (native("uscrt"), dec("1.01")),
+            (native("uatom"), dec("30.15"))
+        ]
+    );
+}
+
+#[test]
+fn test_skip_asset_without_enough_prices() {
+    let chain = MockChain::new();
+    let start = chain.app.block().time;
+    let sources = vec![
+        replay("first", start, &[(0, "SCRT", "1.00"), (0, "ATOM", "30")]),
+        replay("second", start, &[(0, "SCRT", "1.01")]),
+    ];
+    let config = FeederConfig {
+        min_sources: 2,
+        ..FeederConfig::default()
+    };
+    let mut feeder = Feeder::new(chain, config, assets(), sources);
+
+    assert_eq!(
+        feeder.tick().unwrap(),
+        vec![(native("uscrt"), dec("1.005"))]
+    );
+}
+
+#[test]
+fn test_feed_assets_one_by_one_after_rejected_batch() {
+    let chain = MockChain::new();
+    let start = chain.app.block().time;
+    let source = replay(
+        "replay",
+        start,
+        &[
+            (0, "SCRT", "1.00"),
+            (0, "ATOM", "30"),
+            (10, "SCRT", "1.1"),
+            (10, "ATOM", "33.33"),
+        ],
+    );
+    let mut feeder = Feeder::new(chain, FeederConfig::default(), assets(), vec![source]);
+    assert_eq!(feeder.tick().unwrap().len(), 2);
+
+    // SCRT is paused and ATOM loses a decimal after the decimals were cached
+    feeder.client().execute_as_owner(&HandleMsg::Pause {
+        asset_info: Some(native("uscrt")),
+    });
+    feeder.client().execute_as_owner(&HandleMsg::UpdateAsset {
+        asset_info: native("uatom"),
+        feeders: None,
+        heartbeat: None,
+        metadata: Some(AssetMetadata {
+            symbol: "ATOM".to_string(),
+            decimals: 1,
+            quote_currency: "USD".to_string(),
+            description: None,
+        }),
+    });
+
+    feeder.client().app.advance_time(10);
+    assert_eq!(feeder.tick().unwrap(), vec![(native("uatom"), dec("33.3"))]);
+    assert_eq!(feeder.client().latest_price("uatom").price, dec("33.3"));
+}
+
+#[test]
+fn test_check_registration() {
+    let feeder = Feeder::new(MockChain::new(), FeederConfig::default(), assets(), vec![]);
+
+    assert!(feeder.check_registration(&HumanAddr::from(FEEDER)).is_ok());
+    assert!(feeder
+        .check_registration(&HumanAddr::from("other"))
+        .is_err());
+}
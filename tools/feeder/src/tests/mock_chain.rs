@@ -0,0 +1,120 @@
+use cosmwasm_std::{Decimal, HumanAddr};
+use harness::{oracle_contract, App};
+use serde::de::DeserializeOwned;
+
+use crate::client::OracleClient;
+use prediction::{
+    asset::AssetInfo,
+    oracle::{AssetMetadata, HandleMsg, InitMsg, LatestPriceResponse, QueryMsg},
+};
+use secretcli::ClientError;
+
+pub const OWNER: &str = "owner";
+pub const FEEDER: &str = "feeder";
+
+/// Oracle contract on the in-memory chain, the feeder signs as `FEEDER`
+pub struct MockChain {
+    pub app: App,
+    pub oracle: HumanAddr,
+}
+
+impl MockChain {
+    /// Oracle with `uscrt` and `uatom` fed by `FEEDER`
+    pub fn new() -> Self {
+        let mut app = App::new();
+        let code_id = app.store_code(oracle_contract());
+        let oracle = app
+            .instantiate(
+                code_id,
+                OWNER,
+                &InitMsg {
+                    owner: HumanAddr::from(OWNER),
+                    history_retention: None,
+                    quorum: None,
+                    max_price_age: None,
+                    guardian: None,
+                    restrict_reads: None,
+                    access_fee: None,
+                },
+                &[],
+            )
+            .unwrap();
+
+        for denom in &["uscrt", "uatom"] {
+            app.execute(
+                OWNER,
+                &oracle,
+                &HandleMsg::RegisterAsset {
+                    asset_info: native(denom),
+                    feeder: HumanAddr::from(FEEDER),
+                    heartbeat: None,
+                    metadata: AssetMetadata {
+                        symbol: denom[1..].to_uppercase(),
+                        decimals: 6,
+                        quote_currency: "USD".to_string(),
+                        description: None,
+                    },
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        MockChain { app, oracle }
+    }
+
+    pub fn latest_price(&self, denom: &str) -> LatestPriceResponse {
+        self.app
+            .query(
+                &self.oracle,
+                &QueryMsg::LatestPrice {
+                    asset_info: native(denom),
+                    time: None,
+                },
+            )
+            .unwrap()
+    }
+
+    pub fn feed_price(&mut self, denom: &str, price: Decimal) {
+        self.app
+            .execute(
+                FEEDER,
+                &self.oracle,
+                &HandleMsg::FeedPrice {
+                    prices: vec![(native(denom), price)],
+                    quote_currency: None,
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    pub fn execute_as_owner(&mut self, msg: &HandleMsg) {
+        self.app.execute(OWNER, &self.oracle, msg, &[]).unwrap();
+    }
+}
+
+pub fn native(denom: &str) -> AssetInfo {
+    AssetInfo::NativeToken {
+        denom: denom.to_string(),
+    }
+}
+
+impl OracleClient for MockChain {
+    fn block_time(&self) -> Result<u64, ClientError> {
+        Ok(self.app.block().time)
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> Result<T, ClientError> {
+        self.app
+            .query(&self.oracle, msg)
+            .map_err(|err| ClientError::Contract(err.to_string()))
+    }
+
+    fn execute(&mut self, msg: &HandleMsg) -> Result<(), ClientError> {
+        self.app
+            .execute(FEEDER, &self.oracle, msg, &[])
+            .map(|_| ())
+            .map_err(|err| ClientError::Contract(err.to_string()))
+    }
+}
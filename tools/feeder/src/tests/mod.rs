@@ -0,0 +1,2 @@
+pub mod feeder_test;
+pub mod mock_chain;
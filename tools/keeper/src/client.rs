@@ -0,0 +1,27 @@
+use serde::de::DeserializeOwned;
+
+use prediction::prediction::{HandleMsg, QueryMsg};
+pub use secretcli::ClientError;
+use secretcli::SecretCli;
+
+/// Access to the prediction contract on a chain
+pub trait ChainClient {
+    /// Time of the latest block in seconds
+    fn block_time(&self) -> Result<u64, ClientError>;
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> Result<T, ClientError>;
+    fn execute(&mut self, msg: &HandleMsg) -> Result<(), ClientError>;
+}
+
+impl ChainClient for SecretCli {
+    fn block_time(&self) -> Result<u64, ClientError> {
+        self.latest_block_time()
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> Result<T, ClientError> {
+        self.query_contract(msg)
+    }
+
+    fn execute(&mut self, msg: &HandleMsg) -> Result<(), ClientError> {
+        self.execute_contract(msg)
+    }
+}
@@ -0,0 +1,141 @@
+use cosmwasm_std::{Decimal, Uint128};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+use crate::client::{ChainClient, ClientError};
+use crate::metrics::{MarketStatus, Metrics};
+use prediction::prediction::{ConfigResponse, HandleMsg, QueryMsg, State};
+
+/// Fields of a prediction round used by the keeper
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RoundInfo {
+    pub start_time: u64,
+    pub lock_time: u64,
+    pub end_time: u64,
+    pub open_price: Option<Decimal>,
+    pub close_price: Option<Decimal>,
+    pub is_genesis: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeeperConfig {
+    /// Seconds before retrying a failed execution
+    pub retry_interval: u64,
+    /// Seconds between checks of a paused, stuck or expired market
+    pub poll_interval: u64,
+}
+
+impl Default for KeeperConfig {
+    fn default() -> Self {
+        KeeperConfig {
+            retry_interval: 3,
+            poll_interval: 30,
+        }
+    }
+}
+
+/// Executes the progressing round between its end time and the end of the grace interval
+pub struct Keeper<C: ChainClient> {
+    client: C,
+    config: KeeperConfig,
+    metrics: Arc<Mutex<Metrics>>,
+}
+
+impl<C: ChainClient> Keeper<C> {
+    pub fn new(client: C, config: KeeperConfig) -> Self {
+        Keeper {
+            client,
+            config,
+            metrics: Arc::new(Mutex::new(Metrics::default())),
+        }
+    }
+
+    pub fn client(&mut self) -> &mut C {
+        &mut self.client
+    }
+
+    pub fn metrics(&self) -> Arc<Mutex<Metrics>> {
+        self.metrics.clone()
+    }
+
+    /// Check the market once, returns the seconds to wait before the next check
+    pub fn tick(&mut self) -> u64 {
+        match self.try_tick() {
+            Ok(wait) => wait,
+            Err(err) => {
+                eprintln!("keeper: {}", err);
+                self.metrics.lock().unwrap().errors += 1;
+                self.config.retry_interval
+            }
+        }
+    }
+
+    fn try_tick(&mut self) -> Result<u64, ClientError> {
+        let config: ConfigResponse = self.client.query(&QueryMsg::Config {})?;
+        let state: State = self.client.query(&QueryMsg::State {})?;
+        if state.paused {
+            self.set_status(MarketStatus::Paused);
+            return Ok(self.config.poll_interval);
+        }
+
+        // The progressing round is the one before the betting round
+        let epoch = match state.epoch.u128().checked_sub(1) {
+            Some(epoch) => Uint128(epoch),
+            None => {
+                self.set_status(MarketStatus::NotStarted);
+                return Ok(self.config.poll_interval);
+            }
+        };
+        let round: RoundInfo = self.client.query(&QueryMsg::Round { epoch })?;
+        let now = self.client.block_time()?;
+
+        if now < round.end_time {
+            self.set_status(MarketStatus::Running);
+            return Ok(round.end_time - now);
+        }
+
+        if now > round.end_time + config.grace_interval {
+            eprintln!(
+                "keeper: round {} expired, the market must be restarted",
+                epoch
+            );
+            self.set_status(MarketStatus::Expired);
+            return Ok(self.config.poll_interval);
+        }
+
+        match self.client.execute(&HandleMsg::ExecuteRound {}) {
+            Ok(()) => {
+                let mut metrics = self.metrics.lock().unwrap();
+                metrics.rounds_executed += 1;
+                metrics.last_executed_epoch = epoch.u128();
+                metrics.last_execution_time = now;
+                metrics.market_status = MarketStatus::Running;
+                Ok(0)
+            }
+            // The oracle has not been fed since the round started, or its feed is stale or
+            // paused, wait for the feeder
+            Err(ClientError::Contract(msg)) if is_price_pending(&msg) => {
+                self.metrics.lock().unwrap().price_retries += 1;
+                Ok(self.config.retry_interval)
+            }
+            Err(ClientError::Contract(msg)) => {
+                eprintln!("keeper: round {} can not be executed: {}", epoch, msg);
+                let mut metrics = self.metrics.lock().unwrap();
+                metrics.errors += 1;
+                metrics.market_status = MarketStatus::Stuck;
+                Ok(self.config.poll_interval)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn set_status(&self, status: MarketStatus) {
+        self.metrics.lock().unwrap().market_status = status;
+    }
+}
+
+fn is_price_pending(msg: &str) -> bool {
+    ["Price not updated", "Price is stale", "Oracle feed paused"]
+        .iter()
+        .any(|pending| msg.contains(pending))
+}
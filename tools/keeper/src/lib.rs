@@ -0,0 +1,6 @@
+pub mod client;
+pub mod keeper;
+pub mod metrics;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,37 @@
+use std::env;
+use std::thread;
+use std::time::Duration;
+
+use keeper::keeper::{Keeper, KeeperConfig};
+use keeper::metrics::serve;
+use secretcli::SecretCli;
+
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn required(name: &str) -> String {
+    var(name).unwrap_or_else(|| panic!("{} is not set", name))
+}
+
+fn main() {
+    let client = SecretCli {
+        binary: var("KEEPER_SECRETCLI").unwrap_or_else(|| "secretcli".to_string()),
+        contract: required("KEEPER_CONTRACT"),
+        from: required("KEEPER_FROM"),
+        node: var("KEEPER_NODE"),
+        chain_id: var("KEEPER_CHAIN_ID"),
+        gas: var("KEEPER_GAS").map_or(300_000, |gas| gas.parse().expect("invalid KEEPER_GAS")),
+    };
+
+    let mut keeper = Keeper::new(client, KeeperConfig::default());
+
+    let metrics_addr = var("KEEPER_METRICS_ADDR").unwrap_or_else(|| "127.0.0.1:9100".to_string());
+    serve(&metrics_addr, keeper.metrics()).expect("failed to serve metrics");
+
+    loop {
+        let wait = keeper.tick();
+        // Blocks are not produced exactly at the round end, never spin
+        thread::sleep(Duration::from_secs(wait.max(1)));
+    }
+}
@@ -0,0 +1,122 @@
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MarketStatus {
+    Running,
+    Paused,
+    /// The genesis round has not been started
+    NotStarted,
+    /// The round can not be executed for another reason than a missing, stale or paused price
+    Stuck,
+    /// The grace interval passed, the owner has to restart the market
+    Expired,
+}
+
+impl MarketStatus {
+    const ALL: [MarketStatus; 5] = [
+        MarketStatus::Running,
+        MarketStatus::Paused,
+        MarketStatus::NotStarted,
+        MarketStatus::Stuck,
+        MarketStatus::Expired,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            MarketStatus::Running => "running",
+            MarketStatus::Paused => "paused",
+            MarketStatus::NotStarted => "not_started",
+            MarketStatus::Stuck => "stuck",
+            MarketStatus::Expired => "expired",
+        }
+    }
+}
+
+impl Default for MarketStatus {
+    fn default() -> Self {
+        MarketStatus::Running
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metrics {
+    pub rounds_executed: u64,
+    pub price_retries: u64,
+    pub errors: u64,
+    pub last_executed_epoch: u128,
+    pub last_execution_time: u64,
+    pub market_status: MarketStatus,
+}
+
+impl Metrics {
+    /// Prometheus text format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, value: String| {
+            out.push_str(&format!("# TYPE {} {}\n{} {}\n", name, kind, name, value));
+        };
+
+        metric(
+            "keeper_rounds_executed_total",
+            "counter",
+            self.rounds_executed.to_string(),
+        );
+        metric(
+            "keeper_price_retries_total",
+            "counter",
+            self.price_retries.to_string(),
+        );
+        metric("keeper_errors_total", "counter", self.errors.to_string());
+        metric(
+            "keeper_last_executed_epoch",
+            "gauge",
+            self.last_executed_epoch.to_string(),
+        );
+        metric(
+            "keeper_last_execution_time",
+            "gauge",
+            self.last_execution_time.to_string(),
+        );
+
+        out.push_str("# TYPE keeper_market_status gauge\n");
+        for status in MarketStatus::ALL.iter() {
+            out.push_str(&format!(
+                "keeper_market_status{{status=\"{}\"}} {}\n",
+                status.label(),
+                (*status == self.market_status) as u8
+            ));
+        }
+
+        out
+    }
+}
+
+/// Answer every http request on `addr` with the rendered metrics
+pub fn serve(addr: &str, metrics: Arc<Mutex<Metrics>>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+
+            let body = metrics.lock().unwrap().render();
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use cosmwasm_std::Uint128;
+
+use crate::client::ChainClient;
+use crate::keeper::{Keeper, KeeperConfig, RoundInfo};
+use crate::metrics::MarketStatus;
+use crate::tests::mock_chain::{MockChain, GRACE_INTERVAL, INTERVAL};
+use prediction::prediction::{HandleMsg, QueryMsg, State};
+
+fn start_keeper() -> Keeper<MockChain> {
+    let mut chain = MockChain::new();
+    chain.feed_price(3);
+    chain.execute_as_owner(&HandleMsg::StartGenesisRound {});
+
+    Keeper::new(chain, KeeperConfig::default())
+}
+
+fn round(keeper: &mut Keeper<MockChain>, epoch: u128) -> RoundInfo {
+    keeper
+        .client()
+        .query(&QueryMsg::Round {
+            epoch: Uint128(epoch),
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_execute_round_at_end_time() {
+    let mut keeper = start_keeper();
+
+    let end_time = round(&mut keeper, 1).end_time;
+    let now = keeper.client().block_time().unwrap();
+    assert_eq!(keeper.tick(), end_time - now);
+    assert_eq!(keeper.metrics().lock().unwrap().rounds_executed, 0);
+
+    keeper.client().app.advance_to(end_time);
+    keeper.client().feed_price(5);
+    assert_eq!(keeper.tick(), 0);
+
+    let state: State = keeper.client().query(&QueryMsg::State {}).unwrap();
+    assert_eq!(state.epoch, Uint128(3));
+
+    // The next round is waited for
+    let end_time = round(&mut keeper, 2).end_time;
+    assert_eq!(
+        keeper.tick(),
+        end_time - keeper.client().block_time().unwrap()
+    );
+
+    let metrics = keeper.metrics().lock().unwrap().clone();
+    assert_eq!(metrics.rounds_executed, 1);
+    assert_eq!(metrics.last_executed_epoch, 1);
+    assert_eq!(metrics.market_status, MarketStatus::Running);
+    assert!(metrics
+        .render()
+        .contains("keeper_market_status{status=\"running\"} 1\n"));
+}
+
+#[test]
+fn test_retry_until_price_updated() {
+    let mut chain = MockChain::new();
+    chain.feed_price(3);
+    chain.app.advance_time(10);
+    chain.execute_as_owner(&HandleMsg::StartGenesisRound {});
+    let mut keeper = Keeper::new(chain, KeeperConfig::default());
+
+    let end_time = round(&mut keeper, 1).end_time;
+    keeper.client().app.advance_to(end_time);
+    assert_eq!(keeper.tick(), 0);
+
+    // The second round started after the last feed
+    let end_time = round(&mut keeper, 2).end_time;
+    keeper.client().app.advance_to(end_time);
+    assert_eq!(keeper.tick(), KeeperConfig::default().retry_interval);
+    assert_eq!(keeper.metrics().lock().unwrap().price_retries, 1);
+
+    keeper.client().app.advance_time(1);
+    keeper.client().feed_price(4);
+    assert_eq!(keeper.tick(), 0);
+
+    let metrics = keeper.metrics().lock().unwrap().clone();
+    assert_eq!(metrics.rounds_executed, 2);
+    assert_eq!(metrics.last_executed_epoch, 2);
+}
+
+#[test]
+fn test_retry_while_feed_paused() {
+    let mut keeper = start_keeper();
+
+    let end_time = round(&mut keeper, 1).end_time;
+    keeper.client().app.advance_to(end_time);
+    keeper.client().feed_price(5);
+    keeper.client().pause_feed(true);
+    assert_eq!(keeper.tick(), KeeperConfig::default().retry_interval);
+
+    let metrics = keeper.metrics().lock().unwrap().clone();
+    assert_eq!(metrics.price_retries, 1);
+    assert_eq!(metrics.errors, 0);
+    assert_eq!(metrics.market_status, MarketStatus::Running);
+
+    keeper.client().pause_feed(false);
+    assert_eq!(keeper.tick(), 0);
+    assert_eq!(keeper.metrics().lock().unwrap().rounds_executed, 1);
+}
+
+#[test]
+fn test_detect_expired_and_paused_market() {
+    let mut keeper = start_keeper();
+
+    let end_time = round(&mut keeper, 1).end_time;
+    keeper
+        .client()
+        .app
+        .advance_to(end_time + GRACE_INTERVAL + 1);
+    keeper.client().feed_price(5);
+    assert_eq!(keeper.tick(), KeeperConfig::default().poll_interval);
+
+    let metrics = keeper.metrics().lock().unwrap().clone();
+    assert_eq!(metrics.rounds_executed, 0);
+    assert_eq!(metrics.market_status, MarketStatus::Expired);
+
+    keeper.client().execute_as_owner(&HandleMsg::Pause {});
+    keeper.tick();
+    assert_eq!(
+        keeper.metrics().lock().unwrap().market_status,
+        MarketStatus::Paused
+    );
+
+    // Restarted by the owner
+    keeper
+        .client()
+        .execute_as_owner(&HandleMsg::StartGenesisRound {});
+    assert_eq!(keeper.tick(), INTERVAL);
+    assert_eq!(
+        keeper.metrics().lock().unwrap().market_status,
+        MarketStatus::Running
+    );
+}
@@ -0,0 +1,151 @@
+use cosmwasm_std::{Binary, Decimal, HumanAddr};
+use harness::{oracle_contract, prediction_contract, App};
+use serde::de::DeserializeOwned;
+
+use crate::client::{ChainClient, ClientError};
+use prediction::{
+    asset::AssetInfo,
+    oracle::{AssetMetadata, HandleMsg as OracleHandleMsg, InitMsg as OracleInitMsg},
+    prediction::{HandleMsg, InitMsg, QueryMsg},
+};
+
+pub const OWNER: &str = "owner";
+pub const OPERATOR: &str = "operator";
+pub const INTERVAL: u64 = 30;
+pub const GRACE_INTERVAL: u64 = 15;
+
+/// Oracle and prediction contracts on the in-memory chain, the keeper signs as operator
+pub struct MockChain {
+    pub app: App,
+    pub oracle: HumanAddr,
+    pub prediction: HumanAddr,
+}
+
+impl MockChain {
+    pub fn new() -> Self {
+        let mut app = App::new();
+        let oracle_code_id = app.store_code(oracle_contract());
+        let prediction_code_id = app.store_code(prediction_contract());
+
+        let oracle = app
+            .instantiate(
+                oracle_code_id,
+                OWNER,
+                &OracleInitMsg {
+                    owner: HumanAddr::from(OWNER),
+                    history_retention: None,
+                    quorum: None,
+                    max_price_age: None,
+                    guardian: None,
+                    restrict_reads: None,
+                    access_fee: None,
+                },
+                &[],
+            )
+            .unwrap();
+        app.execute(
+            OWNER,
+            &oracle,
+            &OracleHandleMsg::RegisterAsset {
+                asset_info: bet_asset(),
+                feeder: HumanAddr::from(OWNER),
+                heartbeat: None,
+                metadata: AssetMetadata {
+                    symbol: "SCRT".to_string(),
+                    decimals: 6,
+                    quote_currency: "USD".to_string(),
+                    description: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+        let prediction = app
+            .instantiate(
+                prediction_code_id,
+                OWNER,
+                &InitMsg {
+                    operator_addr: HumanAddr::from(OPERATOR),
+                    treasury_addr: HumanAddr::from("treasury"),
+                    bet_asset: bet_asset(),
+                    oracle_code_hash: app.code_hash(&oracle).unwrap(),
+                    oracle_addr: oracle.clone(),
+                    fee_rate: Decimal::percent(3),
+                    interval: INTERVAL,
+                    grace_interval: GRACE_INTERVAL,
+                    prng_seed: Binary::from("seed".as_bytes()),
+                    bucket_bounds: None,
+                    commit_reveal: None,
+                    timelock: None,
+                    quote_currency: None,
+                    auto_settle: None,
+                    import: None,
+                },
+                &[],
+            )
+            .unwrap();
+
+        MockChain {
+            app,
+            oracle,
+            prediction,
+        }
+    }
+
+    pub fn feed_price(&mut self, price: u64) {
+        self.app
+            .execute(
+                OWNER,
+                &self.oracle,
+                &OracleHandleMsg::FeedPrice {
+                    prices: vec![(bet_asset(), Decimal::from_ratio(price, 1u64))],
+                    quote_currency: None,
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    pub fn pause_feed(&mut self, paused: bool) {
+        let msg = if paused {
+            OracleHandleMsg::Pause {
+                asset_info: Some(bet_asset()),
+            }
+        } else {
+            OracleHandleMsg::Unpause {
+                asset_info: Some(bet_asset()),
+            }
+        };
+        self.app.execute(OWNER, &self.oracle, &msg, &[]).unwrap();
+    }
+
+    pub fn execute_as_owner(&mut self, msg: &HandleMsg) {
+        self.app.execute(OWNER, &self.prediction, msg, &[]).unwrap();
+    }
+}
+
+fn bet_asset() -> AssetInfo {
+    AssetInfo::NativeToken {
+        denom: "uscrt".to_string(),
+    }
+}
+
+impl ChainClient for MockChain {
+    fn block_time(&self) -> Result<u64, ClientError> {
+        Ok(self.app.block().time)
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> Result<T, ClientError> {
+        self.app
+            .query(&self.prediction, msg)
+            .map_err(|err| ClientError::Contract(err.to_string()))
+    }
+
+    fn execute(&mut self, msg: &HandleMsg) -> Result<(), ClientError> {
+        self.app
+            .execute(OPERATOR, &self.prediction, msg, &[])
+            .map(|_| ())
+            .map_err(|err| ClientError::Contract(err.to_string()))
+    }
+}
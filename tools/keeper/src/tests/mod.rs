@@ -0,0 +1,2 @@
+pub mod keeper_test;
+pub mod mock_chain;